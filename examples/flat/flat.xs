@@ -1,5 +1,5 @@
-const mvp: mat4x4;
-const flatColor: vec4;
+uniform mvp: mat4x4;
+uniform flatColor: vec4;
 
 struct VertexInput {
     position: vec3,
@@ -9,7 +9,7 @@ struct VertexOutput {
     position: vec4,
 }
 
-fn vertexShader(intput: VertexInput) -> VertexOutput {
+fn vertexShader(input: VertexInput) -> VertexOutput {
     let output = VertexOutput {
         position: mvp * vec4(input.position, 1.0),
     };
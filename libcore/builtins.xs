@@ -0,0 +1,20 @@
+builtin position: vec4 in(tess_control, tess_eval, geometry) out(vertex, tess_control, tess_eval, geometry);
+builtin point_size: f32 out(vertex, tess_eval, geometry);
+
+builtin vertex_index: u32 in(vertex);
+builtin instance_index: u32 in(vertex);
+
+builtin invocation_id: u32 in(tess_control, geometry);
+builtin primitive_id: u32 in(tess_control, tess_eval, geometry, fragment);
+builtin tess_coord: vec3 in(tess_eval);
+
+builtin frag_coord: vec4 in(fragment);
+builtin front_facing: bool in(fragment);
+builtin frag_depth: f32 out(fragment);
+
+builtin global_invocation_id: uvec3 in(compute);
+builtin local_invocation_id: uvec3 in(compute);
+builtin local_invocation_index: u32 in(compute);
+builtin workgroup_id: uvec3 in(compute);
+
+builtin fn max(a: f32, b: f32) -> f32;
@@ -8,6 +8,8 @@ operator / (lhs: f32, rhs: f32) -> f32;
 implicit cast f32 -> f64;
 explicit cast f32 -> i32;
 explicit cast f32 -> i64;
+explicit cast f32 -> u32;
+explicit cast f32 -> u64;
 
 primitive type f64;
 operator + (lhs: f64, rhs: f64) -> f64;
@@ -17,25 +19,76 @@ operator / (lhs: f64, rhs: f64) -> f64;
 explicit cast f64 -> f32;
 explicit cast f64 -> i32;
 explicit cast f64 -> i64;
+explicit cast f64 -> u32;
+explicit cast f64 -> u64;
 
 primitive type i32;
 operator + (lhs: i32, rhs: i32) -> i32;
 operator - (lhs: i32, rhs: i32) -> i32;
 operator * (lhs: i32, rhs: i32) -> i32;
 operator / (lhs: i32, rhs: i32) -> i32;
+operator & (lhs: i32, rhs: i32) -> i32;
+operator | (lhs: i32, rhs: i32) -> i32;
+operator ^ (lhs: i32, rhs: i32) -> i32;
+operator << (lhs: i32, rhs: i32) -> i32;
+operator >> (lhs: i32, rhs: i32) -> i32;
 implicit cast i32 -> i64;
 explicit cast i32 -> f32;
 explicit cast i32 -> f64;
+explicit cast i32 -> u32;
+explicit cast i32 -> u64;
 
 primitive type i64;
 operator + (lhs: i64, rhs: i64) -> i64;
 operator - (lhs: i64, rhs: i64) -> i64;
 operator * (lhs: i64, rhs: i64) -> i64;
 operator / (lhs: i64, rhs: i64) -> i64;
+operator & (lhs: i64, rhs: i64) -> i64;
+operator | (lhs: i64, rhs: i64) -> i64;
+operator ^ (lhs: i64, rhs: i64) -> i64;
+operator << (lhs: i64, rhs: i64) -> i64;
+operator >> (lhs: i64, rhs: i64) -> i64;
 explicit cast i64 -> f32;
+explicit cast i64 -> f64;
 explicit cast i64 -> i32;
-explicit cast i64 -> i64;
+explicit cast i64 -> u32;
+explicit cast i64 -> u64;
 
+primitive type u32;
+operator + (lhs: u32, rhs: u32) -> u32;
+operator - (lhs: u32, rhs: u32) -> u32;
+operator * (lhs: u32, rhs: u32) -> u32;
+operator / (lhs: u32, rhs: u32) -> u32;
+operator & (lhs: u32, rhs: u32) -> u32;
+operator | (lhs: u32, rhs: u32) -> u32;
+operator ^ (lhs: u32, rhs: u32) -> u32;
+operator << (lhs: u32, rhs: u32) -> u32;
+operator >> (lhs: u32, rhs: u32) -> u32;
+implicit cast u32 -> i64;
+implicit cast u32 -> u64;
+explicit cast u32 -> f32;
+explicit cast u32 -> f64;
+explicit cast u32 -> i32;
+
+primitive type u64;
+operator + (lhs: u64, rhs: u64) -> u64;
+operator - (lhs: u64, rhs: u64) -> u64;
+operator * (lhs: u64, rhs: u64) -> u64;
+operator / (lhs: u64, rhs: u64) -> u64;
+operator & (lhs: u64, rhs: u64) -> u64;
+operator | (lhs: u64, rhs: u64) -> u64;
+operator ^ (lhs: u64, rhs: u64) -> u64;
+operator << (lhs: u64, rhs: u64) -> u64;
+operator >> (lhs: u64, rhs: u64) -> u64;
+explicit cast u64 -> f32;
+explicit cast u64 -> f64;
+explicit cast u64 -> i32;
+explicit cast u64 -> i64;
+explicit cast u64 -> u32;
+
+primitive type vec2;
 primitive type vec3;
 primitive type vec4;
+primitive type uvec3;
+primitive type mat3x3;
 primitive type mat4x4;
@@ -0,0 +1,57 @@
+struct Rgb {
+    r: f32,
+    g: f32,
+    b: f32,
+}
+
+struct YCoCg {
+    y: f32,
+    co: f32,
+    cg: f32,
+}
+
+fn luminance(color: vec3) -> f32 {
+    let r = 0.2126 * color.x;
+    let g = 0.7152 * color.y;
+    let b = 0.0722 * color.z;
+    return r + g + b;
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    let quadratic = 0.682171111 * value;
+    let linear = quadratic + 0.305306011;
+    let inner = value * linear;
+    let constant = inner + 0.012522878;
+    return value * constant;
+}
+
+fn reinhard(value: f32) -> f32 {
+    let denominator = 1.0 + value;
+    return value / denominator;
+}
+
+fn rgb_to_ycocg(color: vec3) -> YCoCg {
+    let r = color.x;
+    let g = color.y;
+    let b = color.z;
+    let quarter_r = 0.25 * r;
+    let half_g = 0.5 * g;
+    let quarter_b = 0.25 * b;
+    let half_r = 0.5 * r;
+    let half_b = 0.5 * b;
+    let y = quarter_r + half_g + quarter_b;
+    let co = half_r - half_b;
+    let cg_base = half_g - quarter_r;
+    let cg = cg_base - quarter_b;
+    return YCoCg { y: y, co: co, cg: cg, };
+}
+
+fn ycocg_to_rgb(color: YCoCg) -> Rgb {
+    let tmp = color.y - color.cg;
+    let r = tmp + color.co;
+    let g = color.y + color.cg;
+    let b = tmp - color.co;
+    return Rgb { r: r, g: g, b: b, };
+}
+
+export *;
@@ -0,0 +1,49 @@
+import { PI, INV_PI, dot } from 'std/math';
+
+fn lambert(normal: vec3, light_direction: vec3) -> f32 {
+    let n_dot_l = max(dot(normal, light_direction), 0.0);
+    return n_dot_l * INV_PI;
+}
+
+fn blinn_phong_schlick(n_dot_h: f32, shininess: f32) -> f32 {
+    let scaled = shininess * n_dot_h;
+    let falloff = shininess - scaled;
+    let denominator = falloff + n_dot_h;
+    return n_dot_h / denominator;
+}
+
+fn ggx_distribution(n_dot_h: f32, roughness: f32) -> f32 {
+    let alpha = roughness * roughness;
+    let alpha2 = alpha * alpha;
+    let n_dot_h2 = n_dot_h * n_dot_h;
+    let alpha2_minus_one = alpha2 - 1.0;
+    let scaled = n_dot_h2 * alpha2_minus_one;
+    let d = scaled + 1.0;
+    let denominator = PI * d * d;
+    return alpha2 / denominator;
+}
+
+fn ggx_geometry(n_dot_v: f32, n_dot_l: f32, roughness: f32) -> f32 {
+    let r = roughness + 1.0;
+    let r2 = r * r;
+    let k = r2 / 8.0;
+    let one_minus_k = 1.0 - k;
+    let view_scaled = n_dot_v * one_minus_k;
+    let view_denominator = view_scaled + k;
+    let view = n_dot_v / view_denominator;
+    let light_scaled = n_dot_l * one_minus_k;
+    let light_denominator = light_scaled + k;
+    let light = n_dot_l / light_denominator;
+    return view * light;
+}
+
+fn fresnel_schlick(cos_theta: f32, f0: f32) -> f32 {
+    let one_minus_cos = 1.0 - cos_theta;
+    let p2 = one_minus_cos * one_minus_cos;
+    let p5 = p2 * p2 * one_minus_cos;
+    let scale = 1.0 - f0;
+    let weighted = scale * p5;
+    return f0 + weighted;
+}
+
+export { lambert, blinn_phong_schlick, ggx_distribution, ggx_geometry, fresnel_schlick };
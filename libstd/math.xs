@@ -0,0 +1,27 @@
+const PI: f32 = 3.14159265;
+const TAU: f32 = 6.28318531;
+const HALF_PI: f32 = 1.57079633;
+const INV_PI: f32 = 0.31830989;
+const E: f32 = 2.71828183;
+const SQRT2: f32 = 1.41421356;
+
+fn dot(a: vec3, b: vec3) -> f32 {
+    let x = a.x * b.x;
+    let y = a.y * b.y;
+    let z = a.z * b.z;
+    return x + y + z;
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    let delta = b - a;
+    let offset = delta * t;
+    return a + offset;
+}
+
+fn hermite(t: f32) -> f32 {
+    let twice = 2.0 * t;
+    let weight = 3.0 - twice;
+    return t * t * weight;
+}
+
+export *;
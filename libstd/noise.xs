@@ -0,0 +1,35 @@
+import { lerp, hermite } from 'std/math';
+
+fn pcg_hash(value: u32) -> u32 {
+    let scaled = value * 747796405u;
+    let state = scaled + 2891336453u;
+    let high = state >> 28u;
+    let shift = high + 4u;
+    let shifted = state >> shift;
+    let mixed = shifted ^ state;
+    let word = mixed * 277803737u;
+    let tail = word >> 22u;
+    return tail ^ word;
+}
+
+fn hash2(x: u32, y: u32) -> u32 {
+    let hashed_x = pcg_hash(x);
+    let combined = hashed_x + y;
+    return pcg_hash(combined);
+}
+
+fn hash3(x: u32, y: u32, z: u32) -> u32 {
+    let hashed_xy = hash2(x, y);
+    let combined = hashed_xy + z;
+    return pcg_hash(combined);
+}
+
+fn value_noise(v00: f32, v10: f32, v01: f32, v11: f32, fx: f32, fy: f32) -> f32 {
+    let tx = hermite(fx);
+    let ty = hermite(fy);
+    let bottom = lerp(v00, v10, tx);
+    let top = lerp(v01, v11, tx);
+    return lerp(bottom, top, ty);
+}
+
+export { pcg_hash, hash2, hash3, value_noise };
@@ -0,0 +1,33 @@
+fn pack_u16x2(low: u32, high: u32) -> u32 {
+    let masked_low = low & 0xFFFFu;
+    let masked_high = high & 0xFFFFu;
+    let shifted_high = masked_high << 16u;
+    return shifted_high | masked_low;
+}
+
+fn unpack_u16_low(packed: u32) -> u32 {
+    return packed & 0xFFFFu;
+}
+
+fn unpack_u16_high(packed: u32) -> u32 {
+    return packed >> 16u;
+}
+
+fn pack_u8x4(x: u32, y: u32, z: u32, w: u32) -> u32 {
+    let masked_x = x & 0xFFu;
+    let masked_y = y & 0xFFu;
+    let masked_z = z & 0xFFu;
+    let masked_w = w & 0xFFu;
+    let shifted_y = masked_y << 8u;
+    let shifted_z = masked_z << 16u;
+    let shifted_w = masked_w << 24u;
+    return masked_x | shifted_y | shifted_z | shifted_w;
+}
+
+fn unpack_u8(packed: u32, index: u32) -> u32 {
+    let shift = index * 8u;
+    let shifted = packed >> shift;
+    return shifted & 0xFFu;
+}
+
+export *;
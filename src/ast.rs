@@ -2,6 +2,8 @@ use ::std::fmt;
 use ::std::str;
 use ::nom_locate::LocatedSpan;
 use ::type_system::type_environment::TypeReference;
+use ::type_system::constant_value::ConstantValue;
+use ::type_system::symbol_table::SymbolKind;
 
 // TODO refactor all Vec<ItemKind> to Ast
 pub type Ast = Vec<ItemKind>;
@@ -100,10 +102,61 @@ impl Identifier {
 
 type TypeIdentifier = Identifier;
 
+/// type of a struct member, function argument or return value, these can be arrays like `[Light; MAX_LIGHTS]`
+#[derive(Debug, Eq, PartialEq)]
+pub struct TypeName {
+    pub span: Span,
+    /// spelling of the type, the length of an array is replaced by its value once folded like `[Light; 4]`
+    pub name: String,
+    pub array: Option<Box<ArrayTypeName>>,
+}
+
+impl_spanned!(TypeName);
+
+impl TypeName {
+    pub fn new(name: &str, span: Span) -> TypeName {
+        TypeName {
+            span: span,
+            name: name.to_string(),
+            array: None,
+        }
+    }
+
+    pub fn from_identifier(identifier: Identifier) -> TypeName {
+        TypeName {
+            span: identifier.span,
+            name: identifier.name,
+            array: None,
+        }
+    }
+
+    pub fn new_array(name: &str, span: Span, element_type_name: TypeName, length: ExpressionStatement) -> TypeName {
+        TypeName {
+            span: span,
+            name: name.to_string(),
+            array: Some(Box::new(ArrayTypeName {
+                element_type_name: element_type_name,
+                length: length,
+            })),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ArrayTypeName {
+    pub element_type_name: TypeName,
+    /// constant expression, folded by `EvaluateConstantsPass`
+    pub length: ExpressionStatement,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub enum ImportItem {
     Named(Identifier),
-    All
+    /// `Light as PointLight`
+    Aliased(Identifier /* Name */, Identifier /* Alias */),
+    All,
+    /// `* as lighting`, items are accessed as `lighting::Light`
+    Namespace(Identifier),
 }
 
 type ExportItem = ImportItem;
@@ -112,7 +165,10 @@ type ExportItem = ImportItem;
 pub struct ImportDefinition {
     pub span: Span,
     pub items: Vec<ImportItem>,
+    /// the path as written, relative to the importing module
     pub module_id: String,
+    /// canonical path of the imported module, set when it is loaded
+    pub module_path: Option<String>,
 }
 
 impl_spanned!(ImportDefinition);
@@ -121,10 +177,38 @@ impl_spanned!(ImportDefinition);
 pub struct ExportDefinition {
     pub span: Span,
     pub items: Vec<ExportItem>,
+    /// the module the items are re-exported from, as written
+    pub module_id: Option<String>,
+    /// canonical path of the re-exported module, set when it is loaded
+    pub module_path: Option<String>,
 }
 
 impl_spanned!(ExportDefinition);
 
+/// metadata attached to an item or member, e.g. `@builtin(position)` or `@workgroup_size(8, 8)`
+#[derive(Debug, Eq, PartialEq)]
+pub struct Attribute {
+    pub span: Span,
+    pub attribute_name: Identifier,
+    pub arguments: Vec<ExpressionStatement>,
+}
+
+impl_spanned!(Attribute);
+
+impl Attribute {
+    /// the argument at `index` if it is a plain name, like `position` in `@builtin(position)`
+    pub fn get_name_argument(&self, index: usize) -> Option<&Identifier> {
+        match self.arguments.get(index) {
+            Some(&ExpressionStatement::Variable(ref variable)) => Some(&variable.variable_name),
+            _ => None,
+        }
+    }
+}
+
+pub fn find_attribute<'a>(attributes: &'a [Attribute], attribute_name: &str) -> Option<&'a Attribute> {
+    attributes.iter().find(|a| a.attribute_name.name == attribute_name)
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub enum ConstantVariant {
     Constant,
@@ -134,28 +218,113 @@ pub enum ConstantVariant {
 #[derive(Debug, Eq, PartialEq)]
 pub struct ConstantDefinition {
     pub span: Span,
+    pub attributes: Vec<Attribute>,
     pub constant_name: Identifier,
     pub constant_variant: ConstantVariant,
     pub constant_type_name: TypeIdentifier,
     pub constant_type: Option<TypeReference>,
+    pub constant_initializer: Option<ExpressionStatement>,
+    /// value folded at compile time from `constant_initializer`
+    pub constant_value: Option<ConstantValue>,
 }
 
 impl_spanned!(ConstantDefinition);
 
+/// read-only shader input set by the host, e.g. `uniform mvp: mat4x4;`
+#[derive(Debug, Eq, PartialEq)]
+pub struct UniformDefinition {
+    pub span: Span,
+    pub uniform_name: Identifier,
+    pub uniform_type_name: TypeIdentifier,
+    pub uniform_type: Option<TypeReference>,
+}
+
+impl_spanned!(UniformDefinition);
+
+/// named group of uniforms, e.g. `uniform Camera { view: mat4x4, }`
+/// members are accessed through the block name like `Camera.view`
+#[derive(Debug, Eq, PartialEq)]
+pub struct UniformBlockDefinition {
+    pub span: Span,
+    pub block_name: Identifier,
+    pub block_member: Vec<StructMemberDefinition>,
+    pub declaring_type: Option<TypeReference>,
+}
+
+impl_spanned!(UniformBlockDefinition);
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum ProgramKind {
+    /// `program`, a rasterization pipeline
+    Graphics,
+    /// `kernel`, a single compute stage
+    Kernel,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub struct ProgramDefinition {
     pub span: Span,
+    pub program_kind: ProgramKind,
     pub program_name: Identifier,
     pub program_stages: Vec<ProgramStageDefinition>,
 }
 
 impl_spanned!(ProgramDefinition);
 
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+pub enum StageKind {
+    Vertex,
+    TessControl,
+    TessEval,
+    Geometry,
+    Fragment,
+    Compute,
+}
+
+impl StageKind {
+    pub fn from_name(name: &str) -> Option<StageKind> {
+        match name {
+            "vertex" => Some(StageKind::Vertex),
+            "tess_control" => Some(StageKind::TessControl),
+            "tess_eval" => Some(StageKind::TessEval),
+            "geometry" => Some(StageKind::Geometry),
+            "fragment" => Some(StageKind::Fragment),
+            "compute" => Some(StageKind::Compute),
+            _ => None,
+        }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match *self {
+            StageKind::Vertex => "vertex",
+            StageKind::TessControl => "tess_control",
+            StageKind::TessEval => "tess_eval",
+            StageKind::Geometry => "geometry",
+            StageKind::Fragment => "fragment",
+            StageKind::Compute => "compute",
+        }
+    }
+}
+
+/// function executed by a program stage
+#[derive(Debug, Eq, PartialEq)]
+pub enum ProgramStageFunction {
+    /// `stage vertex(...) -> T { ... }`
+    Inline(FunctionDeclaration),
+    /// `vertex: function_name`
+    Reference(Identifier),
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub struct ProgramStageDefinition {
     pub span: Span,
     pub stage_name: Identifier,
-    pub function: FunctionDeclaration,
+    pub stage_kind: StageKind,
+    pub attributes: Vec<Attribute>,
+    /// evaluated `@workgroup_size(x, y, z)` of a compute stage, missing dimensions default to 1
+    pub workgroup_size: Option<[u32; 3]>,
+    pub function: ProgramStageFunction,
+    /// type of the referenced function, set for `ProgramStageFunction::Reference`
     pub declaring_type: Option<TypeReference>,
 }
 
@@ -164,6 +333,7 @@ impl_spanned!(ProgramStageDefinition);
 #[derive(Debug, Eq, PartialEq)]
 pub struct StructDefinition {
     pub span: Span,
+    pub attributes: Vec<Attribute>,
     pub struct_name: Identifier,
     pub struct_member: Vec<StructMemberDefinition>,
     pub declaring_type: Option<TypeReference>,
@@ -174,18 +344,26 @@ impl_spanned!(StructDefinition);
 #[derive(Debug, Eq, PartialEq)]
 pub struct StructMemberDefinition {
     pub span: Span,
+    pub attributes: Vec<Attribute>,
     pub struct_member_name: Identifier,
-    pub struct_member_type_name: TypeIdentifier,
+    pub struct_member_type_name: TypeName,
     pub struct_member_type: Option<TypeReference>,
 }
 
 impl_spanned!(StructMemberDefinition);
 
+impl StructMemberDefinition {
+    /// system value bound with `@builtin(name)`, backends map it to their native equivalent
+    pub fn get_builtin(&self) -> Option<&Identifier> {
+        find_attribute(&self.attributes, "builtin").and_then(|a| a.get_name_argument(0))
+    }
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub struct FunctionArgumentDeclaration {
     pub span: Span,
     pub argument_name: Identifier,
-    pub argument_type_name: TypeIdentifier,
+    pub argument_type_name: TypeName,
     pub argument_type: Option<TypeReference>,
 }
 
@@ -194,12 +372,15 @@ impl_spanned!(FunctionArgumentDeclaration);
 #[derive(Debug, Eq, PartialEq)]
 pub struct FunctionDeclaration {
     pub span: Span,
+    pub attributes: Vec<Attribute>,
     pub function_name: Identifier,
     pub arguments: Vec<FunctionArgumentDeclaration>,
     pub block: BlockDeclaration,
-    pub return_type_name: TypeIdentifier,
+    pub return_type_name: TypeName,
     pub return_type: Option<TypeReference>,
     pub declaring_type: Option<TypeReference>,
+    /// implemented by the target language, declared in libcore without a body like `builtin fn max(a: f32, b: f32) -> f32;`
+    pub builtin: bool,
 }
 
 impl_spanned!(FunctionDeclaration);
@@ -219,6 +400,8 @@ pub struct StructInstantiationExpression {
     pub span: Span,
     pub struct_type_name: TypeIdentifier,
     pub struct_field_initializer: Vec<StructFieldInitializerExpression>,
+    /// `..base` providing the fields which are not initialized explicitly
+    pub base: Option<Box<ExpressionStatement>>,
     pub struct_type: Option<TypeReference>,
 }
 
@@ -228,13 +411,17 @@ impl_spanned!(StructInstantiationExpression);
 pub enum LiteralType {
     Int,
     Float,
+    Bool,
 }
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct LiteralExpression {
     pub span: Span,
+    /// the literal as written, without its type suffix
     pub value: String,
     pub literal_expression_type: LiteralType,
+    /// type named by a suffix like `1.0f` or `1u`
+    pub literal_type_name: Option<TypeIdentifier>,
     pub literal_type: Option<TypeReference>,
 }
 
@@ -256,6 +443,8 @@ pub struct VariableExpression {
     pub span: Span,
     pub variable_name: Identifier,
     pub variable_type: Option<TypeReference>,
+    /// declaration the name was resolved to
+    pub variable_kind: Option<SymbolKind>,
 }
 
 impl_spanned!(VariableExpression);
@@ -264,7 +453,10 @@ impl_spanned!(VariableExpression);
 pub struct FieldAccessorExpression {
     pub span: Span,
     pub variable_name: Identifier,
-    pub field_name: Identifier,
+    pub variable_kind: Option<SymbolKind>,
+    /// accessed fields from the outermost inwards, `b` and `c` in `a.b.c`
+    pub field_names: Vec<Identifier>,
+    /// type of the innermost field
     pub field_type: Option<TypeReference>,
 }
 
@@ -274,7 +466,14 @@ impl_spanned!(FieldAccessorExpression);
 pub struct IndexAccesorExpression {
     pub span: Span,
     pub variable_name: Identifier,
+    pub variable_kind: Option<SymbolKind>,
+    /// fields accessed before the index, `lights` in `scene.lights[1].color`
+    pub field_names: Vec<Identifier>,
     pub access_expression: Box<ExpressionStatement>,
+    /// fields of the element accessed after the index, `color` in `scene.lights[1].color`
+    pub element_field_names: Vec<Identifier>,
+    /// type of the element or of its innermost field
+    pub accessor_type: Option<TypeReference>,
 }
 
 impl_spanned!(IndexAccesorExpression);
@@ -375,15 +574,36 @@ pub struct PrimitiveDeclaration {
 
 impl_spanned!(PrimitiveDeclaration);
 
+/// system value provided or consumed by the pipeline, declared in libcore
+/// e.g. `builtin vertex_index: u32 in(vertex);`
 #[derive(Debug, Eq, PartialEq)]
+pub struct BuiltinDeclaration {
+    pub span: Span,
+    pub builtin_name: Identifier,
+    pub builtin_type_name: TypeIdentifier,
+    pub builtin_type: Option<TypeReference>,
+    /// stages reading the builtin
+    pub input_stages: Vec<StageKind>,
+    /// stages writing the builtin
+    pub output_stages: Vec<StageKind>,
+}
+
+impl_spanned!(BuiltinDeclaration);
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum Operator {
     Plus,
     Minus,
     Multiply,
     Divide,
+    BitwiseAnd,
+    BitwiseOr,
+    BitwiseXor,
+    ShiftLeft,
+    ShiftRight,
 }
 
-// TODO type check
+/// binary operator declared in libcore, e.g. `operator + (lhs: f32, rhs: f32) -> f32;`
 #[derive(Debug, Eq, PartialEq)]
 pub struct OperatorDeclaration {
     pub span: Span,
@@ -418,9 +638,12 @@ pub enum ItemKind {
     Struct(StructDefinition),
     Program(ProgramDefinition),
     Constant(ConstantDefinition),
+    Uniform(UniformDefinition),
+    UniformBlock(UniformBlockDefinition),
     Function(FunctionDeclaration),
     Block(BlockDeclaration),
     Primitive(PrimitiveDeclaration),
+    Builtin(BuiltinDeclaration),
     Operator(OperatorDeclaration),
     Cast(CastDeclaration),
 }
@@ -433,9 +656,12 @@ impl Spanned for ItemKind {
             ItemKind::Struct(ref item) => item.span,
             ItemKind::Program(ref item) => item.span,
             ItemKind::Constant(ref item) => item.span,
+            ItemKind::Uniform(ref item) => item.span,
+            ItemKind::UniformBlock(ref item) => item.span,
             ItemKind::Function(ref item) => item.span,
             ItemKind::Block(ref item) => item.span,
             ItemKind::Primitive(ref item) => item.span,
+            ItemKind::Builtin(ref item) => item.span,
             ItemKind::Operator(ref item) => item.span,
             ItemKind::Cast(ref item) => item.span,
         }
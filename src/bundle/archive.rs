@@ -0,0 +1,420 @@
+use ::std::error::Error;
+use ::std::fmt;
+use ::ast::{ ProgramKind, StageKind };
+use ::type_system::layout::{ LayoutRule, StructLayout, MemberLayout };
+
+const MAGIC: &[u8; 4] = b"XSPK";
+
+/// format version written to archives, archives of other versions are rejected by the reader
+pub const ARCHIVE_VERSION: u32 = 1;
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum ArchiveError {
+    /// the data does not start with the archive magic
+    NotAnArchive,
+    UnsupportedVersion(u32),
+    /// the data ends in the middle of an entry
+    Truncated,
+    /// an entry holds a value the reader does not know
+    InvalidData(&'static str),
+}
+
+impl fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ArchiveError::NotAnArchive => write!(f, "The data is not a shader archive."),
+            ArchiveError::UnsupportedVersion(version) => write!(f, "Archive version {} is not supported, expected version {}.", version, ARCHIVE_VERSION),
+            ArchiveError::Truncated => write!(f, "The archive is truncated."),
+            ArchiveError::InvalidData(what) => write!(f, "The archive contains an invalid {}.", what),
+        }
+    }
+}
+
+impl Error for ArchiveError {
+    fn description(&self) -> &str {
+        match *self {
+            ArchiveError::NotAnArchive => "Not a shader archive",
+            ArchiveError::UnsupportedVersion(_) => "Unsupported archive version",
+            ArchiveError::Truncated => "Truncated archive",
+            ArchiveError::InvalidData(_) => "Invalid archive data",
+        }
+    }
+}
+
+pub type ArchiveResult<T> = Result<T, ArchiveError>;
+
+/// a stage of an archived program and the function it executes
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ArchiveStage {
+    pub kind: StageKind,
+    pub function_name: String,
+    pub workgroup_size: Option<[u32; 3]>,
+}
+
+/// a plain uniform of the module declaring an archived program
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ArchiveUniform {
+    pub name: String,
+    pub type_name: String,
+}
+
+/// a uniform block of the module declaring an archived program with its std140 layout
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ArchiveUniformBlock {
+    pub name: String,
+    pub layout: StructLayout,
+}
+
+/// what a backend generated for an archived program
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ArchiveOutput {
+    pub backend: String,
+    pub data: Vec<u8>,
+}
+
+/// a compiled program with its reflection data and backend outputs
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ArchiveProgram {
+    pub name: String,
+    /// canonical path of the module declaring the program
+    pub module_path: String,
+    pub kind: ProgramKind,
+    pub stages: Vec<ArchiveStage>,
+    pub uniforms: Vec<ArchiveUniform>,
+    pub uniform_blocks: Vec<ArchiveUniformBlock>,
+    pub outputs: Vec<ArchiveOutput>,
+}
+
+impl ArchiveProgram {
+    pub fn find_stage(&self, kind: StageKind) -> Option<&ArchiveStage> {
+        self.stages.iter().find(|stage| stage.kind == kind)
+    }
+
+    /// output of the backend named `backend`
+    pub fn find_output(&self, backend: &str) -> Option<&[u8]> {
+        self.outputs.iter()
+            .find(|output| output.backend == backend)
+            .map(|output| &output.data[..])
+    }
+}
+
+/// programs compiled ahead of time, written with `to_bytes` and read back with `from_bytes`
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Archive {
+    programs: Vec<ArchiveProgram>,
+}
+
+impl Archive {
+    pub fn new() -> Archive {
+        Archive {
+            programs: Vec::new(),
+        }
+    }
+
+    pub fn add_program(&mut self, program: ArchiveProgram) {
+        self.programs.push(program);
+    }
+
+    pub fn get_programs(&self) -> &Vec<ArchiveProgram> {
+        &self.programs
+    }
+
+    pub fn find_program(&self, name: &str) -> Option<&ArchiveProgram> {
+        self.programs.iter().find(|program| program.name == name)
+    }
+
+    /// the archive in its binary format, all integers are little endian
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer { bytes: Vec::new() };
+        writer.bytes.extend_from_slice(MAGIC);
+        writer.write_u32(ARCHIVE_VERSION);
+
+        writer.write_u32(self.programs.len() as u32);
+        for program in &self.programs {
+            writer.write_program(program);
+        }
+
+        writer.bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> ArchiveResult<Archive> {
+        if bytes.len() < MAGIC.len() || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(ArchiveError::NotAnArchive);
+        }
+
+        let mut reader = Reader { bytes: bytes, position: MAGIC.len() };
+        let version = reader.read_u32()?;
+        if version != ARCHIVE_VERSION {
+            return Err(ArchiveError::UnsupportedVersion(version));
+        }
+
+        let mut archive = Archive::new();
+        for _ in 0..reader.read_u32()? {
+            archive.add_program(reader.read_program()?);
+        }
+
+        if reader.position != bytes.len() {
+            return Err(ArchiveError::InvalidData("trailing data"));
+        }
+
+        Ok(archive)
+    }
+}
+
+fn program_kind_id(kind: ProgramKind) -> u32 {
+    match kind {
+        ProgramKind::Graphics => 0,
+        ProgramKind::Kernel => 1,
+    }
+}
+
+fn layout_rule_id(rule: LayoutRule) -> u32 {
+    match rule {
+        LayoutRule::Std140 => 0,
+        LayoutRule::Std430 => 1,
+        LayoutRule::Scalar => 2,
+    }
+}
+
+struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    fn write_u32(&mut self, value: u32) {
+        for i in 0..4 {
+            self.bytes.push((value >> (i * 8)) as u8);
+        }
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        self.write_u32(bytes.len() as u32);
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    fn write_str(&mut self, value: &str) {
+        self.write_bytes(value.as_bytes());
+    }
+
+    fn write_program(&mut self, program: &ArchiveProgram) {
+        self.write_str(&program.name);
+        self.write_str(&program.module_path);
+        self.write_u32(program_kind_id(program.kind));
+
+        self.write_u32(program.stages.len() as u32);
+        for stage in &program.stages {
+            self.write_str(stage.kind.get_name());
+            self.write_str(&stage.function_name);
+            match stage.workgroup_size {
+                Some(size) => {
+                    self.write_u32(1);
+                    for &dimension in &size {
+                        self.write_u32(dimension);
+                    }
+                },
+                None => self.write_u32(0),
+            }
+        }
+
+        self.write_u32(program.uniforms.len() as u32);
+        for uniform in &program.uniforms {
+            self.write_str(&uniform.name);
+            self.write_str(&uniform.type_name);
+        }
+
+        self.write_u32(program.uniform_blocks.len() as u32);
+        for block in &program.uniform_blocks {
+            self.write_str(&block.name);
+            self.write_layout(&block.layout);
+        }
+
+        self.write_u32(program.outputs.len() as u32);
+        for output in &program.outputs {
+            self.write_str(&output.backend);
+            self.write_bytes(&output.data);
+        }
+    }
+
+    fn write_layout(&mut self, layout: &StructLayout) {
+        self.write_u32(layout_rule_id(layout.rule));
+        self.write_u32(layout.size);
+        self.write_u32(layout.alignment);
+
+        self.write_u32(layout.members.len() as u32);
+        for member in &layout.members {
+            self.write_str(&member.member_name);
+            self.write_u32(member.offset);
+            self.write_u32(member.size);
+            self.write_u32(member.alignment);
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn read_u32(&mut self) -> ArchiveResult<u32> {
+        let bytes = self.read_slice(4)?;
+        Ok(bytes.iter().rev().fold(0, |value, &byte| (value << 8) | byte as u32))
+    }
+
+    fn read_slice(&mut self, length: usize) -> ArchiveResult<&'a [u8]> {
+        if self.bytes.len() - self.position < length {
+            return Err(ArchiveError::Truncated);
+        }
+
+        let slice = &self.bytes[self.position..self.position + length];
+        self.position += length;
+        Ok(slice)
+    }
+
+    fn read_bytes(&mut self) -> ArchiveResult<Vec<u8>> {
+        let length = self.read_u32()? as usize;
+        Ok(self.read_slice(length)?.to_vec())
+    }
+
+    fn read_string(&mut self) -> ArchiveResult<String> {
+        String::from_utf8(self.read_bytes()?).map_err(|_| ArchiveError::InvalidData("string"))
+    }
+
+    fn read_program(&mut self) -> ArchiveResult<ArchiveProgram> {
+        let name = self.read_string()?;
+        let module_path = self.read_string()?;
+        let kind = match self.read_u32()? {
+            0 => ProgramKind::Graphics,
+            1 => ProgramKind::Kernel,
+            _ => return Err(ArchiveError::InvalidData("program kind")),
+        };
+
+        let mut stages = Vec::new();
+        for _ in 0..self.read_u32()? {
+            let kind = StageKind::from_name(&self.read_string()?).ok_or(ArchiveError::InvalidData("stage kind"))?;
+            let function_name = self.read_string()?;
+            let workgroup_size = match self.read_u32()? {
+                0 => None,
+                1 => Some([self.read_u32()?, self.read_u32()?, self.read_u32()?]),
+                _ => return Err(ArchiveError::InvalidData("workgroup size")),
+            };
+            stages.push(ArchiveStage { kind: kind, function_name: function_name, workgroup_size: workgroup_size });
+        }
+
+        let mut uniforms = Vec::new();
+        for _ in 0..self.read_u32()? {
+            let name = self.read_string()?;
+            let type_name = self.read_string()?;
+            uniforms.push(ArchiveUniform { name: name, type_name: type_name });
+        }
+
+        let mut uniform_blocks = Vec::new();
+        for _ in 0..self.read_u32()? {
+            let name = self.read_string()?;
+            let layout = self.read_layout()?;
+            uniform_blocks.push(ArchiveUniformBlock { name: name, layout: layout });
+        }
+
+        let mut outputs = Vec::new();
+        for _ in 0..self.read_u32()? {
+            let backend = self.read_string()?;
+            let data = self.read_bytes()?;
+            outputs.push(ArchiveOutput { backend: backend, data: data });
+        }
+
+        Ok(ArchiveProgram {
+            name: name,
+            module_path: module_path,
+            kind: kind,
+            stages: stages,
+            uniforms: uniforms,
+            uniform_blocks: uniform_blocks,
+            outputs: outputs,
+        })
+    }
+
+    fn read_layout(&mut self) -> ArchiveResult<StructLayout> {
+        let rule = match self.read_u32()? {
+            0 => LayoutRule::Std140,
+            1 => LayoutRule::Std430,
+            2 => LayoutRule::Scalar,
+            _ => return Err(ArchiveError::InvalidData("layout rule")),
+        };
+        let size = self.read_u32()?;
+        let alignment = self.read_u32()?;
+
+        let mut members = Vec::new();
+        for _ in 0..self.read_u32()? {
+            let member_name = self.read_string()?;
+            members.push(MemberLayout {
+                member_name: member_name,
+                offset: self.read_u32()?,
+                size: self.read_u32()?,
+                alignment: self.read_u32()?,
+            });
+        }
+
+        Ok(StructLayout { rule: rule, size: size, alignment: alignment, members: members })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::type_system::layout::TypeShape;
+
+    fn test_archive() -> Archive {
+        let mut archive = Archive::new();
+        archive.add_program(ArchiveProgram {
+            name: "Simple".to_string(),
+            module_path: "shaders/simple.xs".to_string(),
+            kind: ProgramKind::Graphics,
+            stages: vec![ArchiveStage { kind: StageKind::Vertex, function_name: "vertex_main".to_string(), workgroup_size: None }],
+            uniforms: vec![ArchiveUniform { name: "albedo".to_string(), type_name: "vec4".to_string() }],
+            uniform_blocks: vec![ArchiveUniformBlock {
+                name: "Camera".to_string(),
+                layout: StructLayout::new(&[("scale".to_string(), TypeShape::Scalar(4))], LayoutRule::Std140),
+            }],
+            outputs: vec![ArchiveOutput { backend: "rust-structs-std140".to_string(), data: b"pub struct Camera;".to_vec() }],
+        });
+        archive.add_program(ArchiveProgram {
+            name: "Blur".to_string(),
+            module_path: "shaders/blur.xs".to_string(),
+            kind: ProgramKind::Kernel,
+            stages: vec![ArchiveStage { kind: StageKind::Compute, function_name: "blur".to_string(), workgroup_size: Some([8, 8, 1]) }],
+            uniforms: Vec::new(),
+            uniform_blocks: Vec::new(),
+            outputs: Vec::new(),
+        });
+        archive
+    }
+
+    #[test]
+    fn read_written_archive() {
+        let archive = test_archive();
+        let read = Archive::from_bytes(&archive.to_bytes()).unwrap();
+
+        assert_eq!(read, archive);
+        let program = read.find_program("Simple").unwrap();
+        assert_eq!(program.find_output("rust-structs-std140"), Some(&b"pub struct Camera;"[..]));
+        assert_eq!(program.find_stage(StageKind::Vertex).unwrap().function_name, "vertex_main");
+        assert_eq!(read.find_program("Blur").unwrap().stages[0].workgroup_size, Some([8, 8, 1]));
+        assert!(read.find_program("Missing").is_none());
+    }
+
+    #[test]
+    fn reject_invalid_archives() {
+        let bytes = test_archive().to_bytes();
+
+        assert_eq!(Archive::from_bytes(b"XSP"), Err(ArchiveError::NotAnArchive));
+        assert_eq!(Archive::from_bytes(&bytes[..bytes.len() - 1]), Err(ArchiveError::Truncated));
+
+        let mut future = bytes.clone();
+        future[4] = 2;
+        assert_eq!(Archive::from_bytes(&future), Err(ArchiveError::UnsupportedVersion(2)));
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(Archive::from_bytes(&trailing), Err(ArchiveError::InvalidData("trailing data")));
+    }
+}
@@ -0,0 +1,329 @@
+pub mod archive;
+
+use ::std::collections::HashSet;
+use ::std::error::Error;
+use ::std::fmt;
+use ::std::fs;
+use ::std::io;
+use ::std::path::Path;
+use ::ast::{ ProgramDefinition, ProgramStageFunction };
+use ::compile_error::{ CompileError, CompileResult };
+use ::compiler::{ Compiler, Compilation };
+use ::generators::rust_structs::rule_name;
+use ::passes::ast::type_checking::find_program_variables;
+use ::resolver::DirectoryResolver;
+use ::type_system::layout::LayoutRule;
+use self::archive::{ Archive, ArchiveProgram, ArchiveStage, ArchiveUniform, ArchiveUniformBlock, ArchiveOutput };
+
+/// generates the output stored in an archive for each bundled program
+pub trait Backend {
+    /// identifies the outputs of this backend in an archive
+    fn get_name(&self) -> String;
+
+    fn generate(&self, compilation: &Compilation, program: &ProgramDefinition) -> CompileResult<Vec<u8>>;
+}
+
+/// rust mirrors of the structures a program uses, see `Compilation::generate_program_rust_structs`
+pub struct RustStructsBackend {
+    rule: LayoutRule,
+}
+
+impl RustStructsBackend {
+    pub fn new(rule: LayoutRule) -> RustStructsBackend {
+        RustStructsBackend {
+            rule: rule,
+        }
+    }
+}
+
+impl Backend for RustStructsBackend {
+    fn get_name(&self) -> String {
+        format!("rust-structs-{}", rule_name(self.rule))
+    }
+
+    fn generate(&self, compilation: &Compilation, program: &ProgramDefinition) -> CompileResult<Vec<u8>> {
+        Ok(compilation.generate_program_rust_structs(program, self.rule)?.into_bytes())
+    }
+}
+
+#[derive(Debug)]
+pub enum BundleError {
+    Io(io::Error),
+    /// errors of the compiled modules with the path of the module each occurs in, the path is empty for errors outside of the modules like those of the core module
+    Compile(Vec<(String /* Module path */, CompileError)>),
+    /// two modules declare a program of the same name, programs are looked up by name in an archive
+    DuplicateProgram(String /* Program name */, String /* Module path */, String /* Module path */),
+}
+
+impl fmt::Display for BundleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BundleError::Io(ref error) => write!(f, "{}", error),
+            BundleError::Compile(ref errors) => {
+                for (index, &(ref module_path, ref error)) in errors.iter().enumerate() {
+                    if index > 0 {
+                        writeln!(f)?;
+                    }
+                    if module_path.is_empty() {
+                        write!(f, "{}", error)?;
+                    } else {
+                        write!(f, "{}: {}", module_path, error)?;
+                    }
+                }
+                Ok(())
+            },
+            BundleError::DuplicateProgram(ref name, ref first, ref second) => write!(f, "Program `{}` is declared in `{}` and `{}`.", name, first, second),
+        }
+    }
+}
+
+impl Error for BundleError {
+    fn description(&self) -> &str {
+        match *self {
+            BundleError::Io(_) => "IO error",
+            BundleError::Compile(_) => "Compile error",
+            BundleError::DuplicateProgram(_, _, _) => "Duplicate program",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match *self {
+            BundleError::Io(ref error) => Some(error),
+            BundleError::Compile(ref errors) => errors.first().map(|&(_, ref error)| error as &Error),
+            BundleError::DuplicateProgram(_, _, _) => None,
+        }
+    }
+}
+
+impl From<io::Error> for BundleError {
+    fn from(error: io::Error) -> BundleError {
+        BundleError::Io(error)
+    }
+}
+
+/// compiles the programs of many modules into one `Archive`
+pub struct Bundler {
+    backends: Vec<Box<Backend>>,
+}
+
+impl Bundler {
+    pub fn new() -> Bundler {
+        Bundler {
+            backends: Vec::new(),
+        }
+    }
+
+    pub fn add_backend(&mut self, backend: Box<Backend>) {
+        self.backends.push(backend);
+    }
+
+    /// bundles the programs of every `.xs` module below `root`, module paths are relative to `root`
+    pub fn bundle_directory<P: AsRef<Path>>(&self, root: P) -> Result<Archive, BundleError> {
+        let mut module_paths = Vec::new();
+        find_modules(root.as_ref(), "", &mut module_paths)?;
+
+        let mut compiler = Compiler::new(Box::new(DirectoryResolver::new(root.as_ref())));
+        self.bundle_modules(&mut compiler, &module_paths)
+    }
+
+    /// bundles the programs declared by the modules at `module_paths`, their imports are only checked
+    /// the modules are compiled together, so a module imported by several of them is checked once
+    pub fn bundle_modules(&self, compiler: &mut Compiler, module_paths: &[String]) -> Result<Archive, BundleError> {
+        let compilations = compiler.compile_modules(module_paths)
+            .map_err(|error| BundleError::Compile(vec![(String::new(), error)]))?;
+
+        // compilations share their dependencies, the errors of each module are reported once
+        let mut reported = HashSet::new();
+        let mut errors = Vec::new();
+        for compilation in &compilations {
+            let modules = compilation.get_dependencies().iter().map(|module| &**module).chain(Some(compilation.get_module()));
+            for module in modules.filter(|module| reported.insert(module.get_path().to_owned())) {
+                errors.extend(module.get_errors().iter().map(|error| (module.get_path().to_owned(), error.clone())));
+            }
+        }
+        if !errors.is_empty() {
+            return Err(BundleError::Compile(errors));
+        }
+
+        let mut archive = Archive::new();
+        for compilation in &compilations {
+            let module = compilation.get_module();
+            for program in module.find_programs() {
+                if let Some(existing) = archive.find_program(&program.program_name.name) {
+                    return Err(BundleError::DuplicateProgram(program.program_name.name.to_owned(), existing.module_path.to_owned(), module.get_path().to_owned()));
+                }
+
+                let archive_program = self.bundle_program(compilation, program)
+                    .map_err(|error| BundleError::Compile(vec![(module.get_path().to_owned(), error)]))?;
+                archive.add_program(archive_program);
+            }
+        }
+
+        Ok(archive)
+    }
+
+    fn bundle_program(&self, compilation: &Compilation, program: &ProgramDefinition) -> CompileResult<ArchiveProgram> {
+        let module = compilation.get_module();
+
+        let stages = program.program_stages.iter().map(|stage| {
+            let function_name = match stage.function {
+                ProgramStageFunction::Inline(ref function) => function.function_name.name.to_owned(),
+                ProgramStageFunction::Reference(ref function_name) => function_name.name.to_owned(),
+            };
+            ArchiveStage { kind: stage.stage_kind, function_name: function_name, workgroup_size: stage.workgroup_size }
+        }).collect();
+
+        // only the uniforms the program reads are bound for it
+        let variables = find_program_variables(module.get_ast(), program);
+        let uniforms = module.find_uniforms().iter().filter(|uniform| variables.contains(&uniform.uniform_name.name)).map(|uniform| {
+            ArchiveUniform { name: uniform.uniform_name.name.to_owned(), type_name: uniform.uniform_type_name.name.to_owned() }
+        }).collect();
+
+        // a block without a layout has failed to check, checked modules have none
+        let uniform_blocks = module.find_uniform_blocks().iter().filter(|block| variables.contains(&block.block_name.name)).filter_map(|block| {
+            let layout = compilation.find_struct_layout(block.declaring_type?, LayoutRule::Std140)?;
+            Some(ArchiveUniformBlock { name: block.block_name.name.to_owned(), layout: layout })
+        }).collect();
+
+        let mut outputs = Vec::new();
+        for backend in &self.backends {
+            outputs.push(ArchiveOutput { backend: backend.get_name(), data: backend.generate(compilation, program)? });
+        }
+
+        Ok(ArchiveProgram {
+            name: program.program_name.name.to_owned(),
+            module_path: module.get_path().to_owned(),
+            kind: program.program_kind,
+            stages: stages,
+            uniforms: uniforms,
+            uniform_blocks: uniform_blocks,
+            outputs: outputs,
+        })
+    }
+}
+
+/// collects the paths of the `.xs` files below `directory` in a stable order, prefixed with `prefix`
+fn find_modules(directory: &Path, prefix: &str, module_paths: &mut Vec<String>) -> io::Result<()> {
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(directory)? {
+        entries.push(entry?);
+    }
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let module_path = if prefix.is_empty() { name } else { format!("{}/{}", prefix, name) };
+
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            find_modules(&entry.path(), &module_path, module_paths)?;
+        } else if file_type.is_file() && module_path.ends_with(".xs") {
+            module_paths.push(module_path);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use ::std::env;
+    use ::ast::{ ProgramKind, StageKind };
+    use ::resolver::MemoryResolver;
+    use super::*;
+
+    fn bundle(modules: &[(&str, &str)]) -> Result<Archive, BundleError> {
+        let mut resolver = MemoryResolver::new();
+        for &(path, source) in modules {
+            resolver.insert(path, source);
+        }
+        let mut compiler = Compiler::new(Box::new(resolver));
+        let mut bundler = Bundler::new();
+        bundler.add_backend(Box::new(RustStructsBackend::new(LayoutRule::Std140)));
+
+        let module_paths: Vec<String> = modules.iter().map(|&(path, _)| path.to_string()).collect();
+        bundler.bundle_modules(&mut compiler, &module_paths)
+    }
+
+    #[test]
+    fn bundle_programs_with_reflection() {
+        let archive = bundle(&[
+            ("simple.xs", "import { Light, shade } from 'lib.xs'; uniform albedo: vec4; uniform exposure: f32; uniform Camera { scale: f32, offset: vec3, } \
+                fn tint() -> vec4 { let scale = Camera.scale; return albedo; } \
+                program Simple { vertex: tint, } kernel Blur { @workgroup_size(8u, 8u) compute: shade, }"),
+            ("lib.xs", "struct Light { intensity: f32, } fn shade() -> f32 { return 1.0; } export *;"),
+        ]).unwrap();
+
+        assert_eq!(archive.get_programs().len(), 2);
+        assert_eq!(archive.find_program("Blur").unwrap().stages[0].workgroup_size, Some([8, 8, 1]));
+        let simple = archive.find_program("Simple").unwrap();
+        assert_eq!(simple.module_path, "simple.xs");
+        assert_eq!(simple.kind, ProgramKind::Graphics);
+        assert_eq!(simple.find_stage(StageKind::Vertex).unwrap().function_name, "tint");
+        assert_eq!(simple.uniforms, vec![ArchiveUniform { name: "albedo".to_string(), type_name: "vec4".to_string() }]);
+        assert_eq!(simple.uniform_blocks[0].name, "Camera");
+        assert_eq!(simple.uniform_blocks[0].layout.find_member("offset").unwrap().offset, 16);
+        assert!(simple.find_output("rust-structs-std140").is_some());
+
+        let blur = archive.find_program("Blur").unwrap();
+        assert!(blur.uniforms.is_empty());
+        assert!(blur.uniform_blocks.is_empty());
+    }
+
+    #[test]
+    fn bundle_the_structures_of_each_program() {
+        let archive = bundle(&[
+            ("a.xs", "struct VOut { @builtin(position) position: vec4, color: vec4, } struct Unused { x: f32, } uniform Light { color: vec4, } \
+                fn vs() -> VOut { return VOut { position: Light.color, color: Light.color, }; } fn fs() -> f32 { return 1.0; } \
+                program Lit { vertex: vs, } program Flat { vertex: fs, } export *;"),
+        ]).unwrap();
+
+        let lit = archive.find_program("Lit").unwrap().find_output("rust-structs-std140").unwrap();
+        let lit = String::from_utf8(lit.to_vec()).unwrap();
+        assert!(lit.contains("pub struct VOut"));
+        assert!(lit.contains("pub struct Light"));
+        assert!(!lit.contains("pub struct Unused"));
+
+        let flat = archive.find_program("Flat").unwrap().find_output("rust-structs-std140").unwrap();
+        assert!(!String::from_utf8(flat.to_vec()).unwrap().contains("pub struct"));
+    }
+
+    #[test]
+    fn report_errors_of_bundled_modules() {
+        match bundle(&[("a.xs", "import Light from 'b.xs';"), ("b.xs", "struct Light { x: f33, } export Light;")]) {
+            Err(BundleError::Compile(ref errors)) => assert_eq!(errors[0].0, "b.xs"),
+            result => panic!("unexpected result {:?}", result),
+        }
+
+        match bundle(&[("a.xs", "import Light from 'b.xs';"), ("b.xs", "struct Light { x: f32, }"), ("c.xs", "import Shadow from 'missing.xs';")]) {
+            Err(error) => assert_eq!(error.to_string(), "a.xs: Item \"Light\" of module \"b.xs\" is not exported.\nc.xs: Module \"missing.xs\" not found."),
+            result => panic!("unexpected result {:?}", result),
+        }
+
+        // b.xs is imported by both modules and bundled itself, its error is reported once
+        match bundle(&[("a.xs", "import Light from 'b.xs';"), ("c.xs", "import Light from 'b.xs';"), ("b.xs", "struct Light { x: f33, } export Light;")]) {
+            Err(BundleError::Compile(ref errors)) => assert_eq!(errors.iter().filter(|&&(ref path, _)| path == "b.xs").count(), 1),
+            result => panic!("unexpected result {:?}", result),
+        }
+
+        match bundle(&[("a.xs", "fn f() -> f32 { return 1.0; } program P { vertex: f, }"), ("b.xs", "fn f() -> f32 { return 1.0; } program P { vertex: f, }")]) {
+            Err(BundleError::DuplicateProgram(ref name, ref first, ref second)) => assert_eq!((&name[..], &first[..], &second[..]), ("P", "a.xs", "b.xs")),
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn bundle_directory_modules() {
+        let root = env::temp_dir().join(format!("xshade_bundle_{}", ::std::process::id()));
+        fs::create_dir_all(root.join("lib")).unwrap();
+        fs::write(root.join("main.xs"), "import shade from './lib/shade.xs'; program Main { vertex: shade, }").unwrap();
+        fs::write(root.join("lib/shade.xs"), "fn shade() -> f32 { return 1.0; } export shade;").unwrap();
+        fs::write(root.join("readme.md"), "not a module").unwrap();
+
+        let archive = Bundler::new().bundle_directory(&root);
+        fs::remove_dir_all(&root).unwrap();
+
+        let archive = Archive::from_bytes(&archive.unwrap().to_bytes()).unwrap();
+        assert_eq!(archive.find_program("Main").unwrap().module_path, "main.xs");
+    }
+}
@@ -5,14 +5,25 @@ use ::ast::Span;
 
 pub type CompileResult<T> = Result<T, CompileError>;
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq, Clone)]
 pub enum ErrorKind {
     Unknown,
     ParseError,
+    /// what the parser expected where an item or statement went wrong
+    SyntaxError(String /* Message */),
     TypeError(TypeError),
+    ModuleNotFound(String /* Module id */),
+    ModuleNotReadable(String /* Module path */, String /* Reason */),
+    /// modules of the cycle, each with the span of its import of the next one
+    /// the last import leads back to the first module
+    ImportCycle(Vec<(String /* Module path */, Span /* Import */)>),
+    /// an error of the compiler itself without a location in the source
+    Internal(String /* Message */),
+    /// the core module failed to parse or type check, spans refer to the core source
+    CoreModule(Box<CompileError>),
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq, Clone)]
 pub struct CompileError {
     kind: ErrorKind,
     span: Span,
@@ -42,9 +53,47 @@ impl CompileError {
     }
 }
 
+impl From<TypeError> for CompileError {
+    fn from(error: TypeError) -> CompileError {
+        let span = error.get_span();
+        CompileError::new(ErrorKind::TypeError(error), span)
+    }
+}
+
 impl fmt::Display for CompileError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Compile Error")
+        match self.kind {
+            ErrorKind::Unknown => {
+                write!(f, "Unknown error.")
+            },
+            ErrorKind::ParseError => {
+                write!(f, "Parse error.")
+            },
+            ErrorKind::SyntaxError(ref message) => {
+                write!(f, "Syntax error: {}.", message)
+            },
+            ErrorKind::TypeError(ref error) => {
+                write!(f, "{}", error)
+            },
+            ErrorKind::ModuleNotFound(ref module_id) => {
+                write!(f, "Module \"{}\" not found.", module_id)
+            },
+            ErrorKind::ModuleNotReadable(ref module_path, ref reason) => {
+                write!(f, "Module \"{}\" could not be read: {}.", module_path, reason)
+            },
+            ErrorKind::ImportCycle(ref cycle) => {
+                let imports: Vec<String> = cycle.iter().enumerate().map(|(index, &(ref module_path, _))| {
+                    format!("\"{}\" imports \"{}\"", module_path, cycle[(index + 1) % cycle.len()].0)
+                }).collect();
+                write!(f, "Import cycle: {}.", imports.join(", "))
+            },
+            ErrorKind::Internal(ref message) => {
+                write!(f, "Internal compiler error: {}.", message)
+            },
+            ErrorKind::CoreModule(ref error) => {
+                write!(f, "Error in the core module: {}", error)
+            },
+        }
     }
 }
 
@@ -58,6 +107,12 @@ impl Error for CompileError {
             ErrorKind::Unknown => None,
             ErrorKind::TypeError(ref t) => Some(t),
             ErrorKind::ParseError => None,
+            ErrorKind::SyntaxError(_) => None,
+            ErrorKind::ModuleNotFound(_) => None,
+            ErrorKind::ModuleNotReadable(_, _) => None,
+            ErrorKind::ImportCycle(_) => None,
+            ErrorKind::Internal(_) => None,
+            ErrorKind::CoreModule(ref error) => Some(&**error),
         }
     }
 }
@@ -1,97 +1,349 @@
 use ::std::error::Error;
 use ::std::mem;
-use ::std::collections::HashMap;
+use ::std::rc::Rc;
+use ::std::collections::{ HashMap, HashSet };
 use ::compile_error::{ CompileError, CompileResult, ErrorKind };
 use ::module::Module;
-use ::parser::parse_str;
-use ::type_system::symbol_table::SymbolTable;
+use ::module_path;
+use ::resolver::{ ModuleResolver, ResolveError, MemoryResolver, OverlayResolver };
+use ::parser::{ parse_str, parse_module };
+use ::type_system::symbol_table::{ SymbolTable, SymbolTableReference };
 use ::type_system::type_environment::TypeEnvironment;
-use ::ast::{ ItemKind, Span };
+use ::type_system::error::TypeError;
+use ::type_system::layout::{ LayoutRule, StructLayout };
+use ::type_system::type_environment::TypeReference;
+use ::ast::{ ProgramDefinition, Span };
+use ::passes::Pass;
 use ::passes::ast::AstWalker;
+use ::passes::ast::type_checking::{ TypeChecker, find_program_variables };
+use ::passes::results::{ PassResult, PassResultReference };
+use ::generators::rust_structs::{ generate_rust_structs, generate_program_rust_structs };
 
-fn parse_core_modules(symbols: &mut SymbolTable) -> Result<Module, Box<Error>> {
-    let primitives = include_str!("../libcore/primitives.xs");
-    let ast = parse_str(primitives)?;
-    let mut module = Module::new("".to_owned(), primitives.to_owned(), ast, true);
-    Ok(module)
+/// source of the default core module, the primitive types, their operators and casts and the shader builtins
+pub fn default_core_source() -> String {
+    format!("{}\n{}", include_str!("../libcore/primitives.xs"), include_str!("../libcore/builtins.xs"))
 }
 
-pub trait ModuleResolver {
-    fn resolve(&mut self, module_path: &str) -> Result<String, Box<Error>>;
+/// parses and checks the core module into `symbol_table`, its items become global to every module checked after it
+fn load_core_module(source: &str, symbol_table: &SymbolTableReference) -> CompileResult<Module> {
+    let ast = match parse_str(source) {
+        Ok(ast) => ast,
+        Err(error) => return Err(CompileError::new(ErrorKind::CoreModule(Box::new(error.clone())), error.get_span())),
+    };
+    let mut module = Module::new("".to_owned(), source.to_owned(), ast, true);
+    check_module(symbol_table, &mut module, &HashSet::new());
+
+    match module.get_error() {
+        Some(error) => Err(CompileError::new(ErrorKind::CoreModule(Box::new(error.clone())), error.get_span())),
+        None => Ok(module),
+    }
+}
+
+/// modules of the embedded standard library like `std/math`, the compiler places them behind its resolver
+pub fn std_resolver() -> MemoryResolver {
+    let mut resolver = MemoryResolver::new();
+    resolver.insert("std/math", include_str!("../libstd/math.xs"));
+    resolver.insert("std/color", include_str!("../libstd/color.xs"));
+    resolver.insert("std/lighting", include_str!("../libstd/lighting.xs"));
+    resolver.insert("std/noise", include_str!("../libstd/noise.xs"));
+    resolver.insert("std/packing", include_str!("../libstd/packing.xs"));
+    resolver
+}
+
+/// type checks `module`, items of the core module are global while other modules get their own scope
+/// the exports of a checked module can be imported by the modules checked after it
+/// items missing from the modules in `failed_modules`, which failed to load or parse, are not reported by their importers
+fn check_module(symbol_table: &SymbolTableReference, module: &mut Module, failed_modules: &HashSet<String>) {
+    if !module.is_core() {
+        symbol_table.borrow_mut().enter_module_scope();
+    }
+
+    let result = PassResultReference::new(PassResult::new());
+    let mut type_checker = TypeChecker::new(symbol_table.clone(), result.clone(), failed_modules);
+    type_checker.execute(module.get_ast_mut());
+
+    if !module.is_core() {
+        symbol_table.borrow_mut().leave_module_scope(module.get_path());
+    }
+
+    for error in result.borrow_mut().take_errors() {
+        let error = match error.downcast::<TypeError>() {
+            Ok(type_error) => CompileError::from(*type_error),
+            Err(error) => match error.downcast::<CompileError>() {
+                Ok(compile_error) => *compile_error,
+                Err(error) => CompileError::new(ErrorKind::Internal(error.to_string()), Span::new(0, 0, 1, 1)),
+            },
+        };
+        module.add_error(error);
+    }
+}
+
+/// `error` of resolving `module_id` as a compile error at `span`
+fn resolve_error(error: ResolveError, module_id: &str, span: Span) -> CompileError {
+    let kind = match error {
+        ResolveError::NotFound(_) => ErrorKind::ModuleNotFound(module_id.to_owned()),
+        ResolveError::Io(module_path, error) => ErrorKind::ModuleNotReadable(module_path, error.to_string()),
+    };
+    CompileError::new(kind, span)
 }
 
+/// paths of the modules `module_path` imports or re-exports from, directly or indirectly, in `reachable`
+fn find_reachable_modules(module_path: &str, modules: &HashMap<String, Rc<Module>>, reachable: &mut HashSet<String>) {
+    let module = match modules.get(module_path) {
+        Some(module) => module,
+        None => return,
+    };
+
+    let imports = module.find_imports().into_iter().filter_map(|import| import.module_path.as_ref());
+    let exports = module.find_exports().into_iter().filter_map(|export| export.module_path.as_ref());
+    for path in imports.chain(exports) {
+        if reachable.insert(path.to_owned()) {
+            find_reachable_modules(path, modules, reachable);
+        }
+    }
+}
+
+/// modules checked together share one `Compilation` each, a module imported by several of them is checked once
 pub struct Compilation {
-    symbol_table: Option<SymbolTable>,
-    module: Module
+    symbol_table: SymbolTableReference,
+    module: Rc<Module>,
+    dependencies: Vec<Rc<Module>>,
 }
 
 impl Compilation {
-    fn new(symbol_table: SymbolTable, module: Module) -> Compilation {
+    fn new(symbol_table: SymbolTableReference, module: Rc<Module>, dependencies: Vec<Rc<Module>>) -> Compilation {
         Compilation {
-            symbol_table: Some(symbol_table),
+            symbol_table: symbol_table,
             module: module,
+            dependencies: dependencies,
         }
     }
 
-    pub fn get_ast_mut(&mut self) -> &mut Vec<ItemKind> {
-        self.module.get_ast_mut()
+    pub fn get_module(&self) -> &Module {
+        &self.module
+    }
+
+    /// modules imported directly or indirectly by the compiled module, in the order they were checked
+    pub fn get_dependencies(&self) -> &[Rc<Module>] {
+        &self.dependencies
+    }
+
+    /// errors of every module with the module they occur in, dependencies first
+    pub fn get_errors(&self) -> Vec<(&Module, &CompileError)> {
+        self.dependencies.iter().chain(Some(&self.module))
+            .flat_map(|module| module.get_errors().iter().map(move |error| (&**module, error)))
+            .collect()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.dependencies.iter().chain(Some(&self.module)).any(|module| module.has_error())
+    }
+
+    /// `#[repr(C)]` rust source mirroring the exported structures and uniform blocks under `rule`
+    pub fn generate_rust_structs(&self, rule: LayoutRule) -> CompileResult<String> {
+        if let Some(error) = self.module.get_error() {
+            return Err(error.clone());
+        }
+
+        let symbol_table = self.symbol_table.borrow();
+        Ok(generate_rust_structs(self.module.get_ast(), &symbol_table, rule)?)
+    }
+
+    /// `#[repr(C)]` rust source mirroring the structures `program` passes between its stages or reads from uniforms
+    pub fn generate_program_rust_structs(&self, program: &ProgramDefinition, rule: LayoutRule) -> CompileResult<String> {
+        if let Some(error) = self.module.get_error() {
+            return Err(error.clone());
+        }
+
+        let variables = find_program_variables(self.module.get_ast(), program);
+        let symbol_table = self.symbol_table.borrow();
+        Ok(generate_program_rust_structs(self.module.get_ast(), &symbol_table, program, &variables, rule)?)
+    }
+
+    /// memory layout of the structure or uniform block `type_ref` under `rule`
+    pub fn find_struct_layout(&self, type_ref: TypeReference, rule: LayoutRule) -> Option<StructLayout> {
+        let symbol_table = self.symbol_table.borrow();
+        let members = symbol_table.find_type(type_ref)?.get_member()?;
+        members.layout(rule, |member_type| symbol_table.find_type_shape(member_type))
     }
 }
 
 pub struct Compiler {
     resolver: Box<ModuleResolver>,
+    search_roots: Vec<String>,
+    core_source: String,
 }
 
 impl Compiler {
     
+    /// modules are resolved by `resolver`, those it does not find by `std_resolver`
     pub fn new(resolver: Box<ModuleResolver>) -> Compiler {
+        let mut layers = OverlayResolver::new(Box::new(std_resolver()));
+        layers.add_overlay(resolver);
+
         Compiler {
-            resolver: resolver,
+            resolver: Box::new(layers),
+            search_roots: Vec::new(),
+            core_source: default_core_source(),
         }
     }
 
+    /// replaces the core module declaring the primitive types and builtins, see `default_core_source`
+    pub fn set_core_source(&mut self, source: &str) {
+        self.core_source = source.to_owned();
+    }
+
+    /// directory searched for imports that are not found next to the importing module
+    pub fn add_search_root(&mut self, root: &str) {
+        self.search_roots.push(root.to_owned());
+    }
+
     pub fn compile_module(&mut self, module_path: &str) -> CompileResult<Compilation> {
-        let source = match self.resolver.resolve(module_path) {
-            Ok(source) => source,
-            Err(_) => return Err(CompileError::unknown()),
-        };
+        Ok(self.compile_modules(&[module_path.to_owned()])?.pop().unwrap())
+    }
 
-        let ast = parse_str(&source)?;
+    /// compiles the modules at `module_paths` in one symbol table, one compilation for each path in the same order
+    /// modules imported by several of them are loaded and checked once and shared by their compilations
+    pub fn compile_modules(&mut self, module_paths: &[String]) -> CompileResult<Vec<Compilation>> {
+        let mut roots = Vec::new();
+        for module_path in module_paths {
+            match self.resolver.canonicalize(&module_path::resolve_relative(module_path, None)) {
+                Ok(module_path) => roots.push(module_path),
+                Err(error) => return Err(resolve_error(error, module_path, Span::new(0, 0, 1, 1))),
+            }
+        }
 
-        let mut symbol_table = SymbolTable::new(TypeEnvironment::new());
-        parse_core_modules(&mut symbol_table).unwrap();
+        let symbol_table = SymbolTableReference::new(SymbolTable::new(TypeEnvironment::new()));
+        load_core_module(&self.core_source, &symbol_table)?;
 
         let mut modules = HashMap::new();
-        self.load_modules(module_path, &mut modules)?;
+        let mut order = Vec::new();
+        for root in &roots {
+            if !modules.contains_key(root) {
+                self.load_modules(root, &mut modules, &mut order, &mut Vec::new());
+            }
+        }
+        let failed_modules: HashSet<String> = modules.values().filter(|module| module.has_error()).map(|module| module.get_path().to_owned()).collect();
 
-        let mut module = Module::new(module_path.to_owned(), source, ast, false);
+        // imports come before their importers
+        let mut checked = HashMap::new();
+        for path in &order {
+            let mut module = modules.remove(path).unwrap();
+            check_module(&symbol_table, &mut module, &failed_modules);
+            checked.insert(path.to_owned(), Rc::new(module));
+        }
 
-        // TODO insert pass system here
+        Ok(roots.iter().map(|root| {
+            let mut reachable = HashSet::new();
+            find_reachable_modules(root, &checked, &mut reachable);
+            let dependencies = order.iter().filter(|path| *path != root && reachable.contains(*path)).map(|path| checked[path].clone()).collect();
+            Compilation::new(symbol_table.clone(), checked[root].clone(), dependencies)
+        }).collect())
+    }
+
+    /// canonical path of `module_id` imported by the module at `importer`
+    /// paths starting with `.` are only looked up next to the importer, other paths fall back to the search roots
+    /// and last to the root of the resolver, which holds standard library ids like `std/math`
+    fn find_module(&mut self, module_id: &str, importer: &str) -> Result<String, ResolveError> {
+        let mut candidates = vec![module_path::resolve_relative(module_id, Some(importer))];
+        if !module_id.starts_with('.') {
+            for root in &self.search_roots {
+                candidates.push(module_path::join(root, module_id));
+            }
+            candidates.push(module_path::join("", module_id));
+        }
+
+        for candidate in candidates {
+            match self.resolver.canonicalize(&candidate) {
+                Err(ResolveError::NotFound(_)) => continue,
+                result => return result,
+            }
+        }
 
-        Ok(Compilation::new(symbol_table, module))
+        Err(ResolveError::NotFound(module_id.to_owned()))
     }
 
-    fn load_modules(&mut self, module_path: &str, modules: &mut HashMap<String, Module>) -> CompileResult<()> {
-        let source = match self.resolver.resolve(module_path) {
-            Ok(source) => source,
-            Err(_) => return Err(CompileError::unknown()),
+    /// canonical path of `module_id` imported at `span` by the module at `module_path`
+    /// importing a module of the `trail`, which is still being loaded, closes a cycle
+    fn find_import(&mut self, module_id: &str, module_path: &str, span: Span, trail: &[(String, Span)]) -> CompileResult<String> {
+        let import_path = self.find_module(module_id, module_path).map_err(|error| resolve_error(error, module_id, span))?;
+
+        let cycle_start = match trail.iter().position(|&(ref path, _)| *path == import_path) {
+            Some(start) => Some(start),
+            None if import_path == module_path => Some(trail.len()),
+            None => None,
         };
+        if let Some(start) = cycle_start {
+            let mut cycle = trail[start..].to_vec();
+            cycle.push((module_path.to_owned(), span));
+            return Err(CompileError::new(ErrorKind::ImportCycle(cycle), span));
+        }
 
-        let ast = parse_str(&source)?;
-        let module = Module::new(module_path.to_owned(), source, ast, false);
+        Ok(import_path)
+    }
 
-        let imports: Vec<String> = module.find_imports().iter().map(|&i| i.module_id.to_owned()).collect();
+    /// loads the module at `module_path` and everything it imports
+    /// errors of reading, parsing and resolving imports are added to the module they occur in, loading goes on with the other modules
+    /// unresolved imports keep no module path and are skipped by the checker
+    /// `order` receives every loaded path after the paths it imports, a topological order for checking
+    /// `trail` holds the modules being loaded and the imports leading from each to the next
+    fn load_modules(&mut self, module_path: &str, modules: &mut HashMap<String, Module>, order: &mut Vec<String>, trail: &mut Vec<(String, Span)>) {
+        let mut module = match self.resolver.resolve(module_path).map(|module| module.source) {
+            Ok(source) => {
+                // the items that parsed are still checked, so their errors are reported along with the syntax errors
+                let (ast, syntax_errors) = parse_module(&source);
+                let mut module = Module::new(module_path.to_owned(), source, ast, false);
+                for error in syntax_errors {
+                    module.add_error(error);
+                }
+                module
+            },
+            Err(error) => {
+                let mut module = Module::new(module_path.to_owned(), String::new(), Vec::new(), false);
+                module.add_error(resolve_error(error, module_path, Span::new(0, 0, 1, 1)));
+                module
+            },
+        };
+
+        let mut imports = Vec::new();
+        let mut errors = Vec::new();
+        for import in module.find_imports_mut() {
+            match self.find_import(&import.module_id, module_path, import.span, trail) {
+                Ok(import_path) => {
+                    import.module_path = Some(import_path.clone());
+                    imports.push((import_path, import.span));
+                },
+                Err(error) => errors.push(error),
+            }
+        }
+        for export in module.find_exports_mut() {
+            let module_id = match export.module_id {
+                Some(ref module_id) => module_id.to_owned(),
+                None => continue,
+            };
+            match self.find_import(&module_id, module_path, export.span, trail) {
+                Ok(export_path) => {
+                    export.module_path = Some(export_path.clone());
+                    imports.push((export_path, export.span));
+                },
+                Err(error) => errors.push(error),
+            }
+        }
+        for error in errors {
+            module.add_error(error);
+        }
         modules.insert(module_path.to_owned(), module);
 
-        for import in imports {
+        for (import, span) in imports {
             if modules.contains_key(&import) {
                 continue;
             }
 
-            self.load_modules(&import, modules)?;
+            trail.push((module_path.to_owned(), span));
+            self.load_modules(&import, modules, order, trail);
+            trail.pop();
         }
-
-        Ok(())
+        order.push(module_path.to_owned());
     }
 }
 
@@ -99,29 +351,19 @@ impl Compiler {
 mod tests {
     use std::collections::HashMap;
     use super::*;
+    use ::type_system::error::ErrorKind as TypeErrorKind;
 
-    struct TestResolver {
-        map: HashMap<String, String>,
-    }
-    impl TestResolver {
-        pub fn new(map: HashMap<String, String>) -> TestResolver {
-            TestResolver {
-                map: map,
-            }
-        }
-    }
-    impl ModuleResolver for TestResolver {
-        fn resolve(&mut self, module_path: &str) -> Result<String, Box<Error>> {
-            match self.map.get(module_path) {
-                Some(s) => Ok(s.to_string()),
-                None => Err(Box::new(CompileError::unknown())),
-            }
+    fn test_resolver(map: HashMap<String, String>) -> Box<MemoryResolver> {
+        let mut resolver = MemoryResolver::new();
+        for (path, source) in map {
+            resolver.insert(&path, &source);
         }
+        Box::new(resolver)
     }
 
     #[test]
     fn test_create_compiler() {
-        let resolver = Box::new(TestResolver::new(HashMap::new()));
+        let resolver = test_resolver(HashMap::new());
         let compiler = Compiler::new(resolver);
     }
 
@@ -129,7 +371,7 @@ mod tests {
     fn test_compile_module() {
         let mut map = HashMap::new();
         map.insert("test".to_string(), "struct Test {}".to_string());
-        let resolver = Box::new(TestResolver::new(map));
+        let resolver = test_resolver(map);
         let mut compiler = Compiler::new(resolver);
 
         // TODO assert that two modules were loaded
@@ -141,9 +383,444 @@ mod tests {
         let mut map = HashMap::new();
         map.insert("a".to_string(), "import Test from 'b';".to_string());
         map.insert("b".to_string(), "struct Test {}".to_string());
-        let resolver = Box::new(TestResolver::new(map));
+        let resolver = test_resolver(map);
         let mut compiler = Compiler::new(resolver);
 
         assert!(compiler.compile_module("a").is_ok());
     }
+
+    #[test]
+    fn test_resolve_imports_relative_to_importer() {
+        let mut map = HashMap::new();
+        map.insert("shaders/a.xs".to_string(), "import A from './lighting/b.xs'; import C from '../common/c.xs';".to_string());
+        map.insert("shaders/lighting/b.xs".to_string(), "import C from '../../common/c.xs'; struct A {}".to_string());
+        map.insert("common/c.xs".to_string(), "struct C {}".to_string());
+        let resolver = test_resolver(map);
+        let mut compiler = Compiler::new(resolver);
+
+        let compilation = compiler.compile_module("./shaders/a.xs").unwrap();
+        let module = compilation.get_module();
+        let import_paths: Vec<Option<String>> = module.find_imports().iter().map(|i| i.module_path.clone()).collect();
+
+        assert_eq!(module.get_path(), "shaders/a.xs");
+        assert_eq!(import_paths, vec![Some("shaders/lighting/b.xs".to_string()), Some("common/c.xs".to_string())]);
+    }
+
+    #[test]
+    fn test_resolve_imports_from_search_roots() {
+        let mut map = HashMap::new();
+        map.insert("shaders/a.xs".to_string(), "import Light from 'lighting.xs';".to_string());
+        map.insert("libstd/lighting.xs".to_string(), "struct Light {}".to_string());
+        let resolver = test_resolver(map);
+        let mut compiler = Compiler::new(resolver);
+        compiler.add_search_root("libstd");
+
+        let compilation = compiler.compile_module("shaders/a.xs").unwrap();
+
+        assert_eq!(compilation.get_module().find_imports()[0].module_path, Some("libstd/lighting.xs".to_string()));
+    }
+
+    #[test]
+    fn test_relative_imports_skip_search_roots() {
+        let mut map = HashMap::new();
+        map.insert("a.xs".to_string(), "import Light from './lighting.xs';".to_string());
+        map.insert("libstd/lighting.xs".to_string(), "struct Light {}".to_string());
+        let resolver = test_resolver(map);
+        let mut compiler = Compiler::new(resolver);
+        compiler.add_search_root("libstd");
+
+        let error = compiler.compile_module("a.xs").unwrap().get_module().get_error().unwrap().clone();
+
+        assert_eq!(error.get_kind(), &ErrorKind::ModuleNotFound("./lighting.xs".to_string()));
+        assert_eq!(error.get_span(), Span::new(0, 34, 1, 1));
+        assert_eq!(error.to_string(), "Module \"./lighting.xs\" not found.");
+    }
+
+    fn compile_modules(modules: &[(&str, &str)]) -> Compilation {
+        let map = modules.iter().map(|&(path, source)| (path.to_string(), source.to_string())).collect();
+        let mut compiler = Compiler::new(test_resolver(map));
+        compiler.compile_module(modules[0].0).unwrap()
+    }
+
+    #[test]
+    fn test_bind_imported_struct() {
+        let compilation = compile_modules(&[
+            ("a.xs", "import Light from 'b.xs'; fn shade(light: Light) -> f32 { return light.intensity; } struct Lit { light: Light, }"),
+            ("b.xs", "struct Light { intensity: f32, } struct Hidden { x: f32, } export Light;"),
+        ]);
+
+        assert_eq!(None, compilation.get_module().get_error());
+        assert_eq!(compilation.get_dependencies().len(), 1);
+        assert_eq!(compilation.get_dependencies()[0].get_path(), "b.xs");
+    }
+
+    #[test]
+    fn test_imported_types_are_shared() {
+        let compilation = compile_modules(&[
+            ("a.xs", "import { Light, make_light } from 'b.xs'; import * from 'c.xs'; fn test() -> f32 { let light = make_light(); return brightness(light) * SCALE; }"),
+            ("b.xs", "struct Light { intensity: f32, } fn make_light() -> Light { return Light { intensity: 1.0, }; } export { Light, make_light };"),
+            ("c.xs", "import Light from 'b.xs'; const SCALE: f32 = 2.0; fn brightness(light: Light) -> f32 { return light.intensity; } export *;"),
+        ]);
+
+        assert_eq!(None, compilation.get_module().get_error());
+        let paths: Vec<&str> = compilation.get_dependencies().iter().map(|m| m.get_path()).collect();
+        assert_eq!(paths, vec!["b.xs", "c.xs"]);
+    }
+
+    #[test]
+    fn test_aliased_and_namespaced_imports() {
+        let compilation = compile_modules(&[
+            ("main.xs", "import { Light as PointLight } from 'point.xs'; import * as spot from 'spot.xs'; \
+                struct Lights { point: PointLight, spots: [spot::Light; 2], } \
+                fn test(light: spot::Light) -> f32 { let point = PointLight { intensity: spot::SCALE, }; return spot.shade(light) * point.intensity; }"),
+            ("point.xs", "struct Light { intensity: f32, } export Light;"),
+            ("spot.xs", "struct Light { angle: f32, } const SCALE: f32 = 2.0; fn shade(light: Light) -> f32 { return light.angle; } export *;"),
+        ]);
+
+        assert_eq!(None, compilation.get_module().get_error());
+    }
+
+    #[test]
+    fn test_namespaced_items_must_be_exported() {
+        let compilation = compile_modules(&[
+            ("main.xs", "import * as spot from 'spot.xs'; struct Lights { spot: spot::Hidden, }"),
+            ("spot.xs", "struct Hidden { angle: f32, }"),
+        ]);
+        let error = compilation.get_module().get_error().unwrap();
+
+        assert_eq!(error.get_kind(), &ErrorKind::TypeError(TypeError::new(Span::new(55, 12, 1, 56), TypeErrorKind::TypeNotFound("spot::Hidden".to_string()))));
+    }
+
+    #[test]
+    fn test_reexport_items() {
+        let compilation = compile_modules(&[
+            ("main.xs", "import { Light, SpotLight, PI, Shadow } from 'lib/facade.xs'; fn test(light: Light, spot: SpotLight) -> f32 { return light.intensity * spot.angle * PI; }"),
+            ("lib/facade.xs", "export { Light } from './point.xs'; export { Light as SpotLight } from './spot.xs'; export * from './constants.xs'; export { Shadow } from './shadow.xs';"),
+            ("lib/point.xs", "struct Light { intensity: f32, } export Light;"),
+            ("lib/spot.xs", "struct Light { angle: f32, } export Light;"),
+            ("lib/constants.xs", "const PI: f32 = 3.14; export *;"),
+            ("lib/shadow.xs", "import PI from './constants.xs'; struct Shadow { softness: f32, } export *; export { PI } from './constants.xs';"),
+        ]);
+
+        assert_eq!(None, compilation.get_module().get_error());
+        assert!(compilation.get_dependencies().iter().all(|m| m.get_error().is_none()));
+    }
+
+    #[test]
+    fn test_conflicting_reexports() {
+        let compilation = compile_modules(&[
+            ("facade.xs", "export * from 'point.xs';\nexport * from 'spot.xs';"),
+            ("point.xs", "struct Light { intensity: f32, } export Light;"),
+            ("spot.xs", "struct Light { angle: f32, } export Light;"),
+        ]);
+        let error = compilation.get_module().get_error().unwrap();
+
+        assert_eq!(error.get_kind(), &ErrorKind::TypeError(TypeError::new(Span::new(26, 24, 2, 1), TypeErrorKind::ExportConflict("Light".to_string()))));
+    }
+
+    #[test]
+    fn test_reexport_cycle() {
+        let error = compile_error(&[
+            ("a.xs", "export * from 'b.xs';"),
+            ("b.xs", "export * from 'a.xs';"),
+        ]);
+
+        assert_eq!(error.get_kind(), &ErrorKind::ImportCycle(vec![("a.xs".to_string(), Span::new(0, 21, 1, 1)), ("b.xs".to_string(), Span::new(0, 21, 1, 1))]));
+    }
+
+    #[test]
+    fn test_std_modules_type_check() {
+        for &module_id in &["std/math", "std/color", "std/lighting", "std/noise", "std/packing"] {
+            let mut compiler = Compiler::new(test_resolver(HashMap::new()));
+            let compilation = compiler.compile_module(module_id).unwrap();
+
+            assert_eq!(compilation.get_module().get_path(), module_id);
+            assert_eq!(None, compilation.get_module().get_error(), "{}", module_id);
+            for dependency in compilation.get_dependencies() {
+                assert_eq!(None, dependency.get_error(), "{}", dependency.get_path());
+            }
+        }
+    }
+
+    #[test]
+    fn test_unknown_functions_in_std_modules() {
+        let lighting = include_str!("../libstd/lighting.xs").replace("max(", "maximum(");
+        let compilation = compile_modules(&[
+            ("main.xs", "import lambert from 'std/lighting'; fn shade(normal: vec3, light: vec3) -> f32 { return lambert(normal, light); }"),
+            ("std/lighting", &lighting),
+        ]);
+
+        let errors: Vec<(&str, &TypeErrorKind)> = compilation.get_errors().into_iter().filter_map(|(module, error)| match *error.get_kind() {
+            ErrorKind::TypeError(ref error) => Some((module.get_path(), error.get_kind())),
+            _ => None,
+        }).collect();
+        assert_eq!(errors, vec![("std/lighting", &TypeErrorKind::FunctionNotFound("maximum".to_string()))]);
+    }
+
+    #[test]
+    fn test_import_std_modules() {
+        let compilation = compile_modules(&[
+            ("shaders/main.xs", "import { lambert, ggx_distribution } from 'std/lighting'; import * as math from 'std/math'; import YCoCg from 'std/color'; \
+                fn shade(normal: vec3, light: vec3, color: YCoCg) -> f32 { let diffuse = lambert(normal, light); let specular = ggx_distribution(math::HALF_PI, 0.5); return diffuse + specular + color.y; }"),
+        ]);
+
+        assert_eq!(None, compilation.get_module().get_error());
+        let paths: Vec<&str> = compilation.get_dependencies().iter().map(|module| module.get_path()).collect();
+        assert_eq!(paths, vec!["std/math", "std/lighting", "std/color"]);
+    }
+
+    #[test]
+    fn test_resolver_modules_shadow_std_modules() {
+        let compilation = compile_modules(&[
+            ("main.xs", "import { TAU } from 'std/math'; fn turn() -> f32 { return TAU; }"),
+            ("std/math", "const TAU: f32 = 6.0; export TAU;"),
+        ]);
+
+        assert_eq!(None, compilation.get_module().get_error());
+        assert_eq!(compilation.get_dependencies()[0].get_source(), "const TAU: f32 = 6.0; export TAU;");
+    }
+
+    #[test]
+    fn test_import_not_exported_item() {
+        let compilation = compile_modules(&[
+            ("a.xs", "import Hidden from 'b.xs';"),
+            ("b.xs", "struct Hidden { x: f32, }"),
+        ]);
+        let error = compilation.get_module().get_error().unwrap();
+
+        assert_eq!(error.get_kind(), &ErrorKind::TypeError(TypeError::new(Span::new(0, 26, 1, 1), TypeErrorKind::ImportNotExported("Hidden".to_string(), "b.xs".to_string()))));
+    }
+
+    #[test]
+    fn test_import_missing_item() {
+        let compilation = compile_modules(&[
+            ("a.xs", "import { Light, Shadow } from 'b.xs';"),
+            ("b.xs", "struct Light { x: f32, } export *;"),
+        ]);
+        let error = compilation.get_module().get_error().unwrap();
+
+        assert_eq!(error.get_kind(), &ErrorKind::TypeError(TypeError::new(Span::new(0, 37, 1, 1), TypeErrorKind::ImportNotFound("Shadow".to_string(), "b.xs".to_string()))));
+    }
+
+    fn compile_error(modules: &[(&str, &str)]) -> CompileError {
+        let map = modules.iter().map(|&(path, source)| (path.to_string(), source.to_string())).collect();
+        let mut compiler = Compiler::new(test_resolver(map));
+        let compilation = compiler.compile_module(modules[0].0).unwrap();
+        compilation.get_errors()[0].1.clone()
+    }
+
+    #[test]
+    fn test_report_all_errors() {
+        let compilation = compile_modules(&[
+            ("a.xs", "import Light from 'b.xs'; import Missing from 'missing.xs'; import Shadow from 'c.xs'; struct A { x: f33, light: Light, y: f34, } fn f(s: Shadow) -> f32 { return s.x; }"),
+            ("b.xs", "struct Light { x: f32, } export Light;"),
+            ("c.xs", "struct Shadow {"),
+        ]);
+
+        let errors: Vec<(&str, &ErrorKind)> = compilation.get_errors().into_iter().map(|(module, error)| (module.get_path(), error.get_kind())).collect();
+        assert_eq!(errors, vec![
+            ("c.xs", &ErrorKind::SyntaxError("unexpected end of input".to_string())),
+            ("a.xs", &ErrorKind::ModuleNotFound("missing.xs".to_string())),
+            ("a.xs", &ErrorKind::TypeError(TypeError::new(Span::new(101, 3, 1, 102), TypeErrorKind::TypeNotFound("f33".to_string())))),
+            ("a.xs", &ErrorKind::TypeError(TypeError::new(Span::new(123, 3, 1, 124), TypeErrorKind::TypeNotFound("f34".to_string())))),
+        ]);
+        assert!(compilation.get_dependencies()[0].get_errors().is_empty());
+    }
+
+    #[test]
+    fn test_reexport_items_of_failed_module() {
+        let compilation = compile_modules(&[
+            ("main.xs", "import { Shadow, BIAS } from 'facade.xs'; fn test(shadow: Shadow) -> f32 { return shadow.depth * BIAS; }"),
+            ("facade.xs", "export { Shadow, BIAS } from 'shadow.xs';"),
+            ("shadow.xs", "struct Shadow { depth: f32, } const BIAS: f32 = 0.1"),
+        ]);
+
+        let errors: Vec<(&str, &ErrorKind)> = compilation.get_errors().into_iter().map(|(module, error)| (module.get_path(), error.get_kind())).collect();
+        assert_eq!(errors, vec![
+            ("shadow.xs", &ErrorKind::SyntaxError("unexpected end of input".to_string())),
+        ]);
+    }
+
+    #[test]
+    fn test_compile_modules_share_dependencies() {
+        let map = [("a.xs", "import Light from 'lib.xs';"), ("b.xs", "import Light from 'lib.xs';"), ("lib.xs", "struct Light { x: f32, } export Light;")]
+            .iter().map(|&(path, source)| (path.to_string(), source.to_string())).collect();
+        let mut compiler = Compiler::new(test_resolver(map));
+        let compilations = compiler.compile_modules(&["a.xs".to_string(), "b.xs".to_string(), "lib.xs".to_string()]).unwrap();
+
+        let paths: Vec<&str> = compilations.iter().map(|compilation| compilation.get_module().get_path()).collect();
+        assert_eq!(paths, vec!["a.xs", "b.xs", "lib.xs"]);
+        assert!(compilations.iter().all(|compilation| !compilation.has_errors()));
+        assert!(Rc::ptr_eq(&compilations[0].get_dependencies()[0], &compilations[1].get_dependencies()[0]));
+        assert!(Rc::ptr_eq(&compilations[0].get_dependencies()[0], &compilations[2].module));
+        assert!(compilations[2].get_dependencies().is_empty());
+    }
+
+    #[test]
+    fn test_detect_import_cycle() {
+        let error = compile_error(&[
+            ("a.xs", "import B from 'b.xs';"),
+            ("b.xs", "struct B { x: f32, }\nimport C from 'c.xs'; export B;"),
+            ("c.xs", "import B from 'b.xs'; struct C { x: f32, } export C;"),
+        ]);
+
+        assert_eq!(error.get_kind(), &ErrorKind::ImportCycle(vec![
+            ("b.xs".to_string(), Span::new(21, 21, 2, 1)),
+            ("c.xs".to_string(), Span::new(0, 21, 1, 1)),
+        ]));
+        assert_eq!(error.get_span(), Span::new(0, 21, 1, 1));
+        assert_eq!(error.to_string(), "Import cycle: \"b.xs\" imports \"c.xs\", \"c.xs\" imports \"b.xs\".");
+    }
+
+    #[test]
+    fn test_detect_self_import() {
+        let error = compile_error(&[("a.xs", "import A from './a.xs';")]);
+
+        assert_eq!(error.get_kind(), &ErrorKind::ImportCycle(vec![("a.xs".to_string(), Span::new(0, 23, 1, 1))]));
+    }
+
+    #[test]
+    fn test_shared_imports_are_no_cycle() {
+        let compilation = compile_modules(&[
+            ("a.xs", "import B from 'b.xs'; import C from 'c.xs';"),
+            ("b.xs", "import D from 'd.xs'; struct B { d: D, } export B;"),
+            ("c.xs", "import D from 'd.xs'; struct C { d: D, } export C;"),
+            ("d.xs", "struct D { x: f32, } export D;"),
+        ]);
+
+        assert_eq!(None, compilation.get_module().get_error());
+        let paths: Vec<&str> = compilation.get_dependencies().iter().map(|m| m.get_path()).collect();
+        assert_eq!(paths, vec!["d.xs", "b.xs", "c.xs"]);
+    }
+
+    #[test]
+    fn test_check_module_checks_programs() {
+        let source = "primitive type vec4; struct Vertex { position: vec4, color: vec4, }
+            program Test {
+                stage vertex(input: Vertex) -> Vertex { return input; }
+                stage fragment(input: Vertex) -> vec4 { return input.color; }
+                stage fragment(input: Vertex) -> vec4 { return input.color; }
+            }";
+        let mut module = Module::new("test".to_owned(), source.to_owned(), parse_str(source).unwrap(), false);
+        check_module(&SymbolTableReference::new(SymbolTable::new(TypeEnvironment::new())), &mut module, &HashSet::new());
+
+        match module.get_error().unwrap().get_kind() {
+            &ErrorKind::TypeError(ref error) => assert_eq!(error.get_kind(), &TypeErrorKind::ProgramTypeTooManyStageInstances("Test".to_string(), "fragment".to_string())),
+            kind => panic!("unexpected error {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn test_core_module_type_checks() {
+        let symbol_table = SymbolTableReference::new(SymbolTable::new(TypeEnvironment::new()));
+        let core_module = load_core_module(&default_core_source(), &symbol_table).unwrap();
+
+        assert_eq!(None, core_module.get_error());
+        let symbol_table = symbol_table.borrow();
+        assert!(symbol_table.find_type_ref("f32").is_some());
+        assert!(symbol_table.find_type_ref("vec4").is_some());
+    }
+
+    #[test]
+    fn test_core_types_are_global() {
+        let compilation = compile_modules(&[
+            ("a.xs", "import Light from 'b.xs'; fn shade(light: Light, color: vec4) -> f32 { return light.intensity; }"),
+            ("b.xs", "struct Light { intensity: f32, position: vec3, } export Light;"),
+        ]);
+
+        assert_eq!(None, compilation.get_module().get_error());
+        assert_eq!(None, compilation.get_dependencies()[0].get_error());
+    }
+
+    #[test]
+    fn test_alternative_core_module() {
+        let map = vec![("a.xs".to_string(), "struct Light { intensity: f16, color: f32, }".to_string())].into_iter().collect();
+        let mut compiler = Compiler::new(test_resolver(map));
+        compiler.set_core_source("primitive type f16;");
+        let compilation = compiler.compile_module("a.xs").unwrap();
+
+        let error = compilation.get_module().get_error().unwrap();
+        assert_eq!(error.get_kind(), &ErrorKind::TypeError(TypeError::new(Span::new(38, 3, 1, 39), TypeErrorKind::TypeNotFound("f32".to_string()))));
+    }
+
+    #[test]
+    fn test_invalid_core_module() {
+        let mut compiler = Compiler::new(test_resolver(HashMap::new()));
+        compiler.set_core_source("primitive type f32; primitive type f32;");
+
+        match compiler.compile_module("std/math").err().unwrap().get_kind() {
+            &ErrorKind::CoreModule(_) => (),
+            kind => panic!("unexpected error {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn test_generate_rust_structs() {
+        let compilation = ::testing::compile("struct Light { color: vec3, intensity: f32, } uniform Camera { view: mat3x3, light: Light, } export Light;");
+        assert_eq!(None, compilation.get_module().get_error());
+
+        let source = compilation.generate_rust_structs(LayoutRule::Std140).unwrap();
+        assert_eq!(source, "// generated by xshade, do not edit
+
+/// `Light` laid out with std140, 16 bytes
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct Light {
+    pub color: [f32; 3],
+    pub intensity: f32,
+}
+
+#[allow(dead_code)]
+const _LIGHT_SIZE: [(); 16] = [(); ::std::mem::size_of::<Light>()];
+
+/// `Camera` laid out with std140, 64 bytes
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct Camera {
+    pub view: [[f32; 4]; 3],
+    pub light: Light,
+}
+
+#[allow(dead_code)]
+const _CAMERA_SIZE: [(); 64] = [(); ::std::mem::size_of::<Camera>()];
+");
+    }
+
+    #[test]
+    fn test_generate_rust_structs_with_padding() {
+        let compilation = ::testing::compile("uniform Material { roughness: f32, albedo: vec3, metallic: f32, }");
+        let source = compilation.generate_rust_structs(LayoutRule::Std140).unwrap();
+
+        assert!(source.contains("    pub roughness: f32,\n    pub _padding0: [u8; 12],\n    pub albedo: [f32; 3],\n    pub metallic: f32,\n}"));
+        assert!(source.contains("const _MATERIAL_SIZE: [(); 32]"));
+    }
+
+    #[test]
+    fn test_generate_rust_structs_with_arrays() {
+        let compilation = ::testing::compile("const LIGHT_COUNT: u32 = 2;
+            struct Light { color: vec4, } uniform Lights { weights: [f32; 4], lights: [Light; LIGHT_COUNT], }");
+        assert_eq!(None, compilation.get_module().get_error());
+
+        let std140 = compilation.generate_rust_structs(LayoutRule::Std140).unwrap();
+        assert!(std140.contains("    pub weights: [[f32; 4]; 4],\n    pub lights: [Light; 2],\n}"));
+        assert!(std140.contains("const _LIGHTS_SIZE: [(); 96]"));
+
+        let std430 = compilation.generate_rust_structs(LayoutRule::Std430).unwrap();
+        assert!(std430.contains("    pub weights: [f32; 4],\n    pub lights: [Light; 2],\n}"));
+        assert!(std430.contains("const _LIGHTS_SIZE: [(); 48]"));
+    }
+
+    #[test]
+    fn test_generate_rust_structs_without_layout() {
+        let map = vec![("a.xs".to_string(), "struct Textured { t: sampler, } export Textured;".to_string())].into_iter().collect();
+        let mut compiler = Compiler::new(test_resolver(map));
+        compiler.set_core_source("primitive type sampler;");
+        let compilation = compiler.compile_module("a.xs").unwrap();
+        assert_eq!(None, compilation.get_module().get_error());
+
+        match compilation.generate_rust_structs(LayoutRule::Std430).err().unwrap().get_kind() {
+            &ErrorKind::TypeError(ref error) => assert_eq!(error.get_kind(), &TypeErrorKind::NoMemoryLayout("Textured".to_string())),
+            kind => panic!("unexpected error {:?}", kind),
+        }
+    }
 }
@@ -0,0 +1 @@
+pub mod rust_structs;
@@ -0,0 +1,275 @@
+use ::std::collections::HashSet;
+use ::ast::*;
+use ::string_builder::StringBuilder;
+use ::type_system::layout::{ LayoutRule, TypeShape, StructLayout };
+use ::type_system::symbol_table::SymbolTable;
+use ::type_system::type_environment::TypeReference;
+use ::type_system::error::{ TypeError, ErrorKind, TypeCheckResult };
+
+/// rust type of a single component of a primitive, e.g. `f32` for `vec3` or `u32` for `bvec2`
+fn rust_component_type(type_name: &str) -> Option<&'static str> {
+    match type_name {
+        "bool" => return Some("u32"),
+        "i32" => return Some("i32"),
+        "u32" => return Some("u32"),
+        "f32" => return Some("f32"),
+        "i64" => return Some("i64"),
+        "u64" => return Some("u64"),
+        "f64" => return Some("f64"),
+        _ => (),
+    }
+
+    let index = type_name.find("vec").or_else(|| type_name.find("mat"))?;
+    match &type_name[..index] {
+        "" => Some("f32"),
+        "i" => Some("i32"),
+        "u" | "b" => Some("u32"),
+        "d" => Some("f64"),
+        _ => None,
+    }
+}
+
+/// rust type with the same size as a primitive of `shape`, padded matrix columns are widened
+fn rust_primitive_type(shape: &TypeShape, component: &str, rule: LayoutRule) -> Option<String> {
+    match *shape {
+        TypeShape::Scalar(_) => Some(component.to_owned()),
+        TypeShape::Vector(_, components) => Some(format!("[{}; {}]", component, components)),
+        TypeShape::Matrix(component_size, columns, rows) => {
+            let column_stride = TypeShape::Vector(component_size, rows).array_stride(rule);
+            Some(format!("[[{}; {}]; {}]", component, column_stride / component_size, columns))
+        },
+        TypeShape::Array(_, _) | TypeShape::Struct(_) => None,
+    }
+}
+
+fn to_upper_snake_case(name: &str) -> String {
+    let mut result = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() && i > 0 && !result.ends_with('_') {
+            result.push('_');
+        }
+        result.extend(c.to_uppercase());
+    }
+    result
+}
+
+pub fn rule_name(rule: LayoutRule) -> &'static str {
+    match rule {
+        LayoutRule::Std140 => "std140",
+        LayoutRule::Std430 => "std430",
+        LayoutRule::Scalar => "scalar",
+    }
+}
+
+/// writes `#[repr(C)]` rust structures mirroring the memory layout of xshade structures
+pub struct RustStructGenerator<'a> {
+    symbol_table: &'a SymbolTable,
+    rule: LayoutRule,
+    generated: Vec<TypeReference>,
+    output: StringBuilder,
+}
+
+impl<'a> RustStructGenerator<'a> {
+    pub fn new(symbol_table: &'a SymbolTable, rule: LayoutRule) -> RustStructGenerator<'a> {
+        let mut output = StringBuilder::new(4096);
+        output.append("// generated by xshade, do not edit\n");
+
+        RustStructGenerator {
+            symbol_table: symbol_table,
+            rule: rule,
+            generated: Vec::new(),
+            output: output,
+        }
+    }
+
+    /// generates the structure `type_ref` after every structure it contains by value
+    pub fn generate_struct(&mut self, type_ref: TypeReference, span: Span) -> TypeCheckResult<()> {
+        if self.generated.contains(&type_ref) {
+            return Ok(());
+        }
+        self.generated.push(type_ref);
+
+        let symbol_table = self.symbol_table;
+        let type_definition = symbol_table.find_type_or_err(type_ref)?;
+        let type_name = type_definition.get_name();
+        let no_layout = || TypeError::new(span, ErrorKind::NoMemoryLayout(type_name.to_owned()));
+
+        let members = type_definition.get_member().ok_or_else(&no_layout)?;
+        let layout = members.layout(self.rule, |member_type| symbol_table.find_type_shape(member_type)).ok_or_else(&no_layout)?;
+
+        let mut fields = Vec::new();
+        for member in members.get_members() {
+            fields.push((member.member_name.to_owned(), self.field_type(member.member_type, span)?));
+        }
+
+        self.write_struct(type_name, &layout, fields);
+        Ok(())
+    }
+
+    fn field_type(&mut self, type_ref: TypeReference, span: Span) -> TypeCheckResult<String> {
+        let symbol_table = self.symbol_table;
+        let type_definition = symbol_table.find_type_or_err(type_ref)?;
+        let type_name = type_definition.get_name();
+        let no_layout = || TypeError::new(span, ErrorKind::NoMemoryLayout(type_name.to_owned()));
+
+        if type_definition.is_struct() {
+            self.generate_struct(type_ref, span)?;
+            return Ok(type_name.to_owned());
+        }
+
+        if let Some((element_type, length)) = type_definition.get_array() {
+            let element = self.field_type(element_type, span)?;
+            let element_shape = symbol_table.find_type_shape(element_type).ok_or_else(&no_layout)?;
+            let stride = element_shape.array_stride(self.rule);
+            if stride == element_shape.size(self.rule) {
+                return Ok(format!("[{}; {}]", element, length));
+            }
+
+            // only scalars and vectors are smaller than their array stride, they are widened to it
+            let component_type_name = symbol_table.find_type(element_type).map(|t| t.get_name()).unwrap_or_default();
+            return match (element_shape, rust_component_type(component_type_name)) {
+                (TypeShape::Scalar(size), Some(component)) | (TypeShape::Vector(size, _), Some(component)) => {
+                    Ok(format!("[[{}; {}]; {}]", component, stride / size, length))
+                },
+                _ => Err(no_layout()),
+            };
+        }
+
+        TypeShape::from_primitive_name(type_name)
+            .and_then(|shape| rust_primitive_type(&shape, rust_component_type(type_name)?, self.rule))
+            .ok_or_else(no_layout)
+    }
+
+    fn write_struct(&mut self, type_name: &str, layout: &StructLayout, fields: Vec<(String, String)>) {
+        let mut offset = 0;
+        let mut padding = 0;
+
+        self.output.append(&format!("\n/// `{}` laid out with {}, {} bytes\n", type_name, rule_name(self.rule), layout.size));
+        self.output.append("#[repr(C)]\n#[derive(Copy, Clone)]\n");
+        self.output.append(&format!("pub struct {} {{\n", type_name));
+
+        for (member_layout, (field_name, field_type)) in layout.members.iter().zip(fields) {
+            if member_layout.offset > offset {
+                self.write_padding(padding, member_layout.offset - offset);
+                padding += 1;
+            }
+            self.output.append(&format!("    pub {}: {},\n", field_name, field_type));
+            offset = member_layout.offset + member_layout.size;
+        }
+
+        if layout.size > offset {
+            self.write_padding(padding, layout.size - offset);
+        }
+
+        self.output.append("}\n\n");
+        self.output.append("#[allow(dead_code)]\n");
+        self.output.append(&format!("const _{}_SIZE: [(); {}] = [(); ::std::mem::size_of::<{}>()];\n", to_upper_snake_case(type_name), layout.size, type_name));
+    }
+
+    fn write_padding(&mut self, index: usize, size: u32) {
+        self.output.append(&format!("    pub _padding{}: [u8; {}],\n", index, size));
+    }
+
+    pub fn finish(self) -> String {
+        self.output.to_string().unwrap_or_default()
+    }
+}
+
+/// rust mirrors of the exported structures and the uniform blocks of a type checked module
+pub fn generate_rust_structs(ast: &Ast, symbol_table: &SymbolTable, rule: LayoutRule) -> TypeCheckResult<String> {
+    let mut exports = Vec::new();
+    let mut export_all = false;
+    for item in ast {
+        if let &ItemKind::Export(ref export_definition) = item {
+            // re-exported structures are generated with the module declaring them
+            if export_definition.module_id.is_some() {
+                continue;
+            }
+
+            for export_item in &export_definition.items {
+                match export_item {
+                    &ImportItem::Named(ref identifier) => exports.push(identifier.name.to_owned()),
+                    &ImportItem::All => export_all = true,
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    let mut generator = RustStructGenerator::new(symbol_table, rule);
+    for item in ast {
+        let (name, declaring_type, span) = match item {
+            &ItemKind::Struct(ref struct_definition) => {
+                if !export_all && !exports.contains(&struct_definition.struct_name.name) {
+                    continue;
+                }
+                (&struct_definition.struct_name, struct_definition.declaring_type, struct_definition.span)
+            },
+            &ItemKind::UniformBlock(ref block_definition) => (&block_definition.block_name, block_definition.declaring_type, block_definition.span),
+            _ => continue,
+        };
+
+        match declaring_type {
+            Some(type_ref) => generator.generate_struct(type_ref, span)?,
+            None => return Err(TypeError::new(name.span, ErrorKind::NoMemoryLayout(name.name.to_owned()))),
+        }
+    }
+
+    Ok(generator.finish())
+}
+
+/// rust mirrors of the structures `program` passes between its stages or reads from uniforms
+/// `variables` are the names the program reaches, see `find_program_variables`
+pub fn generate_program_rust_structs(ast: &Ast, symbol_table: &SymbolTable, program: &ProgramDefinition, variables: &HashSet<String>, rule: LayoutRule) -> TypeCheckResult<String> {
+    let mut types = Vec::new();
+    for stage in &program.program_stages {
+        match stage.function {
+            ProgramStageFunction::Inline(ref function) => {
+                types.extend(function.arguments.iter().map(|argument| (argument.argument_type, argument.span)));
+                types.push((function.return_type, function.span));
+            },
+            ProgramStageFunction::Reference(ref function_name) => {
+                let signature = stage.declaring_type
+                    .and_then(|type_ref| symbol_table.find_type(type_ref))
+                    .and_then(|type_definition| type_definition.get_call_signature());
+                if let Some(signature) = signature {
+                    types.extend(signature.get_arguments().iter().map(|&argument| (Some(argument), function_name.span)));
+                    types.push((signature.get_return_type(), function_name.span));
+                }
+            },
+        }
+    }
+
+    for item in ast {
+        match item {
+            &ItemKind::Uniform(ref uniform) if variables.contains(&uniform.uniform_name.name) => {
+                types.push((uniform.uniform_type, uniform.span));
+            },
+            &ItemKind::UniformBlock(ref block_definition) if variables.contains(&block_definition.block_name.name) => {
+                let name = &block_definition.block_name;
+                match block_definition.declaring_type {
+                    Some(type_ref) => types.push((Some(type_ref), block_definition.span)),
+                    None => return Err(TypeError::new(name.span, ErrorKind::NoMemoryLayout(name.name.to_owned()))),
+                }
+            },
+            _ => (),
+        }
+    }
+
+    let mut generator = RustStructGenerator::new(symbol_table, rule);
+    for (type_ref, span) in types {
+        // primitives need no mirror, arrays are mirrored by their element structure
+        let mut type_ref = match type_ref {
+            Some(type_ref) => type_ref,
+            None => continue,
+        };
+        while let Some((element_type, _)) = symbol_table.find_type(type_ref).and_then(|t| t.get_array()) {
+            type_ref = element_type;
+        }
+        if symbol_table.find_type(type_ref).map_or(false, |t| t.is_struct()) {
+            generator.generate_struct(type_ref, span)?;
+        }
+    }
+
+    Ok(generator.finish())
+}
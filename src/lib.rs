@@ -9,9 +9,13 @@ extern crate spirv_headers as spirv;
 mod data_structures;
 mod type_system;
 mod passes;
+mod generators;
+mod bundle;
 
 mod compiler;
 mod module;
+mod module_path;
+mod resolver;
 mod compile_error;
 mod ast;
 mod parser;
@@ -24,4 +28,9 @@ pub use ::ast::*;
 
 pub use compile_error::{ CompileError, ErrorKind as CompileErrorKind };
 pub use type_system::error::{ TypeError, ErrorKind as TypeErrorKind };
-pub use compiler::{ Compiler, ModuleResolver };
+pub use type_system::layout::{ LayoutRule, TypeShape, StructLayout, MemberLayout };
+pub use compiler::{ Compiler, Compilation, default_core_source, std_resolver };
+pub use resolver::{ ModuleResolver, ResolvedModule, ResolveError, MemoryResolver, DirectoryResolver, OverlayResolver };
+pub use module::Module;
+pub use bundle::{ Bundler, Backend, RustStructsBackend, BundleError };
+pub use bundle::archive::{ Archive, ArchiveProgram, ArchiveStage, ArchiveUniform, ArchiveUniformBlock, ArchiveOutput, ArchiveError, ARCHIVE_VERSION };
@@ -7,7 +7,7 @@ pub struct Module {
     source: String,
     ast: Vec<ItemKind>,
     is_core_module: bool,
-    error: Option<CompileError>,
+    errors: Vec<CompileError>,
 }
 
 impl Module {
@@ -18,10 +18,14 @@ impl Module {
             source: source,
             ast: ast,
             is_core_module: is_core_module,
-            error: None,
+            errors: Vec::new(),
         }
     }
 
+    pub fn get_ast(&self) -> &Vec<ItemKind> {
+        &self.ast
+    }
+
     pub fn get_ast_mut(&mut self) -> &mut Vec<ItemKind> {
         &mut self.ast
     }
@@ -35,18 +39,24 @@ impl Module {
     }
 
     pub fn has_error(&self) -> bool {
-        self.error.is_some()
+        !self.errors.is_empty()
     }
 
-    pub fn set_error(&mut self, error: CompileError) {
-        self.error = Some(error);
+    /// adds `error` unless the module already has the same error
+    pub fn add_error(&mut self, error: CompileError) {
+        if !self.errors.contains(&error) {
+            self.errors.push(error);
+        }
     }
 
+    /// the first error found in the module
     pub fn get_error(&self) -> Option<&CompileError> {
-        match self.error {
-            Some(ref e) => Some(&e),
-            None => None,
-        }
+        self.errors.first()
+    }
+
+    /// every error found in the module, loading errors first, then type errors in the order of the checking passes
+    pub fn get_errors(&self) -> &Vec<CompileError> {
+        &self.errors
     }
 
     pub fn is_core(&self) -> bool {
@@ -185,6 +195,28 @@ impl Module {
         primitives
     }
 
+    pub fn find_builtins(&self) -> Vec<&BuiltinDeclaration> {
+        let mut builtins = Vec::new();
+        for item in &self.ast {
+            match item {
+                &ItemKind::Builtin(ref b) => builtins.push(b),
+                _ => (),
+            }
+        }
+        builtins
+    }
+
+    pub fn find_builtins_mut(&mut self) -> Vec<&mut BuiltinDeclaration> {
+        let mut builtins = Vec::new();
+        for item in &mut self.ast {
+            match item {
+                &mut ItemKind::Builtin(ref mut b) => builtins.push(b),
+                _ => (),
+            }
+        }
+        builtins
+    }
+
     pub fn find_constants(&self) -> Vec<&ConstantDefinition> {
         let mut constants = Vec::new();
         for item in &self.ast {
@@ -207,6 +239,50 @@ impl Module {
         constants
     }
 
+    pub fn find_uniforms(&self) -> Vec<&UniformDefinition> {
+        let mut uniforms = Vec::new();
+        for item in &self.ast {
+            match item {
+                &ItemKind::Uniform(ref u) => uniforms.push(u),
+                _ => (),
+            }
+        }
+        uniforms
+    }
+
+    pub fn find_uniforms_mut(&mut self) -> Vec<&mut UniformDefinition> {
+        let mut uniforms = Vec::new();
+        for item in &mut self.ast {
+            match item {
+                &mut ItemKind::Uniform(ref mut u) => uniforms.push(u),
+                _ => (),
+            }
+        }
+        uniforms
+    }
+
+    pub fn find_uniform_blocks(&self) -> Vec<&UniformBlockDefinition> {
+        let mut blocks = Vec::new();
+        for item in &self.ast {
+            match item {
+                &ItemKind::UniformBlock(ref b) => blocks.push(b),
+                _ => (),
+            }
+        }
+        blocks
+    }
+
+    pub fn find_uniform_blocks_mut(&mut self) -> Vec<&mut UniformBlockDefinition> {
+        let mut blocks = Vec::new();
+        for item in &mut self.ast {
+            match item {
+                &mut ItemKind::UniformBlock(ref mut b) => blocks.push(b),
+                _ => (),
+            }
+        }
+        blocks
+    }
+
     pub fn find_casts(&self) -> Vec<&CastDeclaration> {
         let mut casts = Vec::new();
         for item in &self.ast {
@@ -0,0 +1,73 @@
+/// directory of a module path, empty for modules at the root
+pub fn parent(module_path: &str) -> &str {
+    match module_path.rfind('/') {
+        Some(index) => &module_path[..index],
+        None => "",
+    }
+}
+
+/// `relative` resolved against the directory `base`, absolute paths ignore `base`
+/// `\` separators become `/`, `.` and `..` segments are removed where possible
+pub fn join(base: &str, relative: &str) -> String {
+    let relative = relative.replace('\\', "/");
+    let path = if relative.starts_with('/') || base.is_empty() {
+        relative
+    } else {
+        format!("{}/{}", base.replace('\\', "/"), relative)
+    };
+    let is_absolute = path.starts_with('/');
+
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => (),
+            ".." => match segments.last() {
+                Some(&last) if last != ".." => { segments.pop(); },
+                _ if is_absolute => (),
+                _ => segments.push(".."),
+            },
+            segment => segments.push(segment),
+        }
+    }
+
+    let joined = segments.join("/");
+    if is_absolute {
+        format!("/{}", joined)
+    } else {
+        joined
+    }
+}
+
+/// path of `module_id` imported by the module at `importer`, or of a root module if there is no importer
+pub fn resolve_relative(module_id: &str, importer: Option<&str>) -> String {
+    match importer {
+        Some(importer) => join(parent(importer), module_id),
+        None => join("", module_id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn join_relative_paths() {
+        assert_eq!(join("shaders", "b.xs"), "shaders/b.xs");
+        assert_eq!(join("shaders/lighting", "../common/./math.xs"), "shaders/common/math.xs");
+        assert_eq!(join("", "../b.xs"), "../b.xs");
+        assert_eq!(join("shaders", "lighting\\brdf.xs"), "shaders/lighting/brdf.xs");
+    }
+
+    #[test]
+    fn join_absolute_paths() {
+        assert_eq!(join("shaders", "/lib/b.xs"), "/lib/b.xs");
+        assert_eq!(join("/shaders", "../../b.xs"), "/b.xs");
+    }
+
+    #[test]
+    fn resolve_relative_to_importer() {
+        assert_eq!(resolve_relative("b.xs", Some("examples/import_export/a.xs")), "examples/import_export/b.xs");
+        assert_eq!(resolve_relative("./a.xs", None), "a.xs");
+        assert_eq!(resolve_relative("b", Some("a")), "b");
+    }
+}
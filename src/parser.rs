@@ -6,6 +6,33 @@ use ::compile_error::ErrorKind as CompileErrorKind;
 
 type NomSpan<'a> = LocatedSpan<&'a str>;
 
+// custom error codes of the parser, `expectation` names what the parser expected where one of them failed
+const EXPECTED_ITEM: u32 = 1;
+const EXPECTED_MEMBER: u32 = 2;
+const EXPECTED_MEMBER_TYPE: u32 = 3;
+const EXPECTED_MEMBER_END: u32 = 4;
+const EXPECTED_ARGUMENTS: u32 = 5;
+const EXPECTED_ARGUMENT: u32 = 6;
+const EXPECTED_STATEMENT: u32 = 7;
+const EXPECTED_STATEMENT_END: u32 = 8;
+
+fn expectation(code: u32) -> &'static str {
+    match code {
+        EXPECTED_ITEM => "an item",
+        EXPECTED_MEMBER => "a member or `}`",
+        EXPECTED_MEMBER_TYPE => "`:` after the member name",
+        EXPECTED_MEMBER_END => "`,` or `}` after the member",
+        EXPECTED_ARGUMENTS => "`(` after the function name",
+        EXPECTED_ARGUMENT => "an argument or `)`",
+        EXPECTED_STATEMENT => "a statement or `}`",
+        EXPECTED_STATEMENT_END => "`;` after the statement",
+        _ => "something else",
+    }
+}
+
+// keywords starting an item, a broken item is skipped up to the next of them
+const ITEM_KEYWORDS: &[&str] = &["import", "export", "sampler", "const", "uniform", "struct", "program", "kernel", "fn", "primitive", "builtin", "operator", "implicit", "explicit"];
+
 named!(parse_identifier<NomSpan, NomSpan>,
     recognize!(
         do_parse!(
@@ -25,19 +52,59 @@ named!(parse_number<NomSpan, NomSpan>,
     )
 );
 
+named!(parse_attribute_arguments<NomSpan, (Vec<ExpressionStatement>, NomSpan)>,
+    do_parse!(
+        ws!(tag!("(")) >>
+        arguments: ws!(separated_list!(tag!(","), parse_expression)) >>
+        to: ws!(tag!(")")) >>
+        ((arguments, to))
+    )
+);
+
+named!(parse_attribute<NomSpan, Attribute>,
+    do_parse!(
+        from: ws!(tag!("@")) >>
+        attribute_name: parse_identifier >>
+        arguments: opt!(parse_attribute_arguments) >>
+        (Attribute {
+            span: Span::from_to(Span::from_nom_span(&from), match arguments {
+                Some((_, ref to)) => Span::from_nom_span(to),
+                None => Span::from_nom_span(&attribute_name),
+            }),
+            attribute_name: Identifier::from_nom_span(attribute_name),
+            arguments: arguments.map(|a| a.0).unwrap_or_default(),
+        })
+    )
+);
+
+named!(parse_attributes<NomSpan, Vec<Attribute>>,
+    many0!(parse_attribute)
+);
+
 named!(parse_constant<NomSpan, ItemKind>,
     do_parse!(
+        attributes: parse_attributes >>
         from: ws!(tag!("const")) >>
         constant_name: parse_symbol_declaration >>
         ws!(tag!(":")) >>
         constant_type_name: parse_type_declaration >>
+        constant_initializer: opt!(
+            do_parse!(
+                ws!(tag!("=")) >>
+                initializer: parse_expression >>
+                (initializer)
+            )
+        ) >>
         to: ws!(tag!(";")) >>
         (ItemKind::Constant(ConstantDefinition{
             span: Span::from_to(Span::from_nom_span(&from), Span::from_nom_span(&to)),
+            attributes: attributes,
             constant_name: constant_name,
             constant_variant: ConstantVariant::Constant,
             constant_type_name: constant_type_name,
             constant_type: None,
+            constant_initializer: constant_initializer,
+            constant_value: None,
         }))
     )
 );
@@ -45,10 +112,22 @@ named!(parse_constant<NomSpan, ItemKind>,
 named!(parse_import_item<NomSpan, ImportItem>,
     do_parse!(
         item: alt!(
+            do_parse!(
+                ws!(tag!("*")) >>
+                ws!(tag!("as")) >>
+                namespace: parse_identifier >>
+                (ImportItem::Namespace(Identifier::from_nom_span(namespace)))
+            ) |
             do_parse!(
                 ws!(tag!("*")) >> 
                 (ImportItem::All)
             ) |
+            do_parse!(
+                name: parse_identifier >>
+                ws!(tag!("as")) >>
+                alias: parse_identifier >>
+                (ImportItem::Aliased(Identifier::from_nom_span(name), Identifier::from_nom_span(alias)))
+            ) |
             do_parse!(
                 name: parse_identifier >> 
                 (ImportItem::Named(Identifier::from_nom_span(name)))
@@ -68,33 +147,18 @@ named!(parse_module_exports<NomSpan, Vec<ImportItem>>,
     )
 );
 
-named!(parse_path<NomSpan, NomSpan>,
-    recognize!(
-        do_parse!(
-            one_of!("_abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ") >>
-            many0!(
-                one_of!("\\/_abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789")
-            ) >>
-            ()
-        )
-    )
-);
-
-named!(parse_path_declaration<NomSpan, String>,
-    do_parse!(
-        name: ws!(parse_path) >>
-        (Identifier::from_nom_span(name).name.to_owned())
+named!(parse_string<NomSpan, NomSpan>,
+    alt!(
+        delimited!(tag!("'"), recognize!(many0!(none_of!("'\r\n"))), tag!("'")) |
+        delimited!(tag!("\""), recognize!(many0!(none_of!("\"\r\n"))), tag!("\""))
     )
 );
 
+// module ids are quoted paths like `'../common/math.xs'`
 named!(parse_module_id<NomSpan, String>,
     do_parse!(
-        ws!(opt!(tag!("'"))) >>
-        module_id: parse_path_declaration >> 
-        ws!(opt!(tag!("'"))) >>
-        (
-            module_id
-        )
+        module_id: ws!(parse_string) >>
+        (module_id.fragment.to_string())
     )
 );
 
@@ -110,6 +174,7 @@ named!(parse_import<NomSpan, ItemKind>,
                 span: Span::from_to(Span::from_nom_span(&from), Span::from_nom_span(&to)),
                 items: imports,
                 module_id: module_id,
+                module_path: None,
             }
         ))
     )
@@ -119,11 +184,14 @@ named!(parse_export<NomSpan, ItemKind>,
     do_parse!(
         from: ws!(tag!("export")) >>
         exports: parse_module_exports >> 
+        module_id: opt!(preceded!(ws!(tag!("from")), parse_module_id)) >>
         to: ws!(tag!(";")) >> 
         (ItemKind::Export(
             ExportDefinition{ 
                 span: Span::from_to(Span::from_nom_span(&from), Span::from_nom_span(&to)),
                 items: exports,
+                module_id: module_id,
+                module_path: None,
             }
         ))     
     )
@@ -131,6 +199,7 @@ named!(parse_export<NomSpan, ItemKind>,
 
 named!(parse_sampler<NomSpan, ItemKind>,
     do_parse!(
+        attributes: parse_attributes >>
         from: ws!(tag!("sampler")) >>
         sampler_name: parse_symbol_declaration >>
         ws!(tag!(":")) >>
@@ -138,64 +207,139 @@ named!(parse_sampler<NomSpan, ItemKind>,
         to: ws!(tag!(";")) >>
         (ItemKind::Constant(ConstantDefinition{
             span: Span::from_to(Span::from_nom_span(&from), Span::from_nom_span(&to)),
+            attributes: attributes,
             constant_name: sampler_name,
             constant_variant: ConstantVariant::Sampler,
             constant_type_name: sampler_type_name,
             constant_type: None,
+            constant_initializer: None,
+            constant_value: None,
+        }))
+    )
+);
+
+named!(parse_uniform<NomSpan, ItemKind>,
+    do_parse!(
+        from: ws!(tag!("uniform")) >>
+        uniform_name: parse_symbol_declaration >>
+        ws!(tag!(":")) >>
+        uniform_type_name: parse_type_declaration >>
+        to: ws!(tag!(";")) >>
+        (ItemKind::Uniform(UniformDefinition{
+            span: Span::from_to(Span::from_nom_span(&from), Span::from_nom_span(&to)),
+            uniform_name: uniform_name,
+            uniform_type_name: uniform_type_name,
+            uniform_type: None,
+        }))
+    )
+);
+
+named!(parse_uniform_block<NomSpan, ItemKind>,
+    do_parse!(
+        from: ws!(tag!("uniform")) >>
+        block_name: parse_symbol_declaration >>
+        ws!(tag!("{")) >>
+        member: parse_struct_members >>
+        (ItemKind::UniformBlock(UniformBlockDefinition{
+            span: Span::from_to(Span::from_nom_span(&from), Span::from_nom_span(&member.1)),
+            block_name: block_name,
+            block_member: member.0,
+            declaring_type: None,
         }))
     )
 );
 
 named!(parse_program<NomSpan, ItemKind>,
     do_parse!(
-        from: ws!(tag!("program")) >>
+        from: ws!(alt!(tag!("program") | tag!("kernel"))) >>
         program_name: parse_symbol_declaration >>
         ws!(tag!("{")) >>
-        program_stages: many0!(ws!(parse_stage)) >> 
-        opt!(ws!(tag!(","))) >>
-        to: ws!(tag!("}")) >>
+        program_stages: many_till!(terminated!(parse_program_stage, opt!(ws!(tag!(",")))), ws!(tag!("}"))) >>
         (ItemKind::Program(ProgramDefinition{
-            span: Span::from_to(Span::from_nom_span(&from), Span::from_nom_span(&to)),
+            span: Span::from_to(Span::from_nom_span(&from), Span::from_nom_span(&program_stages.1)),
+            program_kind: if from.fragment == "kernel" { ProgramKind::Kernel } else { ProgramKind::Graphics },
             program_name: program_name,
-            program_stages: program_stages,
+            program_stages: program_stages.0,
         }))
     )
 );
 
+fn parse_program_stage(input: NomSpan) -> IResult<NomSpan, ProgramStageDefinition> {
+    furthest_alt(input, &[parse_stage, parse_stage_reference])
+}
+
+named!(parse_stage_kind<NomSpan, NomSpan>,
+    ws!(alt!(
+        tag!("vertex") |
+        tag!("fragment") |
+        tag!("compute") |
+        tag!("geometry") |
+        tag!("tess_control") |
+        tag!("tess_eval")
+    ))
+);
+
 named!(parse_stage<NomSpan, ProgramStageDefinition>,    
     do_parse!(
+        attributes: parse_attributes >>
         from: ws!(tag!("stage")) >>
-        stage_name: ws!(alt!(tag!("vertex") | tag!("fragment"))) >> 
+        stage_name: parse_stage_kind >> 
         ws!(tag!("(")) >>
         arguments: ws!(separated_list!(tag!(","), parse_function_argument)) >>
-        ws!(tag!(")")) >>
+        add_return_error!(ErrorKind::Custom(EXPECTED_ARGUMENT), ws!(tag!(")"))) >>
         ws!(tag!("->")) >>
-        return_type_name: parse_type_declaration >>
+        return_type_name: parse_type_name >>
         block: parse_block_declaration >>
          (ProgramStageDefinition {
             span: Span::from_to(Span::from_nom_span(&from), block.span),
             stage_name: Identifier::from_nom_span(stage_name),
-            function: FunctionDeclaration {
+            stage_kind: StageKind::from_name(stage_name.fragment).unwrap(),
+            attributes: attributes,
+            workgroup_size: None,
+            function: ProgramStageFunction::Inline(FunctionDeclaration {
                 span: Span::from_to(Span::from_nom_span(&from), block.span),
+                attributes: Vec::new(),
                 function_name: Identifier::from_nom_span(stage_name),
                 arguments: arguments,
                 block: block,
                 return_type_name: return_type_name,
                 return_type: None,
                 declaring_type: None,
-            },
+                builtin: false,
+            }),
             declaring_type: None,
         })       
     )
 );
 
+named!(parse_stage_reference<NomSpan, ProgramStageDefinition>,
+    do_parse!(
+        attributes: parse_attributes >>
+        stage_name: parse_stage_kind >>
+        ws!(tag!(":")) >>
+        function_name: parse_symbol_declaration >>
+        opt!(ws!(tag!(","))) >>
+        (ProgramStageDefinition {
+            span: Span::from_to(Span::from_nom_span(&stage_name), function_name.span),
+            stage_name: Identifier::from_nom_span(stage_name),
+            stage_kind: StageKind::from_name(stage_name.fragment).unwrap(),
+            attributes: attributes,
+            workgroup_size: None,
+            function: ProgramStageFunction::Reference(function_name),
+            declaring_type: None,
+        })
+    )
+);
+
 named!(parse_struct_member<NomSpan, StructMemberDefinition>,
     do_parse!(
+        attributes: parse_attributes >>
         struct_member_name: parse_symbol_declaration >>
-        ws!(tag!(":")) >>
-        struct_member_type_name: parse_type_declaration >>
+        add_return_error!(ErrorKind::Custom(EXPECTED_MEMBER_TYPE), ws!(tag!(":"))) >>
+        struct_member_type_name: parse_type_name >>
         (StructMemberDefinition{
             span: Span::from_to(struct_member_name.span, struct_member_type_name.span),
+            attributes: attributes,
             struct_member_name: struct_member_name,
             struct_member_type_name: struct_member_type_name,
             struct_member_type: None,
@@ -203,18 +347,29 @@ named!(parse_struct_member<NomSpan, StructMemberDefinition>,
     )
 );
 
+// the members and the closing `}` of a struct or uniform block
+named!(parse_struct_members<NomSpan, (Vec<StructMemberDefinition>, NomSpan)>,
+    many_till!(
+        terminated!(
+            add_return_error!(ErrorKind::Custom(EXPECTED_MEMBER), parse_struct_member),
+            add_return_error!(ErrorKind::Custom(EXPECTED_MEMBER_END), alt!(ws!(tag!(",")) | peek!(ws!(tag!("}")))))
+        ),
+        ws!(tag!("}"))
+    )
+);
+
 named!(parse_struct<NomSpan, ItemKind>,
     do_parse!(
+        attributes: parse_attributes >>
         from: ws!(tag!("struct")) >>
         struct_name: parse_symbol_declaration >>
         ws!(tag!("{")) >>
-        member: ws!(separated_list!(tag!(","), parse_struct_member)) >>
-        opt!(ws!(tag!(","))) >>
-        to: ws!(tag!("}")) >>
+        member: parse_struct_members >>
         (ItemKind::Struct(StructDefinition{
-            span: Span::from_to(Span::from_nom_span(&from), Span::from_nom_span(&to)),
+            span: Span::from_to(Span::from_nom_span(&from), Span::from_nom_span(&member.1)),
+            attributes: attributes,
             struct_name: struct_name,
-            struct_member: member,
+            struct_member: member.0,
             declaring_type: None,
         }))
     )
@@ -224,7 +379,7 @@ named!(parse_function_argument<NomSpan, FunctionArgumentDeclaration>,
     do_parse!(
         argument_name: parse_symbol_declaration >>
         ws!(tag!(":")) >>
-        argument_type_name: parse_type_declaration >>
+        argument_type_name: parse_type_name >>
         (FunctionArgumentDeclaration{
             span: Span::from_to(argument_name.span, argument_type_name.span),
             argument_name: argument_name,
@@ -241,10 +396,67 @@ named!(parse_symbol_declaration<NomSpan, Identifier>,
     )
 );
 
+fn qualified_name(namespace: NomSpan, name: NomSpan) -> Identifier {
+    Identifier::new(&format!("{}::{}", namespace.fragment, name.fragment), Span::from_to(Span::from_nom_span(&namespace), Span::from_nom_span(&name)))
+}
+
+// items of namespaced imports are named `lighting::Light`
+named!(parse_qualified_name<NomSpan, Identifier>,
+    do_parse!(
+        namespace: ws!(parse_identifier) >>
+        tag!("::") >>
+        name: parse_identifier >>
+        (qualified_name(namespace, name))
+    )
+);
+
+named!(parse_path_declaration<NomSpan, Identifier>,
+    alt!(
+        parse_qualified_name |
+        parse_symbol_declaration
+    )
+);
+
+// functions of namespaced imports can also be called as `lighting.shade(...)`
+named!(parse_function_name<NomSpan, Identifier>,
+    alt!(
+        parse_qualified_name |
+        do_parse!(
+            namespace: ws!(parse_identifier) >>
+            tag!(".") >>
+            name: parse_identifier >>
+            peek!(ws!(tag!("("))) >>
+            (qualified_name(namespace, name))
+        ) |
+        parse_symbol_declaration
+    )
+);
+
+// array types like `[Light; MAX_LIGHTS]` are named by their spelling, the length is any constant expression
+named!(parse_array_type_name<NomSpan, TypeName>,
+    do_parse!(
+        from: ws!(tag!("[")) >>
+        element_type_name: parse_type_name >>
+        ws!(tag!(";")) >>
+        length_text: peek!(ws!(recognize!(parse_expression))) >>
+        length: parse_expression >>
+        to: ws!(tag!("]")) >>
+        (TypeName::new_array(&format!("[{}; {}]", element_type_name.name, length_text.fragment.trim()), Span::from_to(Span::from_nom_span(&from), Span::from_nom_span(&to)), element_type_name, length))
+    )
+);
+
+// types of struct members, function arguments and return values
+named!(parse_type_name<NomSpan, TypeName>,
+    alt!(
+        parse_array_type_name |
+        map!(parse_type_declaration, TypeName::from_identifier)
+    )
+);
+
 named!(parse_type_declaration<NomSpan, Identifier>,
     do_parse!(
-        name: ws!(parse_identifier) >>
-        (Identifier::from_nom_span(name))
+        name: parse_path_declaration >>
+        (name)
     )
 );
 
@@ -268,58 +480,173 @@ named!(parse_struct_instantiation<NomSpan, ExpressionStatement>,
         ws!(tag!("{")) >>
         struct_field_initializer: ws!(separated_list!(tag!(","), parse_struct_instantiation_field_initializer)) >>
         opt!(ws!(tag!(","))) >>
+        base: opt!(preceded!(ws!(tag!("..")), parse_expression)) >>
         to: ws!(tag!("}")) >>
         (ExpressionStatement::StructInstantiation(StructInstantiationExpression{
             span: Span::from_to(struct_type_name.span, Span::from_nom_span(&to)),
             struct_type_name: struct_type_name,
             struct_field_initializer: struct_field_initializer,
+            base: base.map(Box::new),
             struct_type: None,
         }))
     )
 );
 
-fn parse_int_literal(parts: NomSpan) -> ExpressionStatement {
-    let string: String = parts.fragment.to_string();
-    ExpressionStatement::Literal(LiteralExpression {
-        span: Span::from_nom_span(&parts),
-        value: string,
-        literal_expression_type: LiteralType::Int,
-        literal_type: None,
-    })
+named!(parse_hex_number<NomSpan, NomSpan>,
+    recognize!(
+        do_parse!(
+            alt!(tag!("0x") | tag!("0X")) >>
+            many1!(one_of!("0123456789abcdefABCDEF")) >>
+            ()
+        )
+    )
+);
+
+named!(parse_binary_number<NomSpan, NomSpan>,
+    recognize!(
+        do_parse!(
+            alt!(tag!("0b") | tag!("0B")) >>
+            many1!(one_of!("01")) >>
+            ()
+        )
+    )
+);
+
+named!(parse_exponent<NomSpan, NomSpan>,
+    recognize!(
+        do_parse!(
+            one_of!("eE") >>
+            opt!(one_of!("+-")) >>
+            parse_number >>
+            ()
+        )
+    )
+);
+
+// the minus sign belongs to the literal, so `-2147483648` is range checked as a whole
+named!(parse_float_number<NomSpan, NomSpan>,
+    recognize!(
+        do_parse!(
+            opt!(tag!("-")) >>
+            alt!(
+                do_parse!(
+                    parse_number >>
+                    tag!(".") >>
+                    parse_number >>
+                    opt!(parse_exponent) >>
+                    ()
+                ) |
+                do_parse!(
+                    parse_number >>
+                    parse_exponent >>
+                    ()
+                )
+            ) >>
+            ()
+        )
+    )
+);
+
+named!(parse_int_number<NomSpan, NomSpan>,
+    recognize!(
+        do_parse!(
+            opt!(tag!("-")) >>
+            alt!(parse_hex_number | parse_binary_number | parse_number) >>
+            ()
+        )
+    )
+);
+
+named!(parse_float_suffix<NomSpan, NomSpan>,
+    alt!(
+        tag!("f32") |
+        tag!("f64") |
+        tag!("f")
+    )
+);
+
+named!(parse_int_suffix<NomSpan, NomSpan>,
+    alt!(
+        tag!("i32") |
+        tag!("i64") |
+        tag!("u32") |
+        tag!("u64") |
+        tag!("u")
+    )
+);
+
+fn literal_suffix_type_name(suffix: NomSpan) -> Identifier {
+    let type_name = match suffix.fragment {
+        "f" => "f32",
+        "u" => "u32",
+        name => name,
+    };
+    Identifier::new(type_name, Span::from_nom_span(&suffix))
 }
 
-fn parse_float_literal(before: NomSpan, after: NomSpan) -> ExpressionStatement {
-    let mut a: String = before.fragment.to_string();
-    let b: String = after.fragment.to_string();
-    a.push_str(".");
-    a.push_str(&b);
+fn create_literal_expression(value: NomSpan, suffix: Option<NomSpan>, literal_expression_type: LiteralType) -> ExpressionStatement {
+    let span = match suffix {
+        Some(ref suffix) => Span::from_to(Span::from_nom_span(&value), Span::from_nom_span(suffix)),
+        None => Span::from_nom_span(&value),
+    };
+
     ExpressionStatement::Literal(LiteralExpression {
-        span: Span::from_to(Span::from_nom_span(&before), Span::from_nom_span(&after)),
-        value: a,
-        literal_expression_type: LiteralType::Float,
+        span: span,
+        value: value.fragment.to_string(),
+        literal_expression_type: literal_expression_type,
+        literal_type_name: suffix.map(literal_suffix_type_name),
         literal_type: None,
     })
 }
 
+named!(parse_float_literal<NomSpan, ExpressionStatement>,
+    do_parse!(
+        value: parse_float_number >>
+        suffix: opt!(parse_float_suffix) >>
+        (create_literal_expression(value, suffix, LiteralType::Float))
+    )
+);
+
+named!(parse_int_literal<NomSpan, ExpressionStatement>,
+    do_parse!(
+        value: parse_int_number >>
+        suffix: opt!(parse_int_suffix) >>
+        (create_literal_expression(value, suffix, LiteralType::Int))
+    )
+);
+
+named!(parse_bool_literal<NomSpan, ExpressionStatement>,
+    do_parse!(
+        value: alt!(tag!("true") | tag!("false")) >>
+        not!(one_of!("_abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789")) >>
+        (create_literal_expression(value, None, LiteralType::Bool))
+    )
+);
+
 named!(parse_float_literal_expression<NomSpan, ExpressionStatement>,
     do_parse!(
-        before: ws!(parse_number) >>
-        ws!(tag!(".")) >>
-        after: ws!(parse_number) >>
-        (parse_float_literal(before, after))
+        literal: ws!(parse_float_literal) >>
+        (literal)
     )
 );
 
 named!(parse_int_literal_expression<NomSpan, ExpressionStatement>,
     do_parse!(
-        numbers: ws!(parse_number) >>
-        (parse_int_literal(numbers))
+        literal: ws!(parse_int_literal) >>
+        (literal)
+    )
+);
+
+named!(parse_bool_literal_expression<NomSpan, ExpressionStatement>,
+    do_parse!(
+        literal: ws!(parse_bool_literal) >>
+        (literal)
     )
 );
 
-// TODO more literals
 named!(parse_literal_expression<NomSpan, ExpressionStatement>,
     alt!(
+        parse_bool_literal_expression |
         parse_float_literal_expression |
         parse_int_literal_expression
     )
@@ -328,11 +655,11 @@ named!(parse_literal_expression<NomSpan, ExpressionStatement>,
 named!(parse_infix_expression<NomSpan, ExpressionStatement>,
     do_parse!(
         left: parse_expression_no_left_recursion >>
-        operator: ws!(one_of!("+-*/")) >>
+        operator: parse_operator_type >>
         right: parse_expression >>
         (ExpressionStatement::Infix(InfixExpression{
             span: Span::from_to(left.get_span(), right.get_span()),
-            operator: char_to_operator(operator),
+            operator: operator,
             left_hand: Box::new(left),
             right_hand: Box::new(right),
             infix_type: None,
@@ -342,11 +669,12 @@ named!(parse_infix_expression<NomSpan, ExpressionStatement>,
 
 named!(parse_variable_expression<NomSpan, ExpressionStatement>,
     do_parse!(
-        variable_name: parse_symbol_declaration >>
+        variable_name: parse_path_declaration >>
         (ExpressionStatement::Variable(VariableExpression{
             span: variable_name.span.clone(),
             variable_name: variable_name,
             variable_type: None,
+            variable_kind: None,
         }))
     )
 );
@@ -360,7 +688,7 @@ named!(parse_call_expression<NomSpan, ExpressionStatement>,
 
 named!(parse_call<NomSpan, CallExpression>,
     do_parse!(
-        function_name: parse_symbol_declaration >>
+        function_name: parse_function_name >>
         ws!(tag!("(")) >>
         arguments: ws!(separated_list!(tag!(","), parse_expression)) >>
         to: ws!(tag!(")")) >>
@@ -373,27 +701,47 @@ named!(parse_call<NomSpan, CallExpression>,
     )
 );
 
-// TODO nested accessor expressions like `a.b.c`
 named!(parse_field_accessor_expression<NomSpan, ExpressionStatement>,
     do_parse!(
         variable_name: parse_symbol_declaration >>
-        ws!(tag!(".")) >>
-        field_name: parse_symbol_declaration >>
+        field_names: many1!(preceded!(ws!(tag!(".")), parse_symbol_declaration)) >>
         (ExpressionStatement::FieldAccessor(FieldAccessorExpression{
-            span: Span::from_to(variable_name.span, field_name.span),
+            span: Span::from_to(variable_name.span, field_names[field_names.len() - 1].span),
             variable_name: variable_name,
-            field_name: field_name,
+            variable_kind: None,
+            field_names: field_names,
             field_type: None,
         }))
     )
 );
 
+named!(parse_index_accessor_expression<NomSpan, ExpressionStatement>,
+    do_parse!(
+        variable_name: parse_symbol_declaration >>
+        field_names: many0!(preceded!(ws!(tag!(".")), parse_symbol_declaration)) >>
+        ws!(tag!("[")) >>
+        access_expression: parse_expression >>
+        to: ws!(tag!("]")) >>
+        element_field_names: many0!(preceded!(ws!(tag!(".")), parse_symbol_declaration)) >>
+        (ExpressionStatement::IndexAccessor(IndexAccesorExpression{
+            span: Span::from_to(variable_name.span, element_field_names.last().map(|f| f.span).unwrap_or_else(|| Span::from_nom_span(&to))),
+            variable_name: variable_name,
+            variable_kind: None,
+            field_names: field_names,
+            access_expression: Box::new(access_expression),
+            element_field_names: element_field_names,
+            accessor_type: None,
+        }))
+    )
+);
+
 named!(parse_expression_no_left_recursion<NomSpan, ExpressionStatement>,
     alt!(
         parse_struct_instantiation |
         parse_literal_expression |
-        parse_field_accessor_expression |
         parse_call_expression |
+        parse_index_accessor_expression |
+        parse_field_accessor_expression |
         parse_variable_expression
     )
 );
@@ -405,8 +753,9 @@ named!(parse_expression<NomSpan, ExpressionStatement>,
         parse_infix_expression |
         parse_struct_instantiation |
         parse_literal_expression |
-        parse_field_accessor_expression |
         parse_call_expression |
+        parse_index_accessor_expression |
+        parse_field_accessor_expression |
         parse_variable_expression
     )
 );
@@ -417,7 +766,7 @@ named!(parse_local_declaration<NomSpan, BlockStatement>,
         symbol_name: parse_symbol_declaration >>
         ws!(tag!("=")) >>
         expression: parse_expression >>
-        to: ws!(tag!(";")) >>
+        to: add_return_error!(ErrorKind::Custom(EXPECTED_STATEMENT_END), ws!(tag!(";"))) >>
         (BlockStatement::Local(
             LocalDeclaration{
                 span: Span::from_to(Span::from_nom_span(&from), Span::from_nom_span(&to)),
@@ -433,7 +782,7 @@ named!(parse_return_declaration<NomSpan, BlockStatement>,
     do_parse!(
         from: ws!(tag!("return")) >>
         expression: parse_expression >>
-        to: ws!(tag!(";")) >>
+        to: add_return_error!(ErrorKind::Custom(EXPECTED_STATEMENT_END), ws!(tag!(";"))) >>
         (BlockStatement::Return(ReturnDeclaration{
             span: Span::from_to(Span::from_nom_span(&from), Span::from_nom_span(&to)),
             expression: expression,
@@ -445,33 +794,28 @@ named!(parse_return_declaration<NomSpan, BlockStatement>,
 named!(parse_expression_declaration<NomSpan, BlockStatement>,
     do_parse!(
         expression: parse_expression >>
-        ws!(tag!(";")) >>
+        add_return_error!(ErrorKind::Custom(EXPECTED_STATEMENT_END), ws!(tag!(";"))) >>
         (BlockStatement::Expression(
             expression
         ))
     )
 );
 
+fn parse_block_statement(input: NomSpan) -> IResult<NomSpan, BlockStatement> {
+    furthest_alt(input, &[parse_local_declaration, parse_return_declaration, parse_expression_declaration])
+}
+
 named!(parse_block_statements<NomSpan, Vec<BlockStatement>>,
-    many0!(
-        ws!(
-            alt!(
-                parse_local_declaration |
-                parse_return_declaration |
-                parse_expression_declaration
-            )
-        )
-    )
+    many0!(ws!(parse_block_statement))
 );
 
 named!(parse_block_declaration<NomSpan, BlockDeclaration>,
     do_parse!(
         from: ws!(tag!("{")) >>
-        statements: parse_block_statements >>
-        to: ws!(tag!("}")) >>
+        statements: many_till!(add_return_error!(ErrorKind::Custom(EXPECTED_STATEMENT), parse_block_statement), ws!(tag!("}"))) >>
         (BlockDeclaration{
-            span: Span::from_to(Span::from_nom_span(&from), Span::from_nom_span(&to)),
-            statements: statements,
+            span: Span::from_to(Span::from_nom_span(&from), Span::from_nom_span(&statements.1)),
+            statements: statements.0,
         })
     )
 );
@@ -479,22 +823,53 @@ named!(parse_block_declaration<NomSpan, BlockDeclaration>,
 // TODO make return type optional
 named!(parse_function<NomSpan, ItemKind>,
     do_parse!(
+        attributes: parse_attributes >>
         from: ws!(tag!("fn")) >>
         function_name: parse_symbol_declaration >>
-        ws!(tag!("(")) >>
+        add_return_error!(ErrorKind::Custom(EXPECTED_ARGUMENTS), ws!(tag!("("))) >>
         arguments: ws!(separated_list!(tag!(","), parse_function_argument)) >>
-        ws!(tag!(")")) >>
+        add_return_error!(ErrorKind::Custom(EXPECTED_ARGUMENT), ws!(tag!(")"))) >>
         ws!(tag!("->")) >>
-        return_type_name: parse_type_declaration >>
+        return_type_name: parse_type_name >>
         block: parse_block_declaration >>
         (ItemKind::Function(FunctionDeclaration{
             span: Span::from_to(Span::from_nom_span(&from), block.span),
+            attributes: attributes,
             function_name: function_name,
             arguments: arguments,
             block: block,
             return_type_name: return_type_name,
             return_type: None,
             declaring_type: None,
+            builtin: false,
+        }))
+    )
+);
+
+named!(parse_builtin_function<NomSpan, ItemKind>,
+    do_parse!(
+        from: ws!(tag!("builtin")) >>
+        ws!(tag!("fn")) >>
+        function_name: parse_symbol_declaration >>
+        add_return_error!(ErrorKind::Custom(EXPECTED_ARGUMENTS), ws!(tag!("("))) >>
+        arguments: ws!(separated_list!(tag!(","), parse_function_argument)) >>
+        add_return_error!(ErrorKind::Custom(EXPECTED_ARGUMENT), ws!(tag!(")"))) >>
+        ws!(tag!("->")) >>
+        return_type_name: parse_type_name >>
+        to: ws!(tag!(";")) >>
+        (ItemKind::Function(FunctionDeclaration{
+            span: Span::from_to(Span::from_nom_span(&from), Span::from_nom_span(&to)),
+            attributes: Vec::new(),
+            function_name: function_name,
+            arguments: arguments,
+            block: BlockDeclaration {
+                span: Span::from_nom_span(&to),
+                statements: Vec::new(),
+            },
+            return_type_name: return_type_name,
+            return_type: None,
+            declaring_type: None,
+            builtin: true,
         }))
     )
 );
@@ -513,20 +888,65 @@ named!(parse_primitive<NomSpan, ItemKind>,
     )
 );
 
-named!(parse_operator_type<NomSpan, Operator>,
+named!(parse_builtin_stages<NomSpan, Vec<StageKind>>,
+    do_parse!(
+        ws!(tag!("(")) >>
+        stages: ws!(separated_list!(tag!(","), parse_stage_kind)) >>
+        ws!(tag!(")")) >>
+        (stages.iter().map(|s| StageKind::from_name(s.fragment).unwrap()).collect())
+    )
+);
+
+named!(parse_builtin<NomSpan, ItemKind>,
     do_parse!(
-        operator: ws!(one_of!("+-*/")) >>
-        (char_to_operator(operator))
+        from: ws!(tag!("builtin")) >>
+        builtin_name: parse_symbol_declaration >>
+        ws!(tag!(":")) >>
+        builtin_type_name: parse_type_declaration >>
+        input_stages: opt!(preceded!(ws!(tag!("in")), parse_builtin_stages)) >>
+        output_stages: opt!(preceded!(ws!(tag!("out")), parse_builtin_stages)) >>
+        to: ws!(tag!(";")) >>
+        (ItemKind::Builtin(BuiltinDeclaration{
+            span: Span::from_to(Span::from_nom_span(&from), Span::from_nom_span(&to)),
+            builtin_name: builtin_name,
+            builtin_type_name: builtin_type_name,
+            builtin_type: None,
+            input_stages: input_stages.unwrap_or_default(),
+            output_stages: output_stages.unwrap_or_default(),
+        }))
     )
 );
 
-fn char_to_operator(operator: char) -> Operator {
+/// symbols of the infix operators, longer symbols first so a prefix does not match early
+pub const OPERATOR_SYMBOLS: &[&str] = &["<<", ">>", "+", "-", "*", "/", "&", "|", "^"];
+
+fn parse_operator_symbol(input: NomSpan) -> IResult<NomSpan, Operator> {
+    let operator = OPERATOR_SYMBOLS.iter()
+        .find(|symbol| input.fragment.starts_with(*symbol))
+        .and_then(|symbol| str_to_operator(symbol).map(|operator| (symbol.len(), operator)));
+
     match operator {
-        '+' => Operator::Plus,
-        '-' => Operator::Minus,
-        '*' => Operator::Multiply,
-        '/' => Operator::Divide,
-        _ => panic!(""),
+        Some((length, operator)) => IResult::Done(input.slice(length..), operator),
+        None => IResult::Error(error_position!(ErrorKind::Tag, input)),
+    }
+}
+
+named!(parse_operator_type<NomSpan, Operator>,
+    ws!(parse_operator_symbol)
+);
+
+pub fn str_to_operator(operator: &str) -> Option<Operator> {
+    match operator {
+        "+" => Some(Operator::Plus),
+        "-" => Some(Operator::Minus),
+        "*" => Some(Operator::Multiply),
+        "/" => Some(Operator::Divide),
+        "&" => Some(Operator::BitwiseAnd),
+        "|" => Some(Operator::BitwiseOr),
+        "^" => Some(Operator::BitwiseXor),
+        "<<" => Some(Operator::ShiftLeft),
+        ">>" => Some(Operator::ShiftRight),
+        _ => None,
     }
 }
 
@@ -536,7 +956,7 @@ named!(parse_operator<NomSpan, ItemKind>,
         operator: parse_operator_type >>
         ws!(tag!("(")) >>
         arguments: ws!(separated_list!(tag!(","), parse_function_argument)) >>
-        ws!(tag!(")")) >>
+        add_return_error!(ErrorKind::Custom(EXPECTED_ARGUMENT), ws!(tag!(")"))) >>
         ws!(tag!("->")) >>
         return_type: parse_type_declaration >>
         to: ws!(tag!(";")) >>
@@ -583,25 +1003,230 @@ named!(parse_explicit_cast<NomSpan, ItemKind>,
     )
 );
 
-named!(parse<NomSpan, Vec<ItemKind>>,
-    many0!(
-        ws!(
-            alt!(
-                parse_import |
-                parse_export | 
-                parse_sampler |
-                parse_constant |
-                parse_struct |
-                parse_program |
-                parse_function |
-                parse_primitive |
-                parse_operator |
-                parse_implicit_cast |
-                parse_explicit_cast
-            )
-        )
-    )
-);
+fn parse_item(input: NomSpan) -> IResult<NomSpan, ItemKind> {
+    add_return_error!(input, ErrorKind::Custom(EXPECTED_ITEM), call!(furthest_alt, &[
+        parse_import,
+        parse_export,
+        parse_sampler,
+        parse_constant,
+        parse_uniform,
+        parse_uniform_block,
+        parse_struct,
+        parse_program,
+        parse_function,
+        parse_primitive,
+        parse_builtin_function,
+        parse_builtin,
+        parse_operator,
+        parse_implicit_cast,
+        parse_explicit_cast
+    ]))
+}
+
+type Parser<O> = for<'a> fn(NomSpan<'a>) -> IResult<NomSpan<'a>, O>;
+
+/// tries `parsers` in order like `alt!`, but fails with the error of the parser that got furthest into `input`
+fn furthest_alt<'a, O>(input: NomSpan<'a>, parsers: &[Parser<O>]) -> IResult<NomSpan<'a>, O> {
+    let mut furthest: Option<(usize, ::nom::Err<NomSpan<'a>>)> = None;
+    for parser in parsers {
+        match parser(input) {
+            IResult::Error(error) => {
+                let offset = Failure::from_error(&error).offset;
+                let is_further = match furthest {
+                    Some((furthest_offset, _)) => offset > furthest_offset,
+                    None => true,
+                };
+                if is_further {
+                    furthest = Some((offset, error));
+                }
+            },
+            result => return result,
+        }
+    }
+
+    match furthest {
+        Some((_, error)) => IResult::Error(error),
+        None => IResult::Error(error_position!(ErrorKind::Alt, input)),
+    }
+}
+
+/// offset of the token at `position`, after the whitespace the parsers skip
+fn token_offset(position: &NomSpan) -> usize {
+    position.offset + position.fragment.len() - position.fragment.trim_start().len()
+}
+
+/// the errors with a position that lead to `error`, from the outermost parser to the innermost
+fn error_path<'a, 'b>(error: &'b ::nom::Err<NomSpan<'a>>) -> Vec<(&'b ErrorKind, &'b NomSpan<'a>)> {
+    match *error {
+        ::nom::Err::Code(_) => Vec::new(),
+        ::nom::Err::Position(ref kind, ref position) => vec![(kind, position)],
+        // nested errors are listed from the innermost
+        ::nom::Err::Node(_, ref next) => next.iter().rev().flat_map(error_path).collect(),
+        ::nom::Err::NodePosition(ref kind, ref position, ref next) => {
+            let mut path = vec![(kind, position)];
+            path.extend(next.iter().rev().flat_map(error_path));
+            path
+        },
+    }
+}
+
+/// where a failed parse got furthest, what the parser expected there and the statement it failed in
+struct Failure {
+    offset: usize,
+    expected: Option<u32>,
+    statement: Option<usize>,
+}
+
+impl Failure {
+    fn from_error(error: &::nom::Err<NomSpan>) -> Failure {
+        let mut failure = Failure {
+            offset: 0,
+            expected: None,
+            statement: None,
+        };
+        // the innermost custom code so far and the offset it was added at
+        let mut expected = None;
+        let mut statement = None;
+        for (kind, position) in error_path(error) {
+            let offset = token_offset(position);
+            if let ErrorKind::Custom(code) = *kind {
+                expected = Some((offset, code));
+                if code == EXPECTED_STATEMENT {
+                    statement = Some(position.offset);
+                }
+            }
+
+            if offset >= failure.offset {
+                failure.offset = offset;
+                // an expectation only names what is missing where it was added
+                failure.expected = expected.and_then(|(expected_offset, code)| if expected_offset == offset { Some(code) } else { None });
+                failure.statement = statement;
+            }
+        }
+        failure
+    }
+
+    fn to_error(&self, program: &str) -> CompileError {
+        let found = token_at(&program[self.offset..]);
+        let found = if found.is_empty() { "end of input".to_string() } else { format!("`{}`", found) };
+        let message = match self.expected {
+            Some(code) => format!("expected {}, found {}", expectation(code), found),
+            None => format!("unexpected {}", found),
+        };
+
+        let line_start = program[..self.offset].rfind('\n').map_or(0, |index| index + 1);
+        let span = Span::new(self.offset, token_at(&program[self.offset..]).len(), program[..self.offset].matches('\n').count() + 1, self.offset - line_start + 1);
+        CompileError::new(CompileErrorKind::SyntaxError(message), span)
+    }
+}
+
+/// the word, operator or character `text` starts with
+fn token_at(text: &str) -> &str {
+    let word = text.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(text.len());
+    if word > 0 {
+        return &text[..word];
+    }
+    if ["->", "::", "..", "<<", ">>"].iter().any(|operator| text.starts_with(operator)) {
+        return &text[..2];
+    }
+    text.chars().next().map_or("", |c| &text[..c.len_utf8()])
+}
+
+fn skip_whitespace(program: &str, offset: usize) -> usize {
+    program.len() - program[offset..].trim_start().len()
+}
+
+fn is_identifier_at(program: &str, offset: usize) -> bool {
+    match program.as_bytes().get(offset) {
+        Some(&byte) => byte == b'_' || (byte as char).is_alphanumeric(),
+        None => false,
+    }
+}
+
+/// end of the broken item at `from`, after its `;` or closing `}` or before the next item keyword
+fn find_item_end(program: &str, from: usize) -> usize {
+    let bytes = program.as_bytes();
+    let mut depth = 0;
+    for offset in from..bytes.len() {
+        match bytes[offset] {
+            b'{' => depth += 1,
+            b'}' if depth <= 1 => return offset + 1,
+            b'}' => depth -= 1,
+            b';' if depth == 0 => return offset + 1,
+            _ if depth == 0 && offset > from && !is_identifier_at(program, offset - 1) => {
+                let is_keyword = ITEM_KEYWORDS.iter().any(|keyword| program[offset..].starts_with(keyword) && !is_identifier_at(program, offset + keyword.len()));
+                if is_keyword {
+                    return offset;
+                }
+            },
+            _ => {},
+        }
+    }
+    program.len()
+}
+
+/// end of the broken statement at `from`, after its `;` or before the `}` closing its block
+fn find_statement_end(program: &str, from: usize) -> usize {
+    let bytes = program.as_bytes();
+    let mut depth = 0;
+    for offset in from..bytes.len() {
+        match bytes[offset] {
+            b'{' => depth += 1,
+            b'}' if depth == 0 => return offset,
+            b'}' => depth -= 1,
+            b';' if depth == 0 => return offset + 1,
+            _ => {},
+        }
+    }
+    program.len()
+}
+
+/// `program` starting at `offset`, spans of the parsed items refer to `program`
+fn span_at<'a>(program: &'a str, offset: usize) -> NomSpan<'a> {
+    LocatedSpan {
+        offset: offset,
+        line: program[..offset].matches('\n').count() as u32 + 1,
+        fragment: &program[offset..],
+    }
+}
+
+/// parses the items of a module, an item that fails to parse is reported with what the parser expected where it failed
+/// a broken statement is blanked and its item parsed again, other broken items are skipped up to their end
+pub fn parse_module(program: &str) -> (Vec<ItemKind>, Vec<CompileError>) {
+    let mut text = program.to_owned();
+    let mut items = Vec::new();
+    let mut errors = Vec::new();
+    let mut offset = skip_whitespace(&text, 0);
+
+    while offset < text.len() {
+        let failure = match parse_item(span_at(&text, offset)) {
+            IResult::Done(remaining, item) => {
+                items.push(item);
+                offset = skip_whitespace(&text, remaining.offset);
+                continue;
+            },
+            IResult::Error(error) => Failure::from_error(&error),
+            IResult::Incomplete(_) => Failure {
+                offset: text.len(),
+                expected: None,
+                statement: None,
+            },
+        };
+        errors.push(failure.to_error(&text));
+
+        let statement = failure.statement.map(|from| (from, find_statement_end(&text, from)));
+        match statement {
+            Some((from, to)) if !text[from..to].trim().is_empty() => {
+                // spaces keep the spans of everything after the statement
+                let blank: String = text[from..to].bytes().map(|byte| if byte == b'\n' { '\n' } else { ' ' }).collect();
+                text.replace_range(from..to, &blank);
+            },
+            _ => offset = skip_whitespace(&text, find_item_end(&text, offset)),
+        }
+    }
+
+    (items, errors)
+}
 
 pub fn parse_block(program: &str) -> CompileResult<Vec<BlockStatement>> {
     let input = NomSpan::new(program);
@@ -617,15 +1242,11 @@ pub fn parse_block(program: &str) -> CompileResult<Vec<BlockStatement>> {
 }
 
 pub fn parse_str(program: &str) -> CompileResult<Vec<ItemKind>> {
-    let input = NomSpan::new(program);
-    match parse(input) {
-        IResult::Done(remaining, result) => {
-            if remaining.fragment.len() > 0 {
-                return Err(CompileError::new(CompileErrorKind::ParseError, Span::from_nom_span(&remaining)));
-            }
-            Ok(result)
-        },
-        _ => Err(CompileError::new(CompileErrorKind::ParseError, Span::new(0, 0, 1, 1))),
+    let (items, mut errors) = parse_module(program);
+    if errors.is_empty() {
+        Ok(items)
+    } else {
+        Err(errors.remove(0))
     }
 }
 
@@ -642,16 +1263,137 @@ mod tests {
                 ItemKind::Constant(
                     ConstantDefinition {
                         span: Span::new(0, 18, 1, 1),
+                        attributes: vec![],
                         constant_name: Identifier::new("mvp", Span::new(6, 3, 1, 7)),
                         constant_variant: ConstantVariant::Constant,
                         constant_type_name: Identifier::new("mat4x4", Span::new(11, 6, 1, 12)),
                         constant_type: None,
+                        constant_initializer: None,
+                        constant_value: None,
+                    }
+                )
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_parse_import_module_path() {
+        let code = "import { Light, * } from \"../common/lighting.xs\";";
+
+        assert_eq!(parse_str(code), Ok(
+            vec![
+                ItemKind::Import(
+                    ImportDefinition {
+                        span: Span::new(0, 49, 1, 1),
+                        items: vec![
+                            ImportItem::Named(Identifier::new("Light", Span::new(9, 5, 1, 10))),
+                            ImportItem::All,
+                        ],
+                        module_id: "../common/lighting.xs".to_string(),
+                        module_path: None,
                     }
                 )
             ]
         ));
     }
 
+    #[test]
+    fn test_parse_import_aliases() {
+        let code = "import { Light as PointLight, * as spot } from 'lights.xs';";
+
+        match parse_str(code).unwrap()[0] {
+            ItemKind::Import(ref import) => assert_eq!(import.items, vec![
+                ImportItem::Aliased(Identifier::new("Light", Span::new(9, 5, 1, 10)), Identifier::new("PointLight", Span::new(18, 10, 1, 19))),
+                ImportItem::Namespace(Identifier::new("spot", Span::new(35, 4, 1, 36))),
+            ]),
+            _ => panic!("expected import"),
+        }
+    }
+
+    #[test]
+    fn test_parse_reexport() {
+        let code = "export * from './lighting.xs'; export Light;";
+
+        assert_eq!(parse_str(code), Ok(
+            vec![
+                ItemKind::Export(ExportDefinition {
+                    span: Span::new(0, 30, 1, 1),
+                    items: vec![ImportItem::All],
+                    module_id: Some("./lighting.xs".to_string()),
+                    module_path: None,
+                }),
+                ItemKind::Export(ExportDefinition {
+                    span: Span::new(31, 13, 1, 32),
+                    items: vec![ImportItem::Named(Identifier::new("Light", Span::new(38, 5, 1, 39)))],
+                    module_id: None,
+                    module_path: None,
+                }),
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_parse_qualified_names() {
+        let code = "fn test(light: lighting::Light) -> f32 { return lighting.shade(lighting::Light { intensity: lighting::PI, }); }";
+
+        let ast = parse_str(code).unwrap();
+        let function = match ast[0] {
+            ItemKind::Function(ref function) => function,
+            _ => panic!("expected function"),
+        };
+        assert_eq!(function.arguments[0].argument_type_name, TypeName::new("lighting::Light", Span::new(15, 15, 1, 16)));
+
+        let call = match function.block.statements[0] {
+            BlockStatement::Return(ReturnDeclaration { expression: ExpressionStatement::Call(ref call), .. }) => call,
+            _ => panic!("expected call"),
+        };
+        assert_eq!(call.function_name.name, "lighting::shade");
+        match call.arguments[0] {
+            ExpressionStatement::StructInstantiation(ref instantiation) => {
+                assert_eq!(instantiation.struct_type_name.name, "lighting::Light");
+                match *instantiation.struct_field_initializer[0].initializer {
+                    ExpressionStatement::Variable(ref variable) => assert_eq!(variable.variable_name.name, "lighting::PI"),
+                    _ => panic!("expected variable"),
+                }
+            },
+            _ => panic!("expected struct instantiation"),
+        }
+    }
+
+    #[test]
+    fn test_parse_uniforms() {
+        let code = "uniform mvp: mat4x4; uniform Camera { view: mat4x4, }";
+
+        assert_eq!(parse_str(code), Ok(
+            vec![
+                ItemKind::Uniform(
+                    UniformDefinition {
+                        span: Span::new(0, 20, 1, 1),
+                        uniform_name: Identifier::new("mvp", Span::new(8, 3, 1, 9)),
+                        uniform_type_name: Identifier::new("mat4x4", Span::new(13, 6, 1, 14)),
+                        uniform_type: None,
+                    }
+                ),
+                ItemKind::UniformBlock(
+                    UniformBlockDefinition {
+                        span: Span::new(21, 32, 1, 22),
+                        block_name: Identifier::new("Camera", Span::new(29, 6, 1, 30)),
+                        block_member: vec![
+                            StructMemberDefinition {
+                                span: Span::new(38, 12, 1, 39),
+                                attributes: vec![],
+                                struct_member_name: Identifier::new("view", Span::new(38, 4, 1, 39)),
+                                struct_member_type_name: TypeName::new("mat4x4", Span::new(44, 6, 1, 45)),
+                                struct_member_type: None,
+                            },
+                        ],
+                        declaring_type: None,
+                    }
+                ),
+            ]
+        ));
+    }
+
     #[test]
     fn test_parse_program() {
         let code = "struct VertexInput {
@@ -681,18 +1423,21 @@ program VertexColored {
                 ItemKind::Struct(
                     StructDefinition {                     
                         span: Span::new(0, 59, 1, 1),
+                        attributes: vec![],
                         struct_name: Identifier::new("VertexInput", Span::new(7, 11, 1, 8)),
                         struct_member: vec![
                             StructMemberDefinition {
                                 span: Span::new(25, 14, 2, 5),
+                                attributes: vec![],
                                 struct_member_name: Identifier::new("position", Span::new(25, 8, 2, 5)),                   
-                                struct_member_type_name: Identifier::new("vec4", Span::new(35, 4, 2, 15)),
+                                struct_member_type_name: TypeName::new("vec4", Span::new(35, 4, 2, 15)),
                                 struct_member_type: None,
                             },
                             StructMemberDefinition {
                                 span: Span::new(45, 11, 3, 5),
+                                attributes: vec![],
                                 struct_member_name: Identifier::new("color", Span::new(45, 5, 3, 5)),
-                                struct_member_type_name: Identifier::new("vec4", Span::new(52, 4, 3, 12)),
+                                struct_member_type_name: TypeName::new("vec4", Span::new(52, 4, 3, 12)),
                                 struct_member_type: None,
                             }
                         ],
@@ -702,18 +1447,21 @@ program VertexColored {
                 ItemKind::Struct(
                     StructDefinition {
                         span: Span::new(61, 60, 6, 1),
+                        attributes: vec![],
                         struct_name: Identifier::new("VertexOutput", Span::new(68, 12, 6, 8)),
                         struct_member: vec![
                             StructMemberDefinition {
                                 span: Span::new(87, 14, 7, 5),
+                                attributes: vec![],
                                 struct_member_name: Identifier::new("position", Span::new(87, 8, 7, 5)),
-                                struct_member_type_name: Identifier::new("vec4", Span::new(97, 4, 7, 15)),
+                                struct_member_type_name: TypeName::new("vec4", Span::new(97, 4, 7, 15)),
                                 struct_member_type: None,
                             },
                             StructMemberDefinition {
                                 span: Span::new(107, 11, 8, 5),
+                                attributes: vec![],
                                 struct_member_name: Identifier::new("color", Span::new(107, 5, 8, 5)),
-                                struct_member_type_name: Identifier::new("vec4", Span::new(114, 4, 8, 12)),
+                                struct_member_type_name: TypeName::new("vec4", Span::new(114, 4, 8, 12)),
                                 struct_member_type: None,
                             }
                         ],
@@ -723,19 +1471,24 @@ program VertexColored {
                 ItemKind::Program(
                     ProgramDefinition {
                         span: Span::new(123, 270, 11, 1),
+                        program_kind: ProgramKind::Graphics,
                         program_name: Identifier::new("VertexColored", Span::new(131, 13, 11, 9)),
                         program_stages: vec![
                             ProgramStageDefinition {
                                 span: Span::new(151, 161, 12, 5),
+                                attributes: vec![],
                                 stage_name: Identifier::new("vertex", Span::new(157, 6, 12, 11)),
-                                function: FunctionDeclaration {
+                                stage_kind: StageKind::Vertex,
+                                workgroup_size: None,
+                                function: ProgramStageFunction::Inline(FunctionDeclaration {
                                     span: Span::new(151, 161, 12, 5),
+                                    attributes: vec![],
                                     function_name: Identifier::new("vertex", Span::new(157, 6, 12, 11)),
                                     arguments: vec![
                                         FunctionArgumentDeclaration {
                                             span: Span::new(164, 15, 12, 18), 
                                             argument_name: Identifier::new("in", Span::new(164, 2, 12, 18)),
-                                            argument_type_name: Identifier::new("VertexInput", Span::new(168, 11, 12, 22)),
+                                            argument_type_name: TypeName::new("VertexInput", Span::new(168, 11, 12, 22)),
                                             argument_type: None,
                                         }
                                     ],
@@ -757,7 +1510,8 @@ program VertexColored {
                                                                         FieldAccessorExpression {
                                                                             span: Span::new(251, 11, 14, 23),
                                                                             variable_name: Identifier::new("in", Span::new(251, 2, 14, 23)),
-                                                                            field_name: Identifier::new("position", Span::new(254, 8, 14, 26)),
+                                                                            variable_kind: None,
+                                                                            field_names: vec![Identifier::new("position", Span::new(254, 8, 14, 26))],
                                                                             field_type: None
                                                                         }
                                                                     )),
@@ -770,13 +1524,15 @@ program VertexColored {
                                                                         FieldAccessorExpression {
                                                                             span: Span::new(286, 8, 15, 23),
                                                                             variable_name: Identifier::new("in", Span::new(286, 2, 15, 23)),
-                                                                            field_name: Identifier::new("color", Span::new(289, 5, 15, 26)),
+                                                                            variable_kind: None,
+                                                                            field_names: vec![Identifier::new("color", Span::new(289, 5, 15, 26))],
                                                                             field_type: None
                                                                         }
                                                                     )),
                                                                     struct_field_type: None
                                                                 }
                                                             ],
+                                                            base: None,
                                                             struct_type: None
                                                         }
                                                     ),
@@ -785,23 +1541,28 @@ program VertexColored {
                                             )
                                         ]
                                     },
-                                    return_type_name: Identifier::new("VertexOutput", Span::new(184, 12, 12, 38)),
+                                    return_type_name: TypeName::new("VertexOutput", Span::new(184, 12, 12, 38)),
                                     return_type: None,
                                     declaring_type: None,
-                                },
+                                    builtin: false,
+                                }),
                                 declaring_type: None,
                             },
                             ProgramStageDefinition {
                                 span: Span::new(318, 73, 19, 5),
+                                attributes: vec![],
                                 stage_name: Identifier::new("fragment", Span::new(324, 8, 19, 11)),
-                                function: FunctionDeclaration {
+                                stage_kind: StageKind::Fragment,
+                                workgroup_size: None,
+                                function: ProgramStageFunction::Inline(FunctionDeclaration {
                                     span: Span::new(318, 73, 19, 5),
+                                    attributes: vec![],
                                     function_name: Identifier::new("fragment", Span::new(324, 8, 19, 11)),
                                     arguments: vec![
                                         FunctionArgumentDeclaration {
                                             span: Span::new(333, 16, 19, 20),
                                             argument_name: Identifier::new("in", Span::new(333, 2, 19, 20)),
-                                            argument_type_name: Identifier::new("VertexOutput", Span::new(337, 12, 19, 24)),
+                                            argument_type_name: TypeName::new("VertexOutput", Span::new(337, 12, 19, 24)),
                                             argument_type: None,
                                         }
                                     ],
@@ -815,7 +1576,8 @@ program VertexColored {
                                                         FieldAccessorExpression {
                                                             span: Span::new(376, 8, 20, 16),
                                                             variable_name: Identifier::new("in", Span::new(376, 2, 20, 16)),
-                                                            field_name: Identifier::new("color", Span::new(379, 5, 20, 19)),
+                                                            variable_kind: None,
+                                                            field_names: vec![Identifier::new("color", Span::new(379, 5, 20, 19))],
                                                             field_type: None,
                                                         }
                                                     ),
@@ -824,10 +1586,11 @@ program VertexColored {
                                             )
                                         ]
                                     },
-                                    return_type_name: Identifier::new("vec4", Span::new(354, 4, 19, 41)),
+                                    return_type_name: TypeName::new("vec4", Span::new(354, 4, 19, 41)),
                                     return_type: None,
                                     declaring_type: None,
-                                },
+                                    builtin: false,
+                                }),
                                 declaring_type: None,
                             }
                         ]
@@ -846,18 +1609,21 @@ program VertexColored {
                 ItemKind::Struct(
                     StructDefinition {
                         span: Span::new(0, 48, 1, 1),
+                        attributes: vec![],
                         struct_name: Identifier::new("VertexInput", Span::new(7, 11, 1, 8)),
                         struct_member: vec![
                             StructMemberDefinition {
                                 span: Span::new(21, 14, 1, 22),
+                                attributes: vec![],
                                 struct_member_name: Identifier::new("position", Span::new(21, 8, 1, 22)),
-                                struct_member_type_name: Identifier::new("vec3", Span::new(31, 4, 1, 32)),
+                                struct_member_type_name: TypeName::new("vec3", Span::new(31, 4, 1, 32)),
                                 struct_member_type: None,
                             },
                             StructMemberDefinition {
                                 span: Span::new(37, 8, 1, 38),
+                                attributes: vec![],
                                 struct_member_name: Identifier::new("uv", Span::new(37, 2, 1, 38)),
-                                struct_member_type_name: Identifier::new("vec2", Span::new(41, 4, 1, 42)),
+                                struct_member_type_name: TypeName::new("vec2", Span::new(41, 4, 1, 42)),
                                 struct_member_type: None,
                             },
                         ],
@@ -877,6 +1643,7 @@ program VertexColored {
                 ItemKind::Function(
                     FunctionDeclaration {
                         span: Span::new(0, 32, 1, 1),
+                        attributes: vec![],
                         function_name: Identifier::new("main", Span::new(3, 4, 1, 4)),
                         arguments: vec![],
                         block: BlockDeclaration {
@@ -890,6 +1657,7 @@ program VertexColored {
                                                 span: Span::new(26, 3, 1, 27),
                                                 value: "0.0".to_string(),
                                                 literal_expression_type: LiteralType::Float,
+                                                literal_type_name: None,
                                                 literal_type: None,
                                             }
                                         ),
@@ -898,15 +1666,45 @@ program VertexColored {
                                 )
                             ],
                         },
-                        return_type_name: Identifier::new("f32", Span::new(13, 3, 1, 14)),
+                        return_type_name: TypeName::new("f32", Span::new(13, 3, 1, 14)),
                         return_type: None,
                         declaring_type: None,
+                        builtin: false,
                     }
                 )
             ]
         ));
     }
 
+    #[test]
+    fn test_parse_literals() {
+        let code = "1.5e3f 0xFFu64 0b101 true -2147483648 -1.5";
+        let literals: Vec<(String, LiteralType, Option<String>)> = parse_block(&format!("return {};", code.replace(" ", "; return ")))
+            .unwrap()
+            .into_iter()
+            .map(|statement| match statement {
+                BlockStatement::Return(ReturnDeclaration { expression: ExpressionStatement::Literal(literal), .. }) => {
+                    (literal.value, literal.literal_expression_type, literal.literal_type_name.map(|t| t.name))
+                },
+                _ => panic!("expected a literal"),
+            })
+            .collect();
+
+        assert_eq!(literals, vec![
+            ("1.5e3".to_string(), LiteralType::Float, Some("f32".to_string())),
+            ("0xFF".to_string(), LiteralType::Int, Some("u64".to_string())),
+            ("0b101".to_string(), LiteralType::Int, None),
+            ("true".to_string(), LiteralType::Bool, None),
+            ("-2147483648".to_string(), LiteralType::Int, None),
+            ("-1.5".to_string(), LiteralType::Float, None),
+        ]);
+
+        match parse_block("return a -1;").unwrap()[0] {
+            BlockStatement::Return(ReturnDeclaration { expression: ExpressionStatement::Infix(ref infix), .. }) => assert_eq!(infix.operator, Operator::Minus),
+            ref statement => panic!("unexpected statement {:?}", statement),
+        }
+    }
+
     #[test]
     fn test_parse_primitive() {
         let code = "primitive type f32;";
@@ -924,6 +1722,15 @@ program VertexColored {
         ));
     }
 
+    #[test]
+    fn test_str_to_operator() {
+        let operators: Vec<Option<Operator>> = OPERATOR_SYMBOLS.iter().map(|symbol| str_to_operator(symbol)).collect();
+
+        assert!(operators.iter().all(|operator| operator.is_some()));
+        assert_eq!(str_to_operator("<"), None);
+        assert!(parse_str("operator < (lhs: f32, rhs: f32) -> bool;").is_err());
+    }
+
     #[test]
     fn test_parse_operator() {
         let code = "operator + (lhs: f32, rhs: f32) -> f32;";
@@ -938,13 +1745,13 @@ program VertexColored {
                             FunctionArgumentDeclaration {
                                 span: Span::new(12, 8, 1, 13),
                                 argument_name: Identifier::new("lhs", Span::new(12, 3, 1, 13)),
-                                argument_type_name: Identifier::new("f32", Span::new(17, 3, 1, 18)),
+                                argument_type_name: TypeName::new("f32", Span::new(17, 3, 1, 18)),
                                 argument_type: None,
                             },
                             FunctionArgumentDeclaration {
                                 span: Span::new(22, 8, 1, 23),
                                 argument_name: Identifier::new("rhs", Span::new(22, 3, 1, 23)),
-                                argument_type_name: Identifier::new("f32", Span::new(27, 3, 1, 28)),
+                                argument_type_name: TypeName::new("f32", Span::new(27, 3, 1, 28)),
                                 argument_type: None,
                             }
                         ],
@@ -955,6 +1762,49 @@ program VertexColored {
         ));
     }
 
+    #[test]
+    fn test_parse_shift_operator() {
+        let code = "operator << (lhs: u32, rhs: u32) -> u32;";
+
+        assert_eq!(parse_str(code), Ok(
+            vec![
+                ItemKind::Operator(
+                    OperatorDeclaration {
+                        span: Span::new(0, 40, 1, 1),
+                        operator: Operator::ShiftLeft,
+                        arguments: vec![
+                            FunctionArgumentDeclaration {
+                                span: Span::new(13, 8, 1, 14),
+                                argument_name: Identifier::new("lhs", Span::new(13, 3, 1, 14)),
+                                argument_type_name: TypeName::new("u32", Span::new(18, 3, 1, 19)),
+                                argument_type: None,
+                            },
+                            FunctionArgumentDeclaration {
+                                span: Span::new(23, 8, 1, 24),
+                                argument_name: Identifier::new("rhs", Span::new(23, 3, 1, 24)),
+                                argument_type_name: TypeName::new("u32", Span::new(28, 3, 1, 29)),
+                                argument_type: None,
+                            }
+                        ],
+                        return_type: Identifier::new("u32", Span::new(36, 3, 1, 37)),
+                    }
+                )
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_parse_bitwise_infix_expression() {
+        let statements = parse_block("return a & b;").unwrap();
+
+        match statements[0] {
+            BlockStatement::Return(ReturnDeclaration { expression: ExpressionStatement::Infix(ref infix), .. }) => {
+                assert_eq!(infix.operator, Operator::BitwiseAnd);
+            },
+            _ => panic!("expected an infix expression"),
+        }
+    }
+
     #[test]
     fn test_parse_implicit_cast() {
         let code = "implicit cast f32 -> f64;";
@@ -973,6 +1823,197 @@ program VertexColored {
         ));
     }
 
+    #[test]
+    fn test_parse_program_with_stage_references() {
+        let code = "program Flat { vertex: vertexShader, fragment: fragmentShader, }";
+
+        assert_eq!(parse_str(code), Ok(
+            vec![
+                ItemKind::Program(
+                    ProgramDefinition {
+                        span: Span::new(0, 64, 1, 1),
+                        program_kind: ProgramKind::Graphics,
+                        program_name: Identifier::new("Flat", Span::new(8, 4, 1, 9)),
+                        program_stages: vec![
+                            ProgramStageDefinition {
+                                span: Span::new(15, 20, 1, 16),
+                                attributes: vec![],
+                                stage_name: Identifier::new("vertex", Span::new(15, 6, 1, 16)),
+                                stage_kind: StageKind::Vertex,
+                                workgroup_size: None,
+                                function: ProgramStageFunction::Reference(Identifier::new("vertexShader", Span::new(23, 12, 1, 24))),
+                                declaring_type: None,
+                            },
+                            ProgramStageDefinition {
+                                span: Span::new(37, 24, 1, 38),
+                                attributes: vec![],
+                                stage_name: Identifier::new("fragment", Span::new(37, 8, 1, 38)),
+                                stage_kind: StageKind::Fragment,
+                                workgroup_size: None,
+                                function: ProgramStageFunction::Reference(Identifier::new("fragmentShader", Span::new(47, 14, 1, 48))),
+                                declaring_type: None,
+                            },
+                        ],
+                    }
+                )
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_parse_kernel_with_workgroup_size() {
+        let code = "kernel Blur { @workgroup_size(8, GROUP) compute: blur, }";
+
+        let ast = parse_str(code).unwrap();
+        match ast[0] {
+            ItemKind::Program(ref program) => {
+                assert_eq!(program.program_kind, ProgramKind::Kernel);
+                assert_eq!(program.program_stages[0].stage_kind, StageKind::Compute);
+
+                let workgroup_size = find_attribute(&program.program_stages[0].attributes, "workgroup_size").unwrap();
+                assert_eq!(workgroup_size.span, Span::new(14, 25, 1, 15));
+                assert_eq!(workgroup_size.arguments.len(), 2);
+            },
+            _ => panic!("expected program"),
+        }
+    }
+
+    #[test]
+    fn test_parse_builtins() {
+        let code = "builtin position: vec4 in(geometry) out(vertex, geometry); struct Output { @builtin(position) position: vec4, }";
+
+        let ast = parse_str(code).unwrap();
+        match ast[0] {
+            ItemKind::Builtin(ref builtin) => {
+                assert_eq!(builtin.builtin_name, Identifier::new("position", Span::new(8, 8, 1, 9)));
+                assert_eq!(builtin.input_stages, vec![StageKind::Geometry]);
+                assert_eq!(builtin.output_stages, vec![StageKind::Vertex, StageKind::Geometry]);
+            },
+            _ => panic!("expected builtin"),
+        }
+        match ast[1] {
+            ItemKind::Struct(ref output) => {
+                assert_eq!(output.struct_member[0].attributes[0].span, Span::new(75, 18, 1, 76));
+                assert_eq!(output.struct_member[0].get_builtin().unwrap().name, "position");
+            },
+            _ => panic!("expected struct"),
+        }
+    }
+
+    #[test]
+    fn test_parse_builtin_functions() {
+        let ast = parse_str("builtin fn max(a: f32, b: f32) -> f32;").unwrap();
+        match ast[0] {
+            ItemKind::Function(ref function) => {
+                assert!(function.builtin);
+                assert_eq!(function.span, Span::new(0, 38, 1, 1));
+                assert_eq!(function.function_name.name, "max");
+                assert_eq!(function.arguments.len(), 2);
+                assert_eq!(function.return_type_name.name, "f32");
+                assert!(function.block.statements.is_empty());
+            },
+            _ => panic!("expected function"),
+        }
+    }
+
+    #[test]
+    fn test_parse_attributes() {
+        let code = "@inline fn test() -> f32 { return 1.0; } struct Output { @location(0) @interpolate(flat) color: vec4, }";
+
+        let ast = parse_str(code).unwrap();
+        match ast[0] {
+            ItemKind::Function(ref function) => {
+                assert_eq!(function.attributes, vec![Attribute {
+                    span: Span::new(0, 7, 1, 1),
+                    attribute_name: Identifier::new("inline", Span::new(1, 6, 1, 2)),
+                    arguments: vec![],
+                }]);
+            },
+            _ => panic!("expected function"),
+        }
+        match ast[1] {
+            ItemKind::Struct(ref output) => {
+                let attributes = &output.struct_member[0].attributes;
+                assert_eq!(attributes.len(), 2);
+                assert_eq!(attributes[0].attribute_name.name, "location");
+                assert_eq!(attributes[0].span, Span::new(57, 12, 1, 58));
+                assert_eq!(attributes[1].get_name_argument(0).unwrap().name, "flat");
+            },
+            _ => panic!("expected struct"),
+        }
+    }
+
+    #[test]
+    fn test_parse_qualified_function_declaration_is_a_syntax_error() {
+        assert_eq!(parse_str("fn a::b() -> f32 { return 1.0; }"), Err(CompileError::new(CompileErrorKind::SyntaxError("expected `(` after the function name, found `::`".to_string()), Span::new(4, 2, 1, 5))));
+        assert_eq!(parse_str("fn lighting.shade() -> f32 { return 1.0; }"), Err(CompileError::new(CompileErrorKind::SyntaxError("expected `(` after the function name, found `.`".to_string()), Span::new(11, 1, 1, 12))));
+    }
+
+    #[test]
+    fn test_parse_index_accessor() {
+        let ast = parse_str("fn test() -> vec4 { return scene.lights[i + 1].color; }").unwrap();
+
+        match ast[0] {
+            ItemKind::Function(ref function) => match function.block.statements[0] {
+                BlockStatement::Return(ReturnDeclaration { expression: ExpressionStatement::IndexAccessor(ref accessor), .. }) => {
+                    assert_eq!(accessor.variable_name.name, "scene");
+                    assert_eq!(accessor.field_names.iter().map(|f| &*f.name).collect::<Vec<&str>>(), vec!["lights"]);
+                    assert_eq!(accessor.element_field_names.iter().map(|f| &*f.name).collect::<Vec<&str>>(), vec!["color"]);
+                    match *accessor.access_expression {
+                        ExpressionStatement::Infix(ref infix) => assert_eq!(infix.operator, Operator::Plus),
+                        ref index => panic!("expected infix index, found {:?}", index),
+                    }
+                },
+                _ => panic!("expected index accessor"),
+            },
+            _ => panic!("expected function"),
+        }
+    }
+
+    #[test]
+    fn test_parse_array_types_and_nested_fields() {
+        let code = "struct Lights { points: [[Light; 4]; 2], spots: [Light; MAX_LIGHTS * 2], } fn test() -> vec3 { return scene.lights.points; }";
+
+        let ast = parse_str(code).unwrap();
+        match ast[0] {
+            ItemKind::Struct(ref lights) => {
+                let points = &lights.struct_member[0].struct_member_type_name;
+                assert_eq!((&points.name[..], points.span), ("[[Light; 4]; 2]", Span::new(24, 15, 1, 25)));
+                assert_eq!(points.array.as_ref().unwrap().element_type_name.name, "[Light; 4]");
+
+                let spots = &lights.struct_member[1].struct_member_type_name;
+                assert_eq!(spots.name, "[Light; MAX_LIGHTS * 2]");
+                match spots.array.as_ref().unwrap().length {
+                    ExpressionStatement::Infix(ref infix) => assert_eq!(infix.operator, Operator::Multiply),
+                    ref length => panic!("expected infix length, found {:?}", length),
+                }
+            },
+            _ => panic!("expected struct"),
+        }
+        match ast[1] {
+            ItemKind::Function(ref function) => match function.block.statements[0] {
+                BlockStatement::Return(ReturnDeclaration { expression: ExpressionStatement::FieldAccessor(ref accessor), .. }) => {
+                    let field_names: Vec<&str> = accessor.field_names.iter().map(|f| &*f.name).collect();
+                    assert_eq!(accessor.variable_name.name, "scene");
+                    assert_eq!(field_names, vec!["lights", "points"]);
+                },
+                _ => panic!("expected field accessor"),
+            },
+            _ => panic!("expected function"),
+        }
+    }
+
+    #[test]
+    fn test_parse_core_library() {
+        assert!(parse_str(include_str!("../libcore/primitives.xs")).is_ok());
+        assert!(parse_str(include_str!("../libcore/builtins.xs")).is_ok());
+    }
+
+    #[test]
+    fn test_parse_flat_example() {
+        assert!(parse_str(include_str!("../examples/flat/flat.xs")).is_ok());
+    }
+
     #[test]
     fn test_parse_incomplete_function_gives_correct_error() {
         let code = "
@@ -983,6 +2024,49 @@ struct Test {
 fn main {
 ";
 
-        assert_eq!(parse_str(code), Err(CompileError::new(CompileErrorKind::ParseError, Span::new(30, 10, 6, 1))));
+        assert_eq!(parse_str(code), Err(CompileError::new(CompileErrorKind::SyntaxError("expected `(` after the function name, found `{`".to_string()), Span::new(38, 1, 6, 9))));
+    }
+
+    #[test]
+    fn test_parse_module_recovers_after_broken_items() {
+        let code = "
+struct A {
+    x = f32,
+}
+
+const B: f32 = 1.0;
+fn c( -> f32 { return 1.0; }
+struct D { y: f32, }
+";
+
+        let (items, errors) = parse_module(code);
+        let names: Vec<&str> = items.iter().map(|item| match *item {
+            ItemKind::Constant(ref constant) => &constant.constant_name.name[..],
+            ItemKind::Struct(ref struct_definition) => &struct_definition.struct_name.name[..],
+            _ => panic!("unexpected item {:?}", item),
+        }).collect();
+
+        assert_eq!(names, vec!["B", "D"]);
+        assert_eq!(errors, vec![
+            CompileError::new(CompileErrorKind::SyntaxError("expected `:` after the member name, found `=`".to_string()), Span::new(18, 1, 3, 7)),
+            CompileError::new(CompileErrorKind::SyntaxError("expected an argument or `)`, found `->`".to_string()), Span::new(54, 2, 7, 7)),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_module_keeps_functions_with_broken_statements() {
+        let code = "fn f() -> f32 {\n    let a = 1.0 * ;\n    return a;\n}\n";
+
+        let (items, errors) = parse_module(code);
+
+        assert_eq!(errors, vec![CompileError::new(CompileErrorKind::SyntaxError("expected `;` after the statement, found `*`".to_string()), Span::new(32, 1, 2, 17))]);
+        assert_eq!(items.len(), 1);
+        match items[0] {
+            ItemKind::Function(ref function) => {
+                assert_eq!(function.span, Span::new(0, 51, 1, 1));
+                assert_eq!(function.block.statements.len(), 1);
+            },
+            _ => panic!("unexpected items {:?}", items),
+        }
     }
 }
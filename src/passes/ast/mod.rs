@@ -17,10 +17,17 @@ pub trait AstWalker {
     fn visit(&mut self, items: &mut Ast) {
         for item in items.iter_mut() {
             match *item {
+                ItemKind::Import(ref mut item) => self.visit_import(item),
                 ItemKind::Export(ref mut item) => self.visit_export(item),
                 ItemKind::Struct(ref mut item) => self.visit_struct(item),
                 ItemKind::Function(ref mut item) => self.visit_function(item),
+                ItemKind::Program(ref mut item) => self.visit_program(item),
                 ItemKind::Primitive(ref mut item) => self.visit_primitive(item),
+                ItemKind::Builtin(ref mut item) => self.visit_builtin(item),
+                ItemKind::Operator(ref mut item) => self.visit_operator(item),
+                ItemKind::Constant(ref mut item) => self.visit_constant(item),
+                ItemKind::Uniform(ref mut item) => self.visit_uniform(item),
+                ItemKind::UniformBlock(ref mut item) => self.visit_uniform_block(item),
                 _ => (),
             };
         }
@@ -29,6 +36,22 @@ pub trait AstWalker {
     fn visit_primitive(&mut self, primitive_declaration: &mut PrimitiveDeclaration) {
     }
 
+    fn visit_builtin(&mut self, builtin_declaration: &mut BuiltinDeclaration) {
+    }
+
+    fn visit_operator(&mut self, operator_declaration: &mut OperatorDeclaration) {
+    }
+
+    fn visit_constant(&mut self, constant_definition: &mut ConstantDefinition) {
+        self.walk_constant(constant_definition);
+    }
+
+    fn walk_constant(&mut self, constant_definition: &mut ConstantDefinition) {
+        if let Some(ref mut initializer) = constant_definition.constant_initializer {
+            self.visit_expression(initializer);
+        }
+    }
+
     fn visit_block(&mut self, block: &mut BlockDeclaration) {
         self.walk_block(block);
     }
@@ -43,6 +66,12 @@ pub trait AstWalker {
         }
     }
 
+    fn visit_uniform(&mut self, uniform_definition: &mut UniformDefinition) {
+    }
+
+    fn visit_uniform_block(&mut self, uniform_block_definition: &mut UniformBlockDefinition) {
+    }
+
     fn visit_local_statement(&mut self, local_statement: &mut LocalDeclaration) {
         self.walk_local_statement(local_statement);
     }
@@ -117,6 +146,10 @@ pub trait AstWalker {
         for e in struct_instantiation_expression.struct_field_initializer.iter_mut() {
             self.visit_struct_field_initializer(e);
         }
+
+        if let Some(ref mut base) = struct_instantiation_expression.base {
+            self.visit_expression(base);
+        }
     }
 
     fn visit_struct_field_initializer(&mut self, struct_field_initializer: &mut StructFieldInitializerExpression) {
@@ -156,6 +189,26 @@ pub trait AstWalker {
         self.walk_function(function_definition);
     }
 
+    fn visit_program(&mut self, program_definition: &mut ProgramDefinition) {
+        self.walk_program(program_definition);
+    }
+
+    fn walk_program(&mut self, program_definition: &mut ProgramDefinition) {
+        for stage in program_definition.program_stages.iter_mut() {
+            self.visit_program_stage(stage);
+        }
+    }
+
+    fn visit_program_stage(&mut self, program_stage_definition: &mut ProgramStageDefinition) {
+        self.walk_program_stage(program_stage_definition);
+    }
+
+    fn walk_program_stage(&mut self, program_stage_definition: &mut ProgramStageDefinition) {
+        if let ProgramStageFunction::Inline(ref mut function) = program_stage_definition.function {
+            self.walk_function(function);
+        }
+    }
+
     fn walk_struct(&mut self, struct_definition: &mut StructDefinition) {
         for member in struct_definition.struct_member.iter_mut() {
             self.visit_struct_member(member);
@@ -169,6 +222,9 @@ pub trait AstWalker {
     fn visit_struct_member(&mut self, struct_member_definition: &mut StructMemberDefinition) {
     }
 
+    fn visit_import(&mut self, import_definition: &mut ImportDefinition) {
+    }
+
     fn visit_export(&mut self, export_definition: &mut ExportDefinition) {
     }
 }
@@ -0,0 +1,175 @@
+use ::std::collections::HashSet;
+use ::ast::*;
+use ::passes::*;
+use ::passes::ast::*;
+use ::passes::results::PassResultReference;
+use ::type_system::symbol_table::SymbolTableReference;
+use ::type_system::error::{ TypeError, ErrorKind };
+
+// binds the names listed by `import` items into the module scope, renamed by `as` if given
+// `import * as name` binds a namespace instead whose items are accessed as `name::item`
+// imported modules are checked first, their exports are looked up by canonical module path
+// errors are reported at the import with the module id as written
+// items missing from modules in `failed_modules` are bound as unknown, the failure is reported at that module
+pub struct BindImportsPass {
+    symbol_table: SymbolTableReference,
+    result: PassResultReference,
+    failed_modules: HashSet<String>,
+}
+
+impl BindImportsPass {
+    pub fn new(symbol_table: SymbolTableReference, result: PassResultReference) -> BindImportsPass {
+        BindImportsPass::with_failed_modules(symbol_table, result, HashSet::new())
+    }
+
+    pub fn with_failed_modules(symbol_table: SymbolTableReference, result: PassResultReference, failed_modules: HashSet<String>) -> BindImportsPass {
+        BindImportsPass {
+            symbol_table: symbol_table,
+            result: result,
+            failed_modules: failed_modules,
+        }
+    }
+}
+
+ast_pass_impl!(BindImportsPass, {
+    fn visit_import(&mut self, import_definition: &mut ImportDefinition) {
+        let module_id = &import_definition.module_id;
+        let span = import_definition.span;
+        let to_import_error = |error: TypeError| to_module_error(error, span, module_id);
+
+        let module_path = match import_definition.module_path {
+            Some(ref module_path) if symbol_table!(self).has_module(module_path) => module_path.to_owned(),
+            // the module could not be resolved, which is reported when loading the module
+            None => return,
+            _ => pass_try!(self, Err(TypeError::new(import_definition.span, ErrorKind::ModuleNotLoaded(module_id.to_owned())))),
+        };
+
+        for item in &import_definition.items {
+            let names = match *item {
+                ImportItem::Named(ref identifier) => vec![(identifier.name.to_owned(), identifier.name.to_owned())],
+                ImportItem::Aliased(ref identifier, ref alias) => vec![(identifier.name.to_owned(), alias.name.to_owned())],
+                ImportItem::All => symbol_table!(self).find_module_exports(&module_path).unwrap_or_default().into_iter().map(|name| (name.clone(), name)).collect(),
+                ImportItem::Namespace(ref namespace) => {
+                    pass_try!(self, symbol_table_mut!(self).import_namespace(&module_path, &namespace.name).map_err(&to_import_error));
+                    continue;
+                },
+            };
+
+            for (name, alias) in names {
+                let imported = symbol_table_mut!(self).import_as(&module_path, &name, &alias);
+                let imported = match imported {
+                    Err(ref error) if self.failed_modules.contains(&module_path) && is_missing_item(error) => symbol_table_mut!(self).import_unknown(&alias),
+                    imported => imported,
+                };
+                pass_try!(self, imported.map_err(&to_import_error));
+            }
+        }
+    }
+});
+
+/// whether `error` is about an item the module does not declare or export
+pub fn is_missing_item(error: &TypeError) -> bool {
+    match *error.get_kind() {
+        ErrorKind::ImportNotFound(..) | ErrorKind::ImportNotExported(..) => true,
+        _ => false,
+    }
+}
+
+/// `error` of the symbol table reported at `span` of an import or export, naming the module `module_id` as written
+pub fn to_module_error(error: TypeError, span: Span, module_id: &str) -> TypeError {
+    let kind = match *error.get_kind() {
+        ErrorKind::ModuleNotLoaded(_) => ErrorKind::ModuleNotLoaded(module_id.to_owned()),
+        ErrorKind::ImportNotFound(ref name, _) => ErrorKind::ImportNotFound(name.to_owned(), module_id.to_owned()),
+        ErrorKind::ImportNotExported(ref name, _) => ErrorKind::ImportNotExported(name.to_owned(), module_id.to_owned()),
+        ref kind => kind.clone(),
+    };
+    TypeError::new(span, kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::testing::compile_ast;
+    use ::passes::results::PassResult;
+    use ::type_system::symbol_table::SymbolTable;
+    use ::type_system::type_environment::TypeEnvironment;
+
+    fn bind(code: &str) -> (SymbolTableReference, PassResultReference) {
+        bind_with_failed(code, HashSet::new())
+    }
+
+    fn bind_with_failed(code: &str, failed_modules: HashSet<String>) -> (SymbolTableReference, PassResultReference) {
+        let mut symbol_table = SymbolTable::new(TypeEnvironment::new());
+        symbol_table.enter_module_scope();
+        symbol_table.create_type("Light").unwrap();
+        symbol_table.create_type("Shadow").unwrap();
+        symbol_table.export("Light").unwrap();
+        symbol_table.leave_module_scope("lighting.xs");
+        symbol_table.enter_module_scope();
+
+        let mut ast = compile_ast(code);
+        for item in ast.iter_mut() {
+            if let ItemKind::Import(ref mut import) = *item {
+                import.module_path = Some(import.module_id.to_owned());
+            }
+        }
+
+        let symbol_table = SymbolTableReference::new(symbol_table);
+        let result = PassResultReference::new(PassResult::new());
+        let mut pass = BindImportsPass::with_failed_modules(symbol_table.clone(), result.clone(), failed_modules);
+        pass.execute(&mut ast);
+
+        (symbol_table, result)
+    }
+
+    #[test]
+    fn bind_named_and_all_imports() {
+        let (symbol_table, result) = bind("import Light from 'lighting.xs'; import * from 'lighting.xs';");
+
+        assert!(!result.borrow().has_errors());
+        assert!(symbol_table.borrow().find_type_ref("Light").is_some());
+        assert!(symbol_table.borrow().find_type_ref("Shadow").is_none());
+    }
+
+    #[test]
+    fn bind_aliased_and_namespaced_imports() {
+        let (symbol_table, result) = bind("import { Light as PointLight } from 'lighting.xs'; import * as lighting from 'lighting.xs';");
+        let symbol_table = symbol_table.borrow();
+
+        assert!(!result.borrow().has_errors());
+        assert!(symbol_table.find_type_ref("Light").is_none());
+        assert_eq!(symbol_table.find_type_ref("PointLight"), symbol_table.find_type_ref("lighting::Light"));
+        assert!(symbol_table.find_type_ref("lighting::Shadow").is_none());
+    }
+
+    #[test]
+    fn import_not_exported_item() {
+        let (_, result) = bind("import Shadow from 'lighting.xs';");
+        let errors = result.borrow_mut().take_errors();
+        let error = errors[0].downcast_ref::<TypeError>().unwrap();
+
+        assert_eq!(error.get_kind(), &ErrorKind::ImportNotExported("Shadow".to_owned(), "lighting.xs".to_owned()));
+        assert_eq!(error.get_span(), Span::new(0, 33, 1, 1));
+    }
+
+    #[test]
+    fn import_missing_items_of_failed_module() {
+        let mut failed_modules = HashSet::new();
+        failed_modules.insert("lighting.xs".to_owned());
+        let (symbol_table, result) = bind_with_failed("import { Light, Shadow, Sun as Sky } from 'lighting.xs';", failed_modules);
+        let mut symbol_table = symbol_table.borrow_mut();
+
+        assert!(!result.borrow().has_errors());
+        assert!(symbol_table.find_type_ref("Shadow").is_some());
+        assert!(symbol_table.find_type_ref("Sky").is_some());
+        assert_eq!(symbol_table.find_symbol("Sky").and_then(|s| s.get_type()), None);
+    }
+
+    #[test]
+    fn import_from_unknown_module() {
+        let (_, result) = bind("import Light from 'unknown.xs';");
+        let errors = result.borrow_mut().take_errors();
+
+        assert_eq!(errors[0].downcast_ref::<TypeError>().unwrap().get_kind(), &ErrorKind::ModuleNotLoaded("unknown.xs".to_owned()));
+    }
+}
@@ -0,0 +1,193 @@
+use ::std::collections::HashSet;
+use ::ast::*;
+use ::passes::*;
+use ::passes::ast::*;
+use ::passes::results::PassResultReference;
+use ::type_system::error::{ TypeError, ErrorKind };
+
+/// declarations an attribute can be attached to
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+enum AttributeTarget {
+    Struct,
+    StructMember,
+    Function,
+    Constant,
+    Sampler,
+    ProgramStage,
+}
+
+impl AttributeTarget {
+    fn get_name(&self) -> &'static str {
+        match *self {
+            AttributeTarget::Struct => "struct",
+            AttributeTarget::StructMember => "struct member",
+            AttributeTarget::Function => "function",
+            AttributeTarget::Constant => "constant",
+            AttributeTarget::Sampler => "sampler",
+            AttributeTarget::ProgramStage => "program stage",
+        }
+    }
+}
+
+/// arguments an attribute expects
+enum AttributeArguments {
+    /// a single name, restricted to the given names unless empty
+    Name(&'static [&'static str]),
+    /// between min and max expressions, checked by later passes
+    Expressions(usize, usize),
+}
+
+struct AttributeRule {
+    name: &'static str,
+    targets: &'static [AttributeTarget],
+    arguments: AttributeArguments,
+}
+
+const ATTRIBUTES: &[AttributeRule] = &[
+    AttributeRule { name: "builtin", targets: &[AttributeTarget::StructMember], arguments: AttributeArguments::Name(&[]) },
+    AttributeRule { name: "workgroup_size", targets: &[AttributeTarget::ProgramStage], arguments: AttributeArguments::Expressions(1, 3) },
+];
+
+/// rejects unknown, misplaced and duplicate attributes and checks the shape of their arguments
+/// the meaning of the arguments is checked by the passes using the attribute, so no symbols are needed
+pub struct CheckAttributesPass {
+    result: PassResultReference,
+}
+
+impl CheckAttributesPass {
+    pub fn new(result: PassResultReference) -> CheckAttributesPass {
+        CheckAttributesPass {
+            result: result,
+        }
+    }
+
+    fn add_error(&mut self, error: TypeError) {
+        self.result.borrow_mut().add_error(Box::new(error));
+    }
+
+    fn check_attributes(&mut self, attributes: &[Attribute], target: AttributeTarget) {
+        let mut seen = HashSet::new();
+
+        for attribute in attributes {
+            let attribute_name = &attribute.attribute_name.name;
+
+            let rule = match ATTRIBUTES.iter().find(|r| r.name == attribute_name) {
+                Some(rule) => rule,
+                None => {
+                    self.add_error(TypeError::new(attribute.attribute_name.span, ErrorKind::UnknownAttribute(attribute_name.to_owned())));
+                    continue;
+                },
+            };
+
+            if !rule.targets.contains(&target) {
+                self.add_error(TypeError::new(attribute.span, ErrorKind::MisplacedAttribute(attribute_name.to_owned(), target.get_name().to_owned())));
+                continue;
+            }
+
+            if !seen.insert(attribute_name.to_owned()) {
+                self.add_error(TypeError::new(attribute.span, ErrorKind::DuplicateAttribute(attribute_name.to_owned())));
+                continue;
+            }
+
+            if !CheckAttributesPass::has_valid_arguments(attribute, &rule.arguments) {
+                self.add_error(TypeError::new(attribute.span, ErrorKind::InvalidAttributeArguments(attribute_name.to_owned())));
+            }
+        }
+    }
+
+    fn has_valid_arguments(attribute: &Attribute, arguments: &AttributeArguments) -> bool {
+        match *arguments {
+            AttributeArguments::Name(names) => {
+                attribute.arguments.len() == 1 && match attribute.get_name_argument(0) {
+                    Some(name) => names.is_empty() || names.contains(&&*name.name),
+                    None => false,
+                }
+            },
+            AttributeArguments::Expressions(min, max) => attribute.arguments.len() >= min && attribute.arguments.len() <= max,
+        }
+    }
+}
+
+ast_pass_impl!(CheckAttributesPass, {
+    fn visit_struct(&mut self, struct_definition: &mut StructDefinition) {
+        self.check_attributes(&struct_definition.attributes, AttributeTarget::Struct);
+        self.walk_struct(struct_definition);
+    }
+
+    fn visit_struct_member(&mut self, struct_member_definition: &mut StructMemberDefinition) {
+        self.check_attributes(&struct_member_definition.attributes, AttributeTarget::StructMember);
+    }
+
+    fn visit_uniform_block(&mut self, uniform_block_definition: &mut UniformBlockDefinition) {
+        for member in uniform_block_definition.block_member.iter_mut() {
+            self.visit_struct_member(member);
+        }
+    }
+
+    fn visit_function(&mut self, function_declaration: &mut FunctionDeclaration) {
+        self.check_attributes(&function_declaration.attributes, AttributeTarget::Function);
+    }
+
+    fn visit_constant(&mut self, constant_definition: &mut ConstantDefinition) {
+        let target = match constant_definition.constant_variant {
+            ConstantVariant::Constant => AttributeTarget::Constant,
+            ConstantVariant::Sampler => AttributeTarget::Sampler,
+        };
+
+        self.check_attributes(&constant_definition.attributes, target);
+    }
+
+    fn visit_program_stage(&mut self, program_stage_definition: &mut ProgramStageDefinition) {
+        self.check_attributes(&program_stage_definition.attributes, AttributeTarget::ProgramStage);
+    }
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::testing::compile_ast;
+    use ::passes::results::PassResult;
+
+    fn check(code: &str) -> PassResultReference {
+        let mut ast = compile_ast(code);
+        let result = PassResultReference::new(PassResult::new());
+        let mut pass = CheckAttributesPass::new(result.clone());
+
+        pass.execute(&mut ast);
+
+        result
+    }
+
+    #[test]
+    fn valid_attributes() {
+        let result = check("struct Output { @builtin(position) position: vec4, color: vec4, }
+            kernel Blur { @workgroup_size(8, 8, 1) compute: blur, }");
+
+        assert!(!result.borrow().has_errors());
+    }
+
+    #[test]
+    fn unknown_attributes_produce_an_error() {
+        assert!(check("@unroll fn helper() -> f32 { return 1.0; }").borrow().has_errors());
+        assert!(check("struct Output { @location(0) color: vec4, }").borrow().has_errors());
+    }
+
+    #[test]
+    fn misplaced_attributes_produce_an_error() {
+        assert!(check("@builtin(position) fn helper() -> f32 { return 1.0; }").borrow().has_errors());
+        assert!(check("@workgroup_size(8) const SCALE: f32 = 1.0;").borrow().has_errors());
+        assert!(check("program Test { @builtin(position) vertex: vertex_main, }").borrow().has_errors());
+    }
+
+    #[test]
+    fn duplicate_attributes_produce_an_error() {
+        assert!(check("struct Output { @builtin(position) @builtin(position) position: vec4, }").borrow().has_errors());
+    }
+
+    #[test]
+    fn invalid_arguments_produce_an_error() {
+        assert!(check("struct Output { @builtin(0) position: vec4, }").borrow().has_errors());
+        assert!(check("struct Output { @builtin(position, point_size) position: vec4, }").borrow().has_errors());
+        assert!(check("kernel Blur { @workgroup_size(1, 2, 3, 4) compute: blur, }").borrow().has_errors());
+    }
+}
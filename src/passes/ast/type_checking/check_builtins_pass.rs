@@ -0,0 +1,105 @@
+use ::ast::*;
+use ::passes::*;
+use ::passes::ast::*;
+use ::passes::results::PassResultReference;
+use ::type_system::symbol_table::SymbolTableReference;
+use ::type_system::builtins::StageBuiltin;
+use ::type_system::error::{ TypeError, ErrorKind };
+
+// registers the `builtin` declarations of libcore with the symbol table
+// later passes check `@builtin(...)` members and declare builtin inputs inside stages
+// builtins and builtin functions can only be declared by the core module, not inside a module scope
+ast_pass!(CheckBuiltinsPass, {
+    fn visit_function(&mut self, function_declaration: &mut FunctionDeclaration) {
+        if function_declaration.builtin && symbol_table!(self).is_in_module_scope() {
+            pass_try!(self, Err(TypeError::new(function_declaration.span, ErrorKind::SyntaxOnlyValidInCoreModule)));
+        }
+    }
+
+    fn visit_builtin(&mut self, builtin_declaration: &mut BuiltinDeclaration) {
+        if symbol_table!(self).is_in_module_scope() {
+            pass_try!(self, Err(TypeError::new(builtin_declaration.span, ErrorKind::SyntaxOnlyValidInCoreModule)));
+        }
+
+        let builtin_type = match symbol_table!(self).find_type_ref(&builtin_declaration.builtin_type_name.name) {
+            Some(t) => t,
+            None => pass_try!(self, Err(TypeError::new(builtin_declaration.builtin_type_name.span, ErrorKind::TypeNotFound(builtin_declaration.builtin_type_name.name.to_owned())))),
+        };
+
+        let builtin = StageBuiltin::new(
+            &builtin_declaration.builtin_name.name,
+            builtin_type,
+            builtin_declaration.input_stages.clone(),
+            builtin_declaration.output_stages.clone());
+
+        pass_try!(self, symbol_table_mut!(self).add_builtin(builtin).map_err(|_| TypeError::new(builtin_declaration.builtin_name.span, ErrorKind::SymbolNameAlreadyUsed(builtin_declaration.builtin_name.name.to_owned()))));
+        builtin_declaration.builtin_type = Some(builtin_type);
+    }
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::testing::compile_ast;
+    use ::passes::results::PassResult;
+    use ::type_system::symbol_table::SymbolTable;
+    use ::type_system::type_environment::TypeEnvironment;
+    use ::passes::ast::type_checking::check_primitives_pass;
+
+    fn check(code: &str) -> (SymbolTableReference, PassResultReference) {
+        check_in(code, SymbolTable::new(TypeEnvironment::new()))
+    }
+
+    fn check_in(code: &str, symbol_table: SymbolTable) -> (SymbolTableReference, PassResultReference) {
+        let mut ast = compile_ast(code);
+        let symbol_table = SymbolTableReference::new(symbol_table);
+        let result = PassResultReference::new(PassResult::new());
+
+        let mut passes = PassCollection::from_passes(vec![
+            Box::new(check_primitives_pass::CheckPrimitivesPass::new(symbol_table.clone(), result.clone())),
+            Box::new(CheckBuiltinsPass::new(symbol_table.clone(), result.clone())),
+        ]);
+
+        passes.execute(&mut ast);
+
+        (symbol_table, result)
+    }
+
+    #[test]
+    fn builtins_are_registered() {
+        let (symbol_table, result) = check("primitive type vec4; builtin position: vec4 in(geometry) out(vertex, geometry);");
+
+        assert!(!result.borrow().has_errors());
+
+        let symbol_table = symbol_table.borrow();
+        let position = symbol_table.find_builtin("position").unwrap();
+        assert!(position.is_input_of(StageKind::Geometry));
+        assert!(position.is_output_of(StageKind::Vertex));
+        assert!(!position.is_input_of(StageKind::Fragment));
+    }
+
+    #[test]
+    fn invalid_builtins_produce_an_error() {
+        assert!(check("builtin position: vec4 out(vertex);").1.borrow().has_errors());
+        assert!(check("primitive type u32; builtin vertex_index: u32 in(vertex); builtin vertex_index: u32 in(vertex);").1.borrow().has_errors());
+    }
+
+    #[test]
+    fn builtins_outside_the_core_module_produce_an_error() {
+        let mut symbol_table = SymbolTable::new(TypeEnvironment::new());
+        symbol_table.enter_module_scope();
+        let (symbol_table, result) = check_in("primitive type u32; builtin vertex_index: u32 in(vertex);", symbol_table);
+        let errors = result.borrow_mut().take_errors();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].downcast_ref::<TypeError>().unwrap().get_kind(), &ErrorKind::SyntaxOnlyValidInCoreModule);
+        assert!(symbol_table.borrow().find_builtin("vertex_index").is_none());
+
+        let mut symbol_table = SymbolTable::new(TypeEnvironment::new());
+        symbol_table.enter_module_scope();
+        let (_, result) = check_in("primitive type f32; builtin fn max(a: f32, b: f32) -> f32;", symbol_table);
+        let errors = result.borrow_mut().take_errors();
+
+        assert_eq!(errors[0].downcast_ref::<TypeError>().unwrap().get_kind(), &ErrorKind::SyntaxOnlyValidInCoreModule);
+    }
+}
@@ -1,42 +1,114 @@
+use ::std::collections::HashSet;
 use ::ast::*;
 use ::passes::*;
 use ::passes::ast::*;
 use ::passes::results::PassResultReference;
-use ::type_system::symbol_table::{ SymbolTableReference };
+use ::type_system::symbol_table::{ SymbolTableReference, SymbolKind };
 use ::type_system::type_environment::TypeReference;
 use ::type_system::error::{ TypeError, ErrorKind, TypeCheckResult };
+use super::bind_imports_pass::{ to_module_error, is_missing_item };
+
+// records the exported structures, functions and constants as the interface of the module
+// `export *` exports everything declared in the module itself
+// `export { X } from 'y'` and `export * from 'y'` re-export items of another module without binding them
+// items missing from modules in `failed_modules` are re-exported as unknown, the failure is reported at that module
+pub struct CheckExportsPass {
+    symbol_table: SymbolTableReference,
+    result: PassResultReference,
+    failed_modules: HashSet<String>,
+}
+
+impl CheckExportsPass {
+    pub fn new(symbol_table: SymbolTableReference, result: PassResultReference) -> CheckExportsPass {
+        CheckExportsPass::with_failed_modules(symbol_table, result, HashSet::new())
+    }
 
-ast_pass!(CheckExportsPass, {
+    pub fn with_failed_modules(symbol_table: SymbolTableReference, result: PassResultReference, failed_modules: HashSet<String>) -> CheckExportsPass {
+        CheckExportsPass {
+            symbol_table: symbol_table,
+            result: result,
+            failed_modules: failed_modules,
+        }
+    }
+}
+
+ast_pass_impl!(CheckExportsPass, {
     fn visit_export(&mut self, export_definition: &mut ExportDefinition) {
         pass_warning!(self, "'export' is experimental syntax and might get changed or removed in the future.");
 
+        if let Some(ref module_id) = export_definition.module_id {
+            self.reexport(export_definition, module_id);
+            return;
+        }
+
         let mut symbol_table_ref = symbol_table_mut!(self); 
 
         for i in &export_definition.items {
-            let type_name = match i {
-                &ImportItem::Named(ref identifier) => &*identifier.name,
-                _ => continue,
+            let identifier = match i {
+                &ImportItem::Named(ref identifier) => identifier,
+                &ImportItem::All => {
+                    pass_try!(self, symbol_table_ref.export_all().map_err(|e| TypeError::new(export_definition.span, e.get_kind().clone())));
+                    continue;
+                },
+                &ImportItem::Aliased(ref identifier, _) | &ImportItem::Namespace(ref identifier) => {
+                    pass_try!(self, Err(TypeError::new(identifier.span, ErrorKind::InvalidExport(identifier.name.to_owned()))))
+                },
             };
+            let type_name = &*identifier.name;
 
-            let type_ref = match symbol_table_ref.find_type_ref(type_name) {
-                Some(t) => t,
-                None => pass_try!(self, Err(TypeError::new(Span::empty(), ErrorKind::TypeNotFound(type_name.to_owned())))),
-            };
-            match symbol_table_ref.find_type_mut(type_ref) {
-                Some(t) => {
-                    if !(t.is_struct() || t.is_callable()) { 
-                        pass_try!(self, Err(TypeError::new(Span::empty(), ErrorKind::InvalidExport(type_name.to_owned()))))
-                    } else {
-                        continue;
-                    }
+            let is_exportable = match symbol_table_ref.find_type_ref(type_name) {
+                Some(type_ref) => match symbol_table_ref.find_type(type_ref) {
+                    Some(t) => t.is_struct() || t.is_callable(),
+                    None => pass_try!(self, Err(TypeError::new(identifier.span, ErrorKind::TypeNotFound(type_name.to_owned())))),
+                },
+                None => match symbol_table_ref.find_symbol(type_name) {
+                    Some(symbol) => symbol.kind == SymbolKind::Constant,
+                    None => pass_try!(self, Err(TypeError::new(identifier.span, ErrorKind::TypeNotFound(type_name.to_owned())))),
                 },
-                None => pass_try!(self, Err(TypeError::new(Span::empty(), ErrorKind::TypeNotFound(type_name.to_owned())))),
             };
-            
+            if !is_exportable {
+                pass_try!(self, Err(TypeError::new(identifier.span, ErrorKind::InvalidExport(type_name.to_owned()))));
+            }
+
+            pass_try!(self, symbol_table_ref.export(type_name).map_err(|e| match *e.get_kind() {
+                // global items like primitives are not part of the module
+                ErrorKind::TypeNotFound(_) => TypeError::new(identifier.span, ErrorKind::InvalidExport(type_name.to_owned())),
+                ref kind => TypeError::new(identifier.span, kind.clone()),
+            }));
         }
     }
 });
 
+impl CheckExportsPass {
+    fn reexport(&mut self, export_definition: &ExportDefinition, module_id: &str) {
+        let span = export_definition.span;
+        let module_path = match export_definition.module_path {
+            Some(ref module_path) if symbol_table!(self).has_module(module_path) => module_path.to_owned(),
+            // the module could not be resolved, which is reported when loading the module
+            None => return,
+            _ => pass_try!(self, Err(TypeError::new(span, ErrorKind::ModuleNotLoaded(module_id.to_owned())))),
+        };
+
+        for item in &export_definition.items {
+            let reexported = match *item {
+                ImportItem::Named(ref identifier) => self.reexport_item(&module_path, &identifier.name, &identifier.name),
+                ImportItem::Aliased(ref identifier, ref alias) => self.reexport_item(&module_path, &identifier.name, &alias.name),
+                ImportItem::All => symbol_table_mut!(self).reexport_all(&module_path),
+                ImportItem::Namespace(ref identifier) => Err(TypeError::new(identifier.span, ErrorKind::InvalidExport(identifier.name.to_owned()))),
+            };
+            pass_try!(self, reexported.map_err(|e| to_module_error(e, span, module_id)));
+        }
+    }
+
+    fn reexport_item(&mut self, module_path: &str, name: &str, alias: &str) -> TypeCheckResult<()> {
+        let reexported = symbol_table_mut!(self).reexport(module_path, name, alias);
+        match reexported {
+            Err(ref error) if self.failed_modules.contains(module_path) && is_missing_item(error) => symbol_table_mut!(self).reexport_unknown(alias),
+            reexported => reexported,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2,7 +2,7 @@ use ::ast::*;
 use ::passes::*;
 use ::passes::ast::*;
 use ::passes::results::PassResultReference;
-use ::type_system::symbol_table::{ SymbolTableReference };
+use ::type_system::symbol_table::{ SymbolTableReference, SymbolKind };
 use ::type_system::type_environment::TypeReference;
 use ::type_system::error::{ TypeError, ErrorKind, TypeCheckResult };
 use ::type_system::call_signature::CallSignature;
@@ -28,11 +28,11 @@ ast_pass_impl!(CheckFunctionSignaturePass, {
         // let mut symbol_table = symbol_table_mut!(self);
 
         let function_type = pass_try!(self, symbol_table_mut!(self).create_type(&function_declaration.function_name.name));
-        pass_try!(self, symbol_table_mut!(self).add_symbol_with_type(&function_declaration.function_name.name, function_type));
+        pass_try!(self, symbol_table_mut!(self).add_symbol_with_type(&function_declaration.function_name.name, function_type, SymbolKind::Function));
 
         symbol_table_mut!(self).enter_scope();
 
-        let type_ref = pass_try!(self, symbol_table!(self).find_type_ref_or_err(&function_declaration.return_type_name.name));
+        let type_ref = pass_try!(self, symbol_table_mut!(self).resolve_type_name_or_err(&function_declaration.return_type_name.name));
         function_declaration.return_type    = Some(type_ref.clone());
         function_declaration.declaring_type = Some(function_type);
 
@@ -49,18 +49,60 @@ ast_pass_impl!(CheckFunctionSignaturePass, {
         symbol_table_mut!(self).leave_scope();
     }
 
-    fn visit_function_argument(&mut self, function_argument_declaration: &mut FunctionArgumentDeclaration) {
-        let mut list = self.argument_type_list.take().unwrap();
+    fn visit_program_stage(&mut self, program_stage_definition: &mut ProgramStageDefinition) {
+        // stages are not callable, only their argument and return types are resolved
+        // referenced functions are resolved like any other function
+        let function_declaration = match program_stage_definition.function {
+            ProgramStageFunction::Inline(ref mut function) => function,
+            ProgramStageFunction::Reference(_) => return,
+        };
+        let type_ref = pass_try!(self, symbol_table_mut!(self).resolve_type_name_or_err(&function_declaration.return_type_name.name));
+        function_declaration.return_type = Some(type_ref);
+
+        symbol_table_mut!(self).enter_scope();
+
+        self.argument_type_list = Some(Vec::new());
+        for argument in function_declaration.arguments.iter_mut() {
+            self.visit_function_argument(argument);
+        }
+        self.argument_type_list = None;
+
+        symbol_table_mut!(self).leave_scope();
+    }
+
+    fn visit_operator(&mut self, operator_declaration: &mut OperatorDeclaration) {
+        // operators are registered on the type of their left hand side
+        if operator_declaration.arguments.len() != 2 {
+            pass_try!(self, Err(TypeError::new(operator_declaration.span, ErrorKind::IncompatibleArguments)));
+        }
+
+        symbol_table_mut!(self).enter_scope();
+
+        self.argument_type_list = Some(Vec::new());
+        for argument in operator_declaration.arguments.iter_mut() {
+            self.visit_function_argument(argument);
+        }
+        let argument_list = self.argument_type_list.take().unwrap();
+
+        symbol_table_mut!(self).leave_scope();
+
+        let return_type = pass_try!(self, symbol_table_mut!(self).resolve_type_name_or_err(&operator_declaration.return_type.name));
+        // unknown argument types are reported by `visit_function_argument`
+        if argument_list.len() == 2 {
+            pass_try!(self, symbol_table_mut!(self).find_type_mut_or_err(argument_list[0])).add_operator(operator_declaration.operator, argument_list[1], return_type);
+        }
+    }
 
-        let type_ref = match symbol_table!(self).find_type_ref(&function_argument_declaration.argument_type_name.name) {
+    fn visit_function_argument(&mut self, function_argument_declaration: &mut FunctionArgumentDeclaration) {
+        let type_ref = match symbol_table_mut!(self).resolve_type_name(&function_argument_declaration.argument_type_name.name) {
             Some(t) => t,
             None => pass_try!(self, Err(TypeError::new(function_argument_declaration.argument_type_name.get_span(), ErrorKind::TypeNotFound(function_argument_declaration.argument_type_name.name.to_owned())))),
         };
         function_argument_declaration.argument_type = Some(type_ref);
-        pass_try!(self, symbol_table_mut!(self).add_symbol_with_type(&function_argument_declaration.argument_name.name, type_ref));
-        list.push(type_ref);
-
-        self.argument_type_list = Some(list);
+        if let Some(ref mut list) = self.argument_type_list {
+            list.push(type_ref);
+        }
+        pass_try!(self, symbol_table_mut!(self).add_symbol_with_type(&function_argument_declaration.argument_name.name, type_ref, SymbolKind::Argument));
     }
 });
 
@@ -131,4 +173,39 @@ mod tests {
         assert!(symbol_table.borrow().find_type_by_name("test").unwrap().get_call_signature().unwrap().match_arguments(vec![i32TypeRef]));
         assert!(symbol_table.borrow().find_type_by_name("test").unwrap().get_call_signature().unwrap().match_return_type(Some(TestTypeRef)));
     }
+
+    fn check_operators(code: &str) -> (SymbolTableReference, PassResultReference) {
+        let mut ast = compile_ast(code);
+        let symbol_table = SymbolTableReference::new(SymbolTable::new(TypeEnvironment::new()));
+        let result = PassResultReference::new(PassResult::new());
+
+        let mut passes = PassCollection::from_passes(vec![
+            Box::new(check_primitives_pass::CheckPrimitivesPass::new(symbol_table.clone(), result.clone())),
+            Box::new(CheckFunctionSignaturePass::new(symbol_table.clone(), result.clone())),
+        ]);
+
+        passes.execute(&mut ast);
+        (symbol_table, result)
+    }
+
+    #[test]
+    fn operator_is_declared_on_left_hand_type() {
+        let (symbol_table, result) = check_operators("primitive type f32; primitive type vec4; operator * (lhs: vec4, rhs: f32) -> vec4;");
+
+        assert!(!result.borrow().has_errors());
+        let symbol_table = symbol_table.borrow();
+        let f32_type = symbol_table.find_type_ref("f32").unwrap();
+        let vec4_type = symbol_table.find_type_ref("vec4").unwrap();
+        assert_eq!(symbol_table.find_type(vec4_type).unwrap().find_operator_type(Operator::Multiply, f32_type), Some(vec4_type));
+        assert_eq!(symbol_table.find_type(f32_type).unwrap().find_operator_type(Operator::Multiply, vec4_type), None);
+    }
+
+    #[test]
+    fn operator_needs_two_arguments() {
+        let (_, result) = check_operators("primitive type f32; operator - (value: f32) -> f32;");
+        let errors = result.borrow_mut().take_errors();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].downcast_ref::<TypeError>().unwrap().get_kind(), &ErrorKind::IncompatibleArguments);
+    }
 }
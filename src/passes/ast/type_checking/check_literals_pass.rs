@@ -0,0 +1,239 @@
+use ::ast::*;
+use ::passes::*;
+use ::passes::ast::*;
+use ::passes::results::PassResultReference;
+use ::type_system::symbol_table::{ SymbolTableReference };
+use ::type_system::type_environment::TypeReference;
+use ::type_system::error::{ TypeError, ErrorKind };
+use ::type_system::literals;
+
+/// assigns a type to every literal
+/// suffixed literals get the suffix type, unsuffixed literals take the type expected
+/// by their context (return type, struct member, call argument, constant) or fall back to a default
+pub struct CheckLiteralsPass {
+    symbol_table: SymbolTableReference,
+    result: PassResultReference,
+    return_type: Option<TypeReference>,
+    expected_type: Option<TypeReference>,
+}
+
+impl CheckLiteralsPass {
+    pub fn new(symbol_table: SymbolTableReference, result: PassResultReference) -> CheckLiteralsPass {
+        CheckLiteralsPass {
+            symbol_table: symbol_table,
+            result: result,
+            return_type: None,
+            expected_type: None,
+        }
+    }
+
+    fn visit_expression_expecting(&mut self, expression: &mut ExpressionStatement, expected_type: Option<TypeReference>) {
+        let previous = self.expected_type.take();
+        self.expected_type = expected_type;
+        self.visit_expression(expression);
+        self.expected_type = previous;
+    }
+
+    fn find_type_name(&self, type_ref: TypeReference) -> Option<String> {
+        match symbol_table!(self).find_type(type_ref) {
+            Some(t) => Some(t.get_name().to_owned()),
+            None => None,
+        }
+    }
+}
+
+ast_pass_impl!(CheckLiteralsPass, {
+    fn visit_function(&mut self, function_declaration: &mut FunctionDeclaration) {
+        self.return_type = function_declaration.return_type;
+        self.walk_function(function_declaration);
+        self.return_type = None;
+    }
+
+    fn visit_constant(&mut self, constant_definition: &mut ConstantDefinition) {
+        let constant_type = symbol_table!(self).find_type_ref(&constant_definition.constant_type_name.name);
+
+        if let Some(ref mut initializer) = constant_definition.constant_initializer {
+            self.visit_expression_expecting(initializer, constant_type);
+        }
+    }
+
+    fn visit_local_statement(&mut self, local_statement: &mut LocalDeclaration) {
+        self.visit_expression_expecting(&mut local_statement.expression, None);
+    }
+
+    fn visit_return_statement(&mut self, return_statement: &mut ReturnDeclaration) {
+        let return_type = self.return_type;
+        self.visit_expression_expecting(&mut return_statement.expression, return_type);
+    }
+
+    fn visit_expression_statement(&mut self, expression_statement: &mut ExpressionStatement) {
+        self.visit_expression_expecting(expression_statement, None);
+    }
+
+    fn visit_call_expression(&mut self, call_expression: &mut CallExpression) {
+        let argument_types = {
+            let symbol_table = symbol_table!(self);
+            match symbol_table.find_type_ref(&call_expression.function_name.name) {
+                Some(t) => match symbol_table.find_type(t).and_then(|t| t.get_call_signature()) {
+                    Some(signature) => signature.get_arguments().clone(),
+                    None => Vec::new(),
+                },
+                None => Vec::new(),
+            }
+        };
+
+        for (i, argument) in call_expression.arguments.iter_mut().enumerate() {
+            let expected_type = argument_types.get(i).cloned();
+            self.visit_expression_expecting(argument, expected_type);
+        }
+    }
+
+    fn visit_struct_instantiation_expression(&mut self, struct_instantiation_expression: &mut StructInstantiationExpression) {
+        let struct_type = symbol_table!(self).find_type_ref(&struct_instantiation_expression.struct_type_name.name);
+
+        for initializer in struct_instantiation_expression.struct_field_initializer.iter_mut() {
+            let expected_type = match struct_type {
+                Some(t) => match symbol_table!(self).find_type(t) {
+                    Some(t) => t.find_member_type(&initializer.struct_field_name.name),
+                    None => None,
+                },
+                None => None,
+            };
+            self.visit_expression_expecting(&mut initializer.initializer, expected_type);
+        }
+
+        if let Some(ref mut base) = struct_instantiation_expression.base {
+            self.visit_expression_expecting(base, struct_type);
+        }
+    }
+
+    fn visit_literal_expression(&mut self, literal_expression: &mut LiteralExpression) {
+        let type_name = match literal_expression.literal_type_name {
+            Some(ref suffix) => {
+                if !literals::is_literal_compatible(&literal_expression.literal_expression_type, &suffix.name) {
+                    pass_try!(self, Err(TypeError::new(literal_expression.span, ErrorKind::IncompatibleLiteral(literal_expression.value.to_owned(), suffix.name.to_owned()))));
+                }
+                suffix.name.to_owned()
+            },
+            None => {
+                let expected_type_name = match self.expected_type {
+                    Some(t) => self.find_type_name(t),
+                    None => None,
+                };
+
+                match expected_type_name {
+                    Some(ref name) if literals::is_literal_compatible(&literal_expression.literal_expression_type, name) => name.to_owned(),
+                    _ => literals::default_type_name(&literal_expression.literal_expression_type).to_owned(),
+                }
+            },
+        };
+
+        pass_try!(self, literals::check_literal_range(literal_expression, &type_name));
+
+        let type_ref = match symbol_table!(self).find_type_ref(&type_name) {
+            Some(t) => t,
+            None => pass_try!(self, Err(TypeError::new(literal_expression.span, ErrorKind::TypeNotFound(type_name.to_owned())))),
+        };
+        literal_expression.literal_type = Some(type_ref);
+    }
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::testing::compile_ast;
+    use ::passes::results::PassResult;
+    use ::type_system::symbol_table::SymbolTable;
+    use ::type_system::type_environment::TypeEnvironment;
+    use ::passes::ast::type_checking::check_primitives_pass;
+    use ::passes::ast::type_checking::discover_structs_pass;
+    use ::passes::ast::type_checking::check_struct_member_pass;
+    use ::passes::ast::type_checking::check_function_signatures_pass;
+
+    const PRIMITIVES: &str = "primitive type bool; primitive type i32; primitive type i64; primitive type u32; primitive type u64; primitive type f32; primitive type f64;";
+
+    fn check(code: &str) -> (Ast, SymbolTableReference, PassResultReference) {
+        let mut ast = compile_ast(&format!("{} {}", PRIMITIVES, code));
+        let symbol_table = SymbolTableReference::new(SymbolTable::new(TypeEnvironment::new()));
+        let result = PassResultReference::new(PassResult::new());
+
+        let mut passes = PassCollection::from_passes(vec![
+            Box::new(check_primitives_pass::CheckPrimitivesPass::new(symbol_table.clone(), result.clone())),
+            Box::new(discover_structs_pass::DiscoverStructsPass::new(symbol_table.clone(), result.clone())),
+            Box::new(check_struct_member_pass::CheckStructMemberPass::new(symbol_table.clone(), result.clone())),
+            Box::new(check_function_signatures_pass::CheckFunctionSignaturePass::new(symbol_table.clone(), result.clone())),
+            Box::new(CheckLiteralsPass::new(symbol_table.clone(), result.clone())),
+        ]);
+
+        passes.execute(&mut ast);
+
+        (ast, symbol_table, result)
+    }
+
+    /// type of the literal returned or bound by the first statement of the first function
+    fn find_return_literal_type(ast: &Ast, symbol_table: &SymbolTableReference) -> String {
+        for item in ast.iter() {
+            if let &ItemKind::Function(ref function) = item {
+                let expression = match function.block.statements[0] {
+                    BlockStatement::Return(ref statement) => &statement.expression,
+                    BlockStatement::Local(ref statement) => &statement.expression,
+                    _ => continue,
+                };
+                if let ExpressionStatement::Literal(ref literal) = *expression {
+                    return symbol_table.borrow().find_type(literal.literal_type.unwrap()).unwrap().get_name().to_owned();
+                }
+            }
+        }
+        panic!("no literal found");
+    }
+
+    #[test]
+    fn suffix_determines_type() {
+        let (ast, symbol_table, result) = check("fn test() -> f64 { return 1.0f; }");
+
+        assert!(!result.borrow().has_errors());
+        assert_eq!(find_return_literal_type(&ast, &symbol_table), "f32");
+    }
+
+    #[test]
+    fn unsuffixed_literal_takes_context_type() {
+        let (ast, symbol_table, result) = check("fn test() -> u64 { return 0xFFFFFFFFFF; }");
+
+        assert!(!result.borrow().has_errors());
+        assert_eq!(find_return_literal_type(&ast, &symbol_table), "u64");
+    }
+
+    #[test]
+    fn unsuffixed_literal_defaults_without_context() {
+        let (ast, symbol_table, result) = check("fn test() -> f64 { let x = 1.5e3; return x; }");
+
+        assert!(!result.borrow().has_errors());
+        assert_eq!(find_return_literal_type(&ast, &symbol_table), "f64");
+    }
+
+    #[test]
+    fn out_of_range_literal_produces_an_error() {
+        let (_, _, result) = check("struct Test { value: i32, } fn test() -> Test { return Test { value: 3000000000, }; }");
+
+        assert!(result.borrow().has_errors());
+    }
+
+    #[test]
+    fn bool_and_suffixed_int_literals() {
+        let (_, _, result) = check("fn test() -> bool { return true; } fn other() -> i64 { return 1i64; }");
+
+        assert!(!result.borrow().has_errors());
+    }
+
+    #[test]
+    fn negative_literal_is_range_checked_as_a_whole() {
+        let (ast, symbol_table, result) = check("fn test() -> i32 { return -2147483648; }");
+
+        assert!(!result.borrow().has_errors());
+        assert_eq!(find_return_literal_type(&ast, &symbol_table), "i32");
+
+        let (_, _, result) = check("fn test() -> u32 { return -1; }");
+
+        assert!(result.borrow().has_errors());
+    }
+}
@@ -0,0 +1,558 @@
+use ::std::collections::HashSet;
+use ::ast::*;
+use ::passes::*;
+use ::passes::ast::*;
+use ::passes::results::PassResultReference;
+use ::type_system::symbol_table::{ SymbolTableReference, SymbolKind };
+use ::type_system::type_environment::TypeReference;
+use ::type_system::error::{ TypeError, ErrorKind };
+use ::type_system::literals;
+
+/// scalar and vector types a fragment stage can write to a render target
+const RENDER_TARGET_TYPES: &[&str] = &["f32", "i32", "u32", "vec2", "vec3", "vec4"];
+
+/// graphics stages in pipeline order, each stage feeds the next present one
+const GRAPHICS_STAGES: &[StageKind] = &[StageKind::Vertex, StageKind::TessControl, StageKind::TessEval, StageKind::Geometry, StageKind::Fragment];
+
+/// argument and return types of a stage function, inline or referenced
+struct StageSignature {
+    stage_name: String,
+    stage_kind: StageKind,
+    span: Span,
+    arguments: Vec<(Span, Option<TypeReference>)>,
+    return_type: Option<TypeReference>,
+    return_span: Span,
+}
+
+/// validates the stages of every program and resolves referenced stage functions
+/// each stage kind may appear once and must be legal for the program kind, stage functions take
+/// at most one structure as input, each stage output must match the input of the next stage,
+/// the fragment output must be a render target and compute stages need a workgroup size
+pub struct CheckProgramsPass {
+    symbol_table: SymbolTableReference,
+    result: PassResultReference,
+}
+
+impl CheckProgramsPass {
+    pub fn new(symbol_table: SymbolTableReference, result: PassResultReference) -> CheckProgramsPass {
+        CheckProgramsPass {
+            symbol_table: symbol_table,
+            result: result,
+        }
+    }
+
+    fn add_error(&mut self, error: TypeError) {
+        self.result.borrow_mut().add_error(Box::new(error));
+    }
+
+    fn type_name(&self, type_ref: TypeReference) -> String {
+        match symbol_table!(self).find_type(type_ref) {
+            Some(t) => t.get_name().to_owned(),
+            None => String::new(),
+        }
+    }
+
+    fn is_struct(&self, type_ref: TypeReference) -> bool {
+        match symbol_table!(self).find_type(type_ref) {
+            Some(t) => t.is_struct(),
+            None => false,
+        }
+    }
+
+    /// a render target type or a structure of render target types for multiple targets
+    fn is_render_target(&self, type_ref: TypeReference) -> bool {
+        let symbol_table = symbol_table!(self);
+        let type_definition = match symbol_table.find_type(type_ref) {
+            Some(t) => t,
+            None => return false,
+        };
+
+        match type_definition.get_member() {
+            Some(members) => members.get_members().iter().all(|m| {
+                match symbol_table.find_type(m.member_type) {
+                    Some(t) => RENDER_TARGET_TYPES.contains(&t.get_name()),
+                    None => false,
+                }
+            }),
+            None => RENDER_TARGET_TYPES.contains(&type_definition.get_name()),
+        }
+    }
+
+    fn stage_signature(&mut self, stage: &mut ProgramStageDefinition) -> Option<StageSignature> {
+        let function_name = match stage.function {
+            ProgramStageFunction::Inline(ref function) => {
+                return Some(StageSignature {
+                    stage_name: stage.stage_name.name.to_owned(),
+                    stage_kind: stage.stage_kind,
+                    span: stage.span,
+                    arguments: function.arguments.iter().map(|a| (a.span, a.argument_type)).collect(),
+                    return_type: function.return_type,
+                    return_span: function.return_type_name.span,
+                });
+            },
+            ProgramStageFunction::Reference(ref function_name) => function_name,
+        };
+
+        let function_type = match symbol_table_mut!(self).find_symbol(&function_name.name) {
+            Some(symbol) if symbol.kind == SymbolKind::Function => symbol.get_type(),
+            _ => None,
+        };
+        let function_type = match function_type {
+            Some(t) => t,
+            None => {
+                self.add_error(TypeError::new(function_name.span, ErrorKind::StageFunctionNotFound(function_name.name.to_owned())));
+                return None;
+            },
+        };
+
+        let (arguments, return_type) = match symbol_table!(self).find_type(function_type).and_then(|t| t.get_call_signature()) {
+            Some(signature) => (signature.get_arguments().iter().map(|&t| (function_name.span, Some(t))).collect(), signature.get_return_type()),
+            None => return None,
+        };
+
+        stage.declaring_type = Some(function_type);
+
+        Some(StageSignature {
+            stage_name: stage.stage_name.name.to_owned(),
+            stage_kind: stage.stage_kind,
+            span: stage.span,
+            arguments: arguments,
+            return_type: return_type,
+            return_span: function_name.span,
+        })
+    }
+
+    fn check_stage(&mut self, program_name: &str, stage: &StageSignature) {
+        let stage_name = &stage.stage_name;
+
+        if stage.arguments.len() > 1 {
+            self.add_error(TypeError::new(stage.span, ErrorKind::ProgramStageTooManyArguments(program_name.to_owned(), stage_name.to_owned())));
+            return;
+        }
+
+        if let Some(&(span, Some(argument_type))) = stage.arguments.first() {
+            if !self.is_struct(argument_type) {
+                let type_name = self.type_name(argument_type);
+                self.add_error(TypeError::new(span, ErrorKind::ProgramStageInputNotStruct(program_name.to_owned(), stage_name.to_owned(), type_name)));
+            }
+        }
+
+        self.check_stage_builtins(stage);
+    }
+
+    /// builtin members of the stage input must be provided to the stage, those of the output written by it
+    fn check_stage_builtins(&mut self, stage: &StageSignature) {
+        let stage_name = stage.stage_kind.get_name();
+        let mut errors = Vec::new();
+
+        {
+            let symbol_table = symbol_table!(self);
+            let builtins_of = |type_ref: Option<TypeReference>| {
+                type_ref.and_then(|t| symbol_table.find_type(t))
+                    .and_then(|t| t.get_member())
+                    .map(|members| members.get_members().iter().filter_map(|m| m.builtin.as_ref()).filter_map(|b| symbol_table.find_builtin(b)).collect())
+                    .unwrap_or_else(Vec::new)
+            };
+
+            if let Some(&(span, input)) = stage.arguments.first() {
+                for builtin in builtins_of(input) {
+                    if !builtin.is_input_of(stage.stage_kind) {
+                        errors.push(TypeError::new(span, ErrorKind::BuiltinNotStageInput(builtin.name.to_owned(), stage_name.to_owned())));
+                    }
+                }
+            }
+
+            for builtin in builtins_of(stage.return_type) {
+                if !builtin.is_output_of(stage.stage_kind) {
+                    errors.push(TypeError::new(stage.return_span, ErrorKind::BuiltinNotStageOutput(builtin.name.to_owned(), stage_name.to_owned())));
+                }
+            }
+        }
+
+        for error in errors {
+            self.add_error(error);
+        }
+    }
+
+    fn check_stage_combination(&mut self, program_definition: &ProgramDefinition) {
+        let program_name = &program_definition.program_name;
+        let has_stage = |kind: StageKind| program_definition.program_stages.iter().any(|s| s.stage_kind == kind);
+
+        let (allowed, required): (&[StageKind], &[StageKind]) = match program_definition.program_kind {
+            ProgramKind::Graphics => (GRAPHICS_STAGES, &[StageKind::Vertex]),
+            ProgramKind::Kernel => (&[StageKind::Compute], &[StageKind::Compute]),
+        };
+
+        for stage in &program_definition.program_stages {
+            if !allowed.contains(&stage.stage_kind) {
+                self.add_error(TypeError::new(stage.span, ErrorKind::ProgramStageNotAllowed(program_name.name.to_owned(), stage.stage_name.name.to_owned())));
+            }
+        }
+
+        for &kind in required {
+            if !has_stage(kind) {
+                self.add_error(TypeError::new(program_name.span, ErrorKind::MissingProgramStage(program_name.name.to_owned(), kind.get_name().to_owned())));
+            }
+        }
+
+        // tessellation needs both of its stages
+        if has_stage(StageKind::TessControl) != has_stage(StageKind::TessEval) {
+            let missing = if has_stage(StageKind::TessControl) { StageKind::TessEval } else { StageKind::TessControl };
+            self.add_error(TypeError::new(program_name.span, ErrorKind::MissingProgramStage(program_name.name.to_owned(), missing.get_name().to_owned())));
+        }
+    }
+
+    fn evaluate_workgroup_dimension(&self, dimension: &ExpressionStatement) -> Option<u32> {
+        let value = match *dimension {
+            ExpressionStatement::Literal(ref literal) if literal.literal_expression_type == LiteralType::Int => literals::parse_integer_literal(&literal.value),
+            ExpressionStatement::Variable(ref variable) => symbol_table!(self).find_constant_value(&variable.variable_name.name).and_then(|v| v.as_u64()),
+            _ => None,
+        };
+
+        match value {
+            Some(v) if v > 0 && v <= u32::max_value() as u64 => Some(v as u32),
+            _ => None,
+        }
+    }
+
+    fn check_workgroup_size(&mut self, program_definition: &mut ProgramDefinition) {
+        let program_name = program_definition.program_name.name.to_owned();
+
+        for stage in program_definition.program_stages.iter_mut() {
+            let stage_name = stage.stage_name.name.to_owned();
+
+            let attribute = match find_attribute(&stage.attributes, "workgroup_size") {
+                Some(attribute) => attribute,
+                None => {
+                    if stage.stage_kind == StageKind::Compute {
+                        self.add_error(TypeError::new(stage.span, ErrorKind::MissingWorkgroupSize(program_name.to_owned())));
+                    }
+                    continue;
+                },
+            };
+
+            if stage.stage_kind != StageKind::Compute {
+                self.add_error(TypeError::new(attribute.span, ErrorKind::InvalidWorkgroupSize(program_name.to_owned(), stage_name)));
+                continue;
+            }
+
+            // the argument count is reported by the attribute checks
+            if attribute.arguments.is_empty() || attribute.arguments.len() > 3 {
+                continue;
+            }
+
+            let mut size = [1; 3];
+            let mut is_valid = true;
+            for (i, dimension) in attribute.arguments.iter().enumerate() {
+                match self.evaluate_workgroup_dimension(dimension) {
+                    Some(value) => size[i] = value,
+                    None => {
+                        self.add_error(TypeError::new(dimension.get_span(), ErrorKind::InvalidWorkgroupSize(program_name.to_owned(), stage_name.to_owned())));
+                        is_valid = false;
+                        break;
+                    },
+                }
+            }
+
+            if is_valid {
+                stage.workgroup_size = Some(size);
+            }
+        }
+    }
+
+    fn check_linkage(&mut self, source: &StageSignature, target: &StageSignature) {
+        let source_output = match source.return_type {
+            Some(t) => t,
+            None => return,
+        };
+        let target_input = match target.arguments.first().and_then(|a| a.1) {
+            Some(t) => t,
+            None => return,
+        };
+
+        let is_compatible = symbol_table!(self).is_linkable(source_output, target_input);
+
+        if !is_compatible {
+            let error = ErrorKind::ProgramStageSignatureMismatch(
+                source.stage_name.to_owned(),
+                target.stage_name.to_owned(),
+                self.type_name(source_output),
+                self.type_name(target_input));
+            self.add_error(TypeError::new(target.span, error));
+        }
+    }
+}
+
+ast_pass_impl!(CheckProgramsPass, {
+    fn visit_program(&mut self, program_definition: &mut ProgramDefinition) {
+        let program_name = program_definition.program_name.name.to_owned();
+        let mut stage_kinds = HashSet::new();
+
+        self.check_stage_combination(program_definition);
+        self.check_workgroup_size(program_definition);
+
+        let mut signatures = Vec::new();
+
+        for stage in program_definition.program_stages.iter_mut() {
+            if !stage_kinds.insert(stage.stage_kind) {
+                self.add_error(TypeError::new(stage.span, ErrorKind::ProgramTypeTooManyStageInstances(program_name.to_owned(), stage.stage_name.name.to_owned())));
+            }
+
+            if let Some(signature) = self.stage_signature(stage) {
+                self.check_stage(&program_name, &signature);
+                signatures.push(signature);
+            }
+        }
+
+        let pipeline: Vec<&StageSignature> = GRAPHICS_STAGES.iter()
+            .filter_map(|&kind| signatures.iter().find(|s| s.stage_kind == kind))
+            .collect();
+
+        for pair in pipeline.windows(2) {
+            self.check_linkage(pair[0], pair[1]);
+        }
+
+        if let Some(fragment) = signatures.iter().find(|s| s.stage_kind == StageKind::Fragment) {
+            if let Some(output) = fragment.return_type {
+                if !self.is_render_target(output) {
+                    let type_name = self.type_name(output);
+                    self.add_error(TypeError::new(fragment.return_span, ErrorKind::InvalidRenderTarget(type_name)));
+                }
+            }
+        }
+    }
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::testing::compile_ast;
+    use ::passes::results::PassResult;
+    use ::type_system::symbol_table::SymbolTable;
+    use ::type_system::type_environment::TypeEnvironment;
+    use ::passes::ast::type_checking::check_primitives_pass;
+    use ::passes::ast::type_checking::check_builtins_pass;
+    use ::passes::ast::type_checking::discover_structs_pass;
+    use ::passes::ast::type_checking::check_struct_member_pass;
+    use ::passes::ast::type_checking::check_function_signatures_pass;
+    use ::passes::ast::type_checking::check_literals_pass;
+    use ::passes::ast::type_checking::evaluate_constants_pass;
+    use ::passes::ast::type_checking::check_uniforms_pass;
+    use ::passes::ast::type_checking::resolve_names_pass;
+
+    const TYPES: &str = "primitive type i32; primitive type u32; primitive type f32; primitive type vec2; primitive type vec4; primitive type mat4x4;
+        struct VertexInput { position: vec4, uv: vec2, }
+        struct VertexOutput { position: vec4, uv: vec2, }
+        struct FragmentInput { uv: vec2, position: vec4, }
+        struct Targets { color: vec4, normal: vec4, }
+        builtin position: vec4 out(vertex);
+        builtin vertex_index: u32 in(vertex);
+        builtin frag_depth: f32 out(fragment);
+        builtin local_invocation_index: u32 in(compute);
+        uniform color: vec4;
+        uniform mvp: mat4x4;";
+
+    fn check(code: &str) -> PassResultReference {
+        check_ast(code).2
+    }
+
+    fn check_ast(code: &str) -> (Ast, SymbolTableReference, PassResultReference) {
+        let mut ast = compile_ast(&format!("{} {}", TYPES, code));
+        let symbol_table = SymbolTableReference::new(SymbolTable::new(TypeEnvironment::new()));
+        let result = PassResultReference::new(PassResult::new());
+
+        let mut passes = PassCollection::from_passes(vec![
+            Box::new(check_primitives_pass::CheckPrimitivesPass::new(symbol_table.clone(), result.clone())),
+            Box::new(check_builtins_pass::CheckBuiltinsPass::new(symbol_table.clone(), result.clone())),
+            Box::new(evaluate_constants_pass::EvaluateConstantsPass::new(symbol_table.clone(), result.clone())),
+            Box::new(discover_structs_pass::DiscoverStructsPass::new(symbol_table.clone(), result.clone())),
+            Box::new(check_struct_member_pass::CheckStructMemberPass::new(symbol_table.clone(), result.clone())),
+            Box::new(check_function_signatures_pass::CheckFunctionSignaturePass::new(symbol_table.clone(), result.clone())),
+            Box::new(check_uniforms_pass::CheckUniformsPass::new(symbol_table.clone(), result.clone())),
+            Box::new(check_literals_pass::CheckLiteralsPass::new(symbol_table.clone(), result.clone())),
+            Box::new(CheckProgramsPass::new(symbol_table.clone(), result.clone())),
+            Box::new(resolve_names_pass::ResolveNamesPass::new(symbol_table.clone(), result.clone())),
+        ]);
+
+        passes.execute(&mut ast);
+
+        (ast, symbol_table, result)
+    }
+
+    #[test]
+    fn valid_program() {
+        let result = check("program Test {
+            stage vertex(input: VertexInput) -> VertexOutput { return VertexOutput { position: input.position, uv: input.uv, }; }
+            stage fragment(input: FragmentInput) -> Targets { return Targets { color: input.position, normal: input.position, }; }
+        }");
+
+        assert!(!result.borrow().has_errors());
+    }
+
+    #[test]
+    fn valid_program_with_referenced_functions() {
+        let result = check("program Test {
+            vertex: vertex_main,
+            fragment: fragment_main,
+        }
+        fn vertex_main(input: VertexInput) -> VertexOutput { return VertexOutput { position: input.position, uv: input.uv, }; }
+        fn fragment_main(input: FragmentInput) -> vec4 { return input.position; }");
+
+        assert!(!result.borrow().has_errors());
+    }
+
+    #[test]
+    fn referenced_stage_functions_are_resolved() {
+        let (ast, symbol_table, result) = check_ast("fn vertex_main(input: VertexInput) -> VertexOutput { return input; } program Test { vertex: vertex_main, }");
+
+        assert!(!result.borrow().has_errors());
+        let function_type = symbol_table.borrow_mut().find_symbol("vertex_main").unwrap().get_type();
+        let stage_type = ast.iter().filter_map(|item| match *item {
+            ItemKind::Program(ref program) => program.program_stages[0].declaring_type,
+            _ => None,
+        }).next();
+        assert!(function_type.is_some());
+        assert_eq!(stage_type, function_type);
+    }
+
+    #[test]
+    fn unknown_stage_references_produce_an_error() {
+        for &(code, name) in &[("program Test { vertex: missing, }", "missing"), ("program Test { vertex: VertexInput, }", "VertexInput")] {
+            let errors = check(code).borrow_mut().take_errors();
+            let kinds: Vec<ErrorKind> = errors.into_iter().map(|e| e.downcast::<TypeError>().unwrap().get_kind().clone()).collect();
+
+            assert_eq!(kinds, vec![ErrorKind::StageFunctionNotFound(name.to_string())]);
+        }
+    }
+
+    #[test]
+    fn referenced_functions_are_checked_as_stages() {
+        assert!(check("program Test { vertex: missing, }").borrow().has_errors());
+        assert!(check("fn fragment_main() -> mat4x4 { return mvp; } program Test { stage vertex() -> vec4 { return color; } fragment: fragment_main, }").borrow().has_errors());
+    }
+
+    #[test]
+    fn duplicate_stages_produce_an_error() {
+        let result = check("program Test {
+            stage vertex() -> vec4 { return color; }
+            stage fragment() -> vec4 { return color; }
+            stage fragment() -> vec4 { return color; }
+        }");
+
+        assert!(result.borrow().has_errors());
+    }
+
+    #[test]
+    fn stage_inputs_must_be_a_single_struct() {
+        assert!(check("program Test { stage vertex(a: VertexInput, b: VertexInput) -> VertexOutput { return a; } }").borrow().has_errors());
+        assert!(check("program Test { stage vertex(a: vec4) -> vec4 { return a; } }").borrow().has_errors());
+    }
+
+    #[test]
+    fn stages_link_nested_structs() {
+        let program = |fragment_input: &str| check(&format!("struct Surface {{ color: vec4, }}
+            struct Albedo {{ color: vec4, }}
+            struct SurfaceOutput {{ surface: Surface, }}
+            struct SurfaceInput {{ surface: {}, }}
+            program Test {{
+                stage vertex(input: VertexInput) -> SurfaceOutput {{ return SurfaceOutput {{ surface: Surface {{ color: input.position, }}, }}; }}
+                stage fragment(input: SurfaceInput) -> vec4 {{ return input.surface.color; }}
+            }}", fragment_input));
+
+        assert!(!program("Albedo").borrow().has_errors());
+        assert!(program("vec4").borrow().has_errors());
+    }
+
+    #[test]
+    fn mismatching_stages_produce_an_error() {
+        let result = check("program Test {
+            stage vertex(input: VertexInput) -> VertexOutput { return VertexOutput { position: input.position, uv: input.uv, }; }
+            stage fragment(input: Targets) -> vec4 { return input.color; }
+        }");
+
+        assert!(result.borrow().has_errors());
+    }
+
+    #[test]
+    fn invalid_render_target_produces_an_error() {
+        let result = check("program Test { stage vertex() -> vec4 { return color; } stage fragment() -> mat4x4 { return mvp; } }");
+
+        assert!(result.borrow().has_errors());
+    }
+
+    #[test]
+    fn builtin_members_must_match_the_stage() {
+        assert!(!check("struct Input { @builtin(vertex_index) index: u32, }
+            struct Output { @builtin(position) position: vec4, }
+            program Test { stage vertex(input: Input) -> Output { return Output { position: color, }; } }").borrow().has_errors());
+
+        assert!(check("struct Input { @builtin(vertex_index) index: u32, }
+            program Test {
+                stage vertex() -> vec4 { return color; }
+                stage fragment(input: Input) -> vec4 { return color; }
+            }").borrow().has_errors());
+
+        assert!(check("struct Output { @builtin(frag_depth) depth: f32, }
+            program Test { stage vertex() -> Output { return Output { depth: 1.0, }; } }").borrow().has_errors());
+    }
+
+    #[test]
+    fn builtin_outputs_do_not_link_to_the_next_stage() {
+        let program = |fragment_input: &str| check(&format!("struct VOut {{ @builtin(position) position: vec4, uv: vec2, }}
+            struct FIn {{ {} }}
+            program Test {{
+                stage vertex(input: VertexInput) -> VOut {{ return VOut {{ position: input.position, uv: input.uv, }}; }}
+                stage fragment(input: FIn) -> vec4 {{ return color; }}
+            }}", fragment_input));
+
+        assert!(!program("uv: vec2,").borrow().has_errors());
+
+        let errors = program("uv: vec4,").borrow_mut().take_errors();
+        let kinds: Vec<ErrorKind> = errors.into_iter().map(|e| e.downcast::<TypeError>().unwrap().get_kind().clone()).collect();
+        assert_eq!(kinds, vec![ErrorKind::ProgramStageSignatureMismatch("vertex".to_string(), "fragment".to_string(), "VOut".to_string(), "FIn".to_string())]);
+
+        assert!(program("uv: vec2, normal: vec4,").borrow().has_errors());
+    }
+
+    #[test]
+    fn valid_kernel() {
+        let result = check("const GROUP: u32 = 8u; kernel Blur { @workgroup_size(GROUP, 8) stage compute() -> u32 { return local_invocation_index; } }");
+
+        assert!(!result.borrow().has_errors());
+    }
+
+    #[test]
+    fn kernels_need_a_valid_workgroup_size() {
+        assert!(check("kernel Blur { stage compute() -> u32 { return local_invocation_index; } }").borrow().has_errors());
+        assert!(check("kernel Blur { @workgroup_size(0) stage compute() -> u32 { return local_invocation_index; } }").borrow().has_errors());
+        assert!(check("program Test { @workgroup_size(8) stage vertex() -> vec4 { return color; } }").borrow().has_errors());
+    }
+
+    #[test]
+    fn stages_must_match_the_program_kind() {
+        assert!(check("program Test { stage vertex() -> vec4 { return color; } @workgroup_size(8) stage compute() -> u32 { return local_invocation_index; } }").borrow().has_errors());
+        assert!(check("kernel Blur { @workgroup_size(8) stage compute() -> u32 { return local_invocation_index; } stage fragment() -> vec4 { return color; } }").borrow().has_errors());
+        assert!(check("program Test { stage fragment() -> vec4 { return color; } }").borrow().has_errors());
+    }
+
+    #[test]
+    fn tessellation_stages_are_paired_and_linked() {
+        assert!(check("program Test {
+            stage vertex(input: VertexInput) -> VertexOutput { return input; }
+            stage tess_control(input: VertexOutput) -> VertexOutput { return input; }
+        }").borrow().has_errors());
+
+        assert!(!check("program Test {
+            stage vertex(input: VertexInput) -> VertexOutput { return input; }
+            stage tess_control(input: VertexOutput) -> VertexOutput { return input; }
+            stage tess_eval(input: VertexOutput) -> VertexOutput { return input; }
+            stage geometry(input: VertexOutput) -> VertexOutput { return input; }
+            stage fragment(input: FragmentInput) -> vec4 { return input.position; }
+        }").borrow().has_errors());
+
+        assert!(check("program Test {
+            stage vertex(input: VertexInput) -> VertexOutput { return input; }
+            stage geometry(input: Targets) -> Targets { return input; }
+        }").borrow().has_errors());
+    }
+}
@@ -2,9 +2,10 @@ use ::ast::*;
 use ::passes::*;
 use ::passes::ast::*;
 use ::passes::results::PassResultReference;
-use ::type_system::symbol_table::{ SymbolTableReference };
+use ::type_system::symbol_table::{ SymbolTable, SymbolTableReference };
 use ::type_system::type_environment::TypeReference;
 use ::type_system::structure_members::{ StructureMembers, StructureMember };
+use ::type_system::error::{ TypeError, ErrorKind, TypeCheckResult };
 
 pub struct CheckStructMemberPass {
     symbol_table: SymbolTableReference,
@@ -23,6 +24,16 @@ impl CheckStructMemberPass {
 }
 
 ast_pass_impl!(CheckStructMemberPass, {
+    fn visit(&mut self, items: &mut Ast) {
+        for item in items.iter_mut() {
+            if let ItemKind::Struct(ref mut item) = *item {
+                self.visit_struct(item);
+            }
+        }
+
+        self.check_recursive_structs(items);
+    }
+
     fn visit_struct(&mut self, struct_definition: &mut StructDefinition) {
         self.member_list = Some(Vec::new());
 
@@ -36,14 +47,118 @@ ast_pass_impl!(CheckStructMemberPass, {
     }
 
     fn visit_struct_member(&mut self, struct_member_definition: &mut StructMemberDefinition) {
+        let type_name = &struct_member_definition.struct_member_type_name;
+        let struct_member_type = match symbol_table_mut!(self).resolve_type_name(&type_name.name) {
+            Some(t) => t,
+            None => pass_try!(self, Err(TypeError::new(type_name.span, ErrorKind::TypeNotFound(type_name.name.to_owned())))),
+        };
         let mut list = self.member_list.take().unwrap();
-        let struct_member_type = pass_try!(self, symbol_table!(self).find_type_ref_or_err(&struct_member_definition.struct_member_type_name.name));
         struct_member_definition.struct_member_type = Some(struct_member_type);
-        list.push(StructureMember::new(struct_member_definition.struct_member_name.name.clone(), struct_member_type));
+
+        let builtin = match struct_member_definition.get_builtin() {
+            Some(builtin_name) => {
+                // the member is still added so the struct stays usable
+                if let Err(err) = self.check_builtin(builtin_name, struct_member_type) {
+                    self.result.borrow_mut().add_error(Box::new(err));
+                }
+                Some(builtin_name.name.to_owned())
+            },
+            None => None,
+        };
+
+        list.push(StructureMember::new(struct_member_definition.struct_member_name.name.clone(), struct_member_type).with_builtin(builtin));
         self.member_list = Some(list);
     }
 });
 
+/// structures `current` contains by value, looking through arrays
+fn contained_structs(symbol_table: &SymbolTable, current: TypeReference) -> Vec<TypeReference> {
+    let members = match symbol_table.find_type(current).and_then(|t| t.get_member()) {
+        Some(m) => m,
+        None => return Vec::new(),
+    };
+
+    let mut contained = Vec::new();
+    for member in members.get_members() {
+        let mut member_type = member.member_type;
+        while let Some((element_type, _)) = symbol_table.find_type(member_type).and_then(|t| t.get_array()) {
+            member_type = element_type;
+        }
+        contained.push(member_type);
+    }
+    contained
+}
+
+/// the chain of structures leading from `start` back to itself, if any
+fn find_struct_cycle(symbol_table: &SymbolTable, start: TypeReference, current: TypeReference, trail: &mut Vec<TypeReference>) -> Option<Vec<TypeReference>> {
+    trail.push(current);
+
+    for contained in contained_structs(symbol_table, current) {
+        if contained == start {
+            let mut cycle = trail.clone();
+            cycle.push(start);
+            return Some(cycle);
+        }
+
+        if !trail.contains(&contained) {
+            if let Some(cycle) = find_struct_cycle(symbol_table, start, contained, trail) {
+                return Some(cycle);
+            }
+        }
+    }
+
+    trail.pop();
+    None
+}
+
+impl CheckStructMemberPass {
+    /// reports every structure containing itself by value, each cycle is reported once
+    fn check_recursive_structs(&mut self, items: &Ast) {
+        let mut reported = Vec::new();
+
+        for item in items.iter() {
+            let struct_definition = match *item {
+                ItemKind::Struct(ref s) => s,
+                _ => continue,
+            };
+            let type_ref = match struct_definition.declaring_type {
+                Some(t) if !reported.contains(&t) => t,
+                _ => continue,
+            };
+
+            let cycle = {
+                let symbol_table = symbol_table!(self);
+                find_struct_cycle(&symbol_table, type_ref, type_ref, &mut Vec::new()).map(|cycle| {
+                    let names = cycle.iter().map(|&t| symbol_table.find_type(t).map(|t| t.get_name().to_owned()).unwrap_or_default()).collect();
+                    (cycle, names)
+                })
+            };
+
+            if let Some((cycle, names)) = cycle {
+                reported.extend(cycle);
+                self.result.borrow_mut().add_error(Box::new(TypeError::new(struct_definition.struct_name.span, ErrorKind::RecursiveStruct(names))));
+            }
+        }
+    }
+
+    /// the builtin must be declared and match the type of the member
+    fn check_builtin(&self, builtin_name: &Identifier, member_type: TypeReference) -> TypeCheckResult<()> {
+        let symbol_table = symbol_table!(self);
+
+        let builtin = match symbol_table.find_builtin(&builtin_name.name) {
+            Some(b) => b,
+            None => return Err(TypeError::new(builtin_name.span, ErrorKind::BuiltinNotFound(builtin_name.name.to_owned()))),
+        };
+
+        if builtin.builtin_type != member_type {
+            let type_name = symbol_table.find_type(builtin.builtin_type).map(|t| t.get_name().to_owned()).unwrap_or_default();
+            return Err(TypeError::new(builtin_name.span, ErrorKind::BuiltinTypeMismatch(builtin_name.name.to_owned(), type_name)));
+        }
+
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -53,6 +168,7 @@ mod tests {
     use ::type_system::type_environment::TypeEnvironment;
     use ::passes::ast::type_checking::check_primitives_pass;
     use ::passes::ast::type_checking::discover_structs_pass;
+    use ::passes::ast::type_checking::check_builtins_pass;
 
     #[test]
     fn it_works() {
@@ -74,4 +190,43 @@ mod tests {
             StructureMember::new("bar".to_string(), TypeReference::new(0)),
         ]));
     }
+
+    fn check_builtin_members(code: &str) -> PassResultReference {
+        let mut ast = compile_ast(&format!("primitive type u32; primitive type vec4; builtin position: vec4 out(vertex); {}", code));
+        let symbol_table = SymbolTableReference::new(SymbolTable::new(TypeEnvironment::new()));
+        let result = PassResultReference::new(PassResult::new());
+
+        let mut passes = PassCollection::from_passes(vec![
+            Box::new(check_primitives_pass::CheckPrimitivesPass::new(symbol_table.clone(), result.clone())),
+            Box::new(check_builtins_pass::CheckBuiltinsPass::new(symbol_table.clone(), result.clone())),
+            Box::new(discover_structs_pass::DiscoverStructsPass::new(symbol_table.clone(), result.clone())),
+            Box::new(CheckStructMemberPass::new(symbol_table.clone(), result.clone())),
+        ]);
+
+        passes.execute(&mut ast);
+
+        result
+    }
+
+    #[test]
+    fn builtin_members_are_checked() {
+        assert!(!check_builtin_members("struct Output { @builtin(position) position: vec4, }").borrow().has_errors());
+        assert!(check_builtin_members("struct Output { @builtin(position) position: u32, }").borrow().has_errors());
+        assert!(check_builtin_members("struct Output { @builtin(postion) position: vec4, }").borrow().has_errors());
+    }
+
+    #[test]
+    fn struct_and_array_members() {
+        let result = check_builtin_members("struct Scene { lights: [Light; 4], camera: Camera, } struct Light { color: vec4, } struct Camera { position: vec4, }");
+
+        assert!(!result.borrow().has_errors());
+    }
+
+    #[test]
+    fn recursive_structs_produce_an_error() {
+        assert_eq!(check_builtin_members("struct Node { next: Node, }").borrow_mut().take_errors().len(), 1);
+        assert_eq!(check_builtin_members("struct A { b: B, } struct B { a: [A; 2], }").borrow_mut().take_errors().len(), 1);
+        assert_eq!(check_builtin_members("struct A { b: B, c: B, } struct B { a: A, }").borrow_mut().take_errors().len(), 1);
+        assert!(!check_builtin_members("struct A { b: B, c: B, } struct B { position: vec4, }").borrow().has_errors());
+    }
 }
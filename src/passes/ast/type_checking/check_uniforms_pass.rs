@@ -0,0 +1,342 @@
+use ::std::collections::{ HashMap, HashSet };
+use ::ast::*;
+use ::passes::*;
+use ::passes::ast::*;
+use ::passes::results::PassResultReference;
+use ::type_system::symbol_table::{ SymbolTableReference, SymbolKind };
+use ::type_system::structure_members::{ StructureMembers, StructureMember };
+use ::type_system::error::{ TypeError, ErrorKind };
+
+/// names a function refers to and the functions it calls
+struct FunctionReferences {
+    variables: Vec<(String, Span)>,
+    calls: Vec<String>,
+    locals: Vec<(String, Span)>,
+}
+
+impl FunctionReferences {
+    fn from_function(function: &FunctionDeclaration) -> FunctionReferences {
+        let mut references = FunctionReferences {
+            variables: Vec::new(),
+            calls: Vec::new(),
+            locals: Vec::new(),
+        };
+
+        for statement in &function.block.statements {
+            match *statement {
+                BlockStatement::Local(ref s) => {
+                    references.locals.push((s.symbol_name.name.to_owned(), s.symbol_name.span));
+                    references.add_expression(&s.expression);
+                },
+                BlockStatement::Return(ref s) => references.add_expression(&s.expression),
+                BlockStatement::Expression(ref s) => references.add_expression(s),
+            }
+        }
+
+        // arguments shadow uniforms of the same name
+        let arguments: Vec<&str> = function.arguments.iter().map(|a| &*a.argument_name.name).collect();
+        references.variables.retain(|&(ref name, _)| !arguments.contains(&&**name));
+
+        references
+    }
+
+    fn add_expression(&mut self, expression: &ExpressionStatement) {
+        match *expression {
+            ExpressionStatement::Infix(ref e) => {
+                self.add_expression(&e.left_hand);
+                self.add_expression(&e.right_hand);
+            },
+            ExpressionStatement::Literal(_) => (),
+            ExpressionStatement::Call(ref e) => {
+                self.calls.push(e.function_name.name.to_owned());
+                for argument in &e.arguments {
+                    self.add_expression(argument);
+                }
+            },
+            ExpressionStatement::StructInstantiation(ref e) => {
+                for initializer in &e.struct_field_initializer {
+                    self.add_expression(&initializer.initializer);
+                }
+                if let Some(ref base) = e.base {
+                    self.add_expression(base);
+                }
+            },
+            ExpressionStatement::FieldAccessor(ref e) => self.variables.push((e.variable_name.name.to_owned(), e.variable_name.span)),
+            ExpressionStatement::IndexAccessor(ref e) => {
+                self.variables.push((e.variable_name.name.to_owned(), e.variable_name.span));
+                self.add_expression(&e.access_expression);
+            },
+            ExpressionStatement::Variable(ref e) => self.variables.push((e.variable_name.name.to_owned(), e.variable_name.span)),
+        }
+    }
+}
+
+/// names of the functions called directly or indirectly by the `pending` functions, these included
+fn find_reachable_functions(functions: &HashMap<String, FunctionReferences>, mut pending: Vec<String>) -> HashSet<String> {
+    let mut reachable = HashSet::new();
+    while let Some(function_name) = pending.pop() {
+        if !reachable.insert(function_name.to_owned()) {
+            continue;
+        }
+        if let Some(references) = functions.get(&function_name) {
+            pending.extend(references.calls.iter().cloned());
+        }
+    }
+    reachable
+}
+
+/// names of the variables used by the stages of `program` and the functions reachable from them
+/// the uniforms and uniform blocks of these names are the ones the program reads
+pub fn find_program_variables(items: &Ast, program: &ProgramDefinition) -> HashSet<String> {
+    let mut functions = HashMap::new();
+    for item in items.iter() {
+        if let ItemKind::Function(ref function) = *item {
+            functions.insert(function.function_name.name.to_owned(), FunctionReferences::from_function(function));
+        }
+    }
+
+    let mut variables = HashSet::new();
+    let mut pending = Vec::new();
+    for stage in &program.program_stages {
+        match stage.function {
+            ProgramStageFunction::Inline(ref function) => {
+                let references = FunctionReferences::from_function(function);
+                variables.extend(references.variables.into_iter().map(|(name, _)| name));
+                pending.extend(references.calls);
+            },
+            ProgramStageFunction::Reference(ref function_name) => pending.push(function_name.name.to_owned()),
+        }
+    }
+
+    for function_name in find_reachable_functions(&functions, pending) {
+        if let Some(references) = functions.get(&function_name) {
+            variables.extend(references.variables.iter().map(|&(ref name, _)| name.to_owned()));
+        }
+    }
+    variables
+}
+
+/// resolves the types of `uniform` declarations and checks how uniforms are used
+/// uniforms cannot be rebound by a `let`, cannot appear in constant initializers and may only be
+/// used by program stages and functions reachable from them
+/// the language has no assignment, so a `let` of the uniform's name is the only write to check
+pub struct CheckUniformsPass {
+    symbol_table: SymbolTableReference,
+    result: PassResultReference,
+}
+
+impl CheckUniformsPass {
+    pub fn new(symbol_table: SymbolTableReference, result: PassResultReference) -> CheckUniformsPass {
+        CheckUniformsPass {
+            symbol_table: symbol_table,
+            result: result,
+        }
+    }
+
+    fn add_error(&mut self, error: TypeError) {
+        self.result.borrow_mut().add_error(Box::new(error));
+    }
+
+    fn check_locals(&mut self, references: &FunctionReferences, uniforms: &HashSet<String>) {
+        for &(ref name, span) in &references.locals {
+            if uniforms.contains(name) {
+                self.add_error(TypeError::new(span, ErrorKind::UniformIsReadOnly(name.to_owned())));
+            }
+        }
+    }
+
+    fn check_uniform_usage(&mut self, items: &Ast, uniforms: &HashSet<String>) {
+        let mut functions = HashMap::new();
+        let mut pending = Vec::new();
+
+        for item in items.iter() {
+            match *item {
+                ItemKind::Function(ref function) => {
+                    let references = FunctionReferences::from_function(function);
+                    self.check_locals(&references, uniforms);
+                    functions.insert(function.function_name.name.to_owned(), references);
+                },
+                ItemKind::Program(ref program) => {
+                    for stage in &program.program_stages {
+                        match stage.function {
+                            ProgramStageFunction::Inline(ref function) => {
+                                let references = FunctionReferences::from_function(function);
+                                self.check_locals(&references, uniforms);
+                                pending.extend(references.calls);
+                            },
+                            ProgramStageFunction::Reference(ref function_name) => pending.push(function_name.name.to_owned()),
+                        }
+                    }
+                },
+                ItemKind::Constant(ref constant) => {
+                    if let Some(ref initializer) = constant.constant_initializer {
+                        let mut references = FunctionReferences { variables: Vec::new(), calls: Vec::new(), locals: Vec::new() };
+                        references.add_expression(initializer);
+                        for (name, span) in references.variables {
+                            if uniforms.contains(&name) {
+                                self.add_error(TypeError::new(span, ErrorKind::UniformInConstant(name)));
+                            }
+                        }
+                    }
+                },
+                _ => (),
+            }
+        }
+
+        let reachable = find_reachable_functions(&functions, pending);
+        for item in items.iter() {
+            if let ItemKind::Function(ref function) = *item {
+                let function_name = &function.function_name.name;
+                if reachable.contains(function_name) {
+                    continue;
+                }
+
+                let mut reported = HashSet::new();
+                for &(ref name, span) in &functions[function_name].variables {
+                    if uniforms.contains(name) && reported.insert(name.to_owned()) {
+                        self.add_error(TypeError::new(span, ErrorKind::UniformOutsideStage(name.to_owned(), function_name.to_owned())));
+                    }
+                }
+            }
+        }
+    }
+}
+
+ast_pass_impl!(CheckUniformsPass, {
+    fn visit(&mut self, items: &mut Ast) {
+        let mut uniforms = HashSet::new();
+
+        for item in items.iter_mut() {
+            match *item {
+                ItemKind::Uniform(ref mut uniform) => {
+                    uniforms.insert(uniform.uniform_name.name.to_owned());
+                    self.visit_uniform(uniform);
+                },
+                ItemKind::UniformBlock(ref mut block) => {
+                    uniforms.insert(block.block_name.name.to_owned());
+                    self.visit_uniform_block(block);
+                },
+                _ => (),
+            }
+        }
+
+        self.check_uniform_usage(items, &uniforms);
+    }
+
+    fn visit_uniform(&mut self, uniform_definition: &mut UniformDefinition) {
+        let type_ref = match symbol_table_mut!(self).resolve_type_name(&uniform_definition.uniform_type_name.name) {
+            Some(t) => t,
+            None => pass_try!(self, Err(TypeError::new(uniform_definition.uniform_type_name.span, ErrorKind::TypeNotFound(uniform_definition.uniform_type_name.name.to_owned())))),
+        };
+
+        pass_try!(self, symbol_table_mut!(self).add_symbol_with_type(&uniform_definition.uniform_name.name, type_ref, SymbolKind::Uniform));
+        uniform_definition.uniform_type = Some(type_ref);
+    }
+
+    fn visit_uniform_block(&mut self, uniform_block_definition: &mut UniformBlockDefinition) {
+        let mut member_list = Vec::new();
+        for member in uniform_block_definition.block_member.iter_mut() {
+            if let Some(builtin_name) = member.get_builtin() {
+                self.add_error(TypeError::new(builtin_name.span, ErrorKind::BuiltinInUniform(builtin_name.name.to_owned())));
+            }
+            let member_type = match symbol_table_mut!(self).resolve_type_name(&member.struct_member_type_name.name) {
+                Some(t) => t,
+                None => pass_try!(self, Err(TypeError::new(member.struct_member_type_name.span, ErrorKind::TypeNotFound(member.struct_member_type_name.name.to_owned())))),
+            };
+            member.struct_member_type = Some(member_type);
+            member_list.push(StructureMember::new(member.struct_member_name.name.clone(), member_type));
+        }
+
+        let type_ref = pass_try!(self, symbol_table_mut!(self).create_type(&uniform_block_definition.block_name.name));
+        pass_try!(self, symbol_table_mut!(self).find_type_mut_or_err(type_ref).and_then(|t| t.set_members(StructureMembers::new(member_list))));
+        pass_try!(self, symbol_table_mut!(self).add_symbol_with_type(&uniform_block_definition.block_name.name, type_ref, SymbolKind::Uniform));
+        uniform_block_definition.declaring_type = Some(type_ref);
+    }
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::testing::compile_ast;
+    use ::passes::results::PassResult;
+    use ::type_system::symbol_table::SymbolTable;
+    use ::type_system::type_environment::TypeEnvironment;
+    use ::passes::ast::type_checking::check_primitives_pass;
+    use ::passes::ast::type_checking::discover_structs_pass;
+    use ::passes::ast::type_checking::check_struct_member_pass;
+
+    fn check(code: &str) -> (SymbolTableReference, PassResultReference) {
+        let mut ast = compile_ast(&format!("primitive type f32; primitive type vec4; primitive type mat4x4; {}", code));
+        let symbol_table = SymbolTableReference::new(SymbolTable::new(TypeEnvironment::new()));
+        let result = PassResultReference::new(PassResult::new());
+
+        let mut passes = PassCollection::from_passes(vec![
+            Box::new(check_primitives_pass::CheckPrimitivesPass::new(symbol_table.clone(), result.clone())),
+            Box::new(discover_structs_pass::DiscoverStructsPass::new(symbol_table.clone(), result.clone())),
+            Box::new(check_struct_member_pass::CheckStructMemberPass::new(symbol_table.clone(), result.clone())),
+            Box::new(CheckUniformsPass::new(symbol_table.clone(), result.clone())),
+        ]);
+
+        passes.execute(&mut ast);
+
+        (symbol_table, result)
+    }
+
+    #[test]
+    fn uniforms_are_typed() {
+        let (symbol_table, result) = check("uniform mvp: mat4x4; uniform Light { color: vec4, intensity: f32, }");
+
+        assert!(!result.borrow().has_errors());
+
+        let mut symbol_table = symbol_table.borrow_mut();
+        let light_type = symbol_table.find_symbol("Light").unwrap().get_type().unwrap();
+        assert!(symbol_table.find_symbol("mvp").unwrap().get_type().is_some());
+        assert!(symbol_table.find_type(light_type).unwrap().find_member_type("intensity").is_some());
+    }
+
+    #[test]
+    fn builtins_in_uniform_blocks_produce_an_error() {
+        let (_, result) = check("uniform Light { @builtin(position) position: vec4, }");
+
+        assert!(result.borrow().has_errors());
+    }
+
+    #[test]
+    fn uniforms_reachable_from_stages() {
+        let (_, result) = check("uniform Light { color: vec4, }
+            fn light_color() -> vec4 { return Light.color; }
+            program Test { stage fragment() -> vec4 { return light_color(); } }");
+
+        assert!(!result.borrow().has_errors());
+    }
+
+    #[test]
+    fn uniforms_in_referenced_stage_functions() {
+        let (_, result) = check("uniform mvp: mat4x4; fn vertex_main() -> mat4x4 { return mvp; } program Test { vertex: vertex_main, }");
+
+        assert!(!result.borrow().has_errors());
+    }
+
+    #[test]
+    fn uniforms_outside_stages_produce_an_error() {
+        let (_, result) = check("uniform mvp: mat4x4; fn helper() -> mat4x4 { return mvp; }");
+
+        assert!(result.borrow().has_errors());
+    }
+
+    #[test]
+    fn uniforms_in_constants_produce_an_error() {
+        let (_, result) = check("uniform scale: f32; const DOUBLE: f32 = scale * 2.0;");
+
+        assert!(result.borrow().has_errors());
+    }
+
+    #[test]
+    fn uniforms_cannot_be_rebound_by_locals() {
+        let (_, result) = check("uniform mvp: mat4x4; uniform Light { color: vec4, } program Test { stage vertex() -> mat4x4 { let color = Light.color; let mvp = mvp; return mvp; } }");
+
+        let errors = result.borrow_mut().take_errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].downcast_ref::<TypeError>().unwrap().get_kind(), &ErrorKind::UniformIsReadOnly("mvp".to_owned()));
+    }
+}
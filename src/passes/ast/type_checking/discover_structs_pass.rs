@@ -2,13 +2,13 @@ use ::ast::*;
 use ::passes::*;
 use ::passes::ast::*;
 use ::passes::results::PassResultReference;
-use ::type_system::symbol_table::{ SymbolTableReference };
+use ::type_system::symbol_table::{ SymbolTableReference, SymbolKind };
 use ::type_system::type_environment::TypeReference;
 
 ast_pass!(DiscoverStructsPass, {
     fn visit_struct(&mut self, struct_definition: &mut StructDefinition) {
         let type_ref = pass_try!(self, symbol_table_mut!(self).create_type(&struct_definition.struct_name.name));
-        pass_try!(self, symbol_table_mut!(self).add_symbol_with_type(&struct_definition.struct_name.name, type_ref));
+        pass_try!(self, symbol_table_mut!(self).add_symbol_with_type(&struct_definition.struct_name.name, type_ref, SymbolKind::Type));
         struct_definition.declaring_type = Some(type_ref);
         self.walk_struct(struct_definition);
     }
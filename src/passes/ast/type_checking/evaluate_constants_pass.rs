@@ -0,0 +1,396 @@
+use ::std::collections::HashMap;
+use ::ast::*;
+use ::passes::*;
+use ::passes::ast::*;
+use ::passes::results::PassResultReference;
+use ::type_system::symbol_table::{ SymbolTableReference, SymbolKind };
+use ::type_system::type_environment::TypeReference;
+use ::type_system::error::{ TypeError, ErrorKind, TypeCheckResult };
+use ::type_system::constant_value::ConstantValue;
+use ::type_system::literals;
+
+enum ConstantState {
+    Evaluating,
+    Evaluated(ConstantValue, String),
+    Failed,
+}
+
+/// folds the initializer of every `const` into a `ConstantValue`
+/// supports literals, infix operators, casts like `i32(x)` and references to other constants
+/// the values are stored on the definition and in the symbol table for later passes
+/// array lengths are folded too, `[Light; MAX_LIGHTS]` is renamed to `[Light; 4]` before any type is resolved
+pub struct EvaluateConstantsPass {
+    symbol_table: SymbolTableReference,
+    result: PassResultReference,
+    states: HashMap<String, ConstantState>,
+    trail: Vec<String>,
+}
+
+impl EvaluateConstantsPass {
+    pub fn new(symbol_table: SymbolTableReference, result: PassResultReference) -> EvaluateConstantsPass {
+        EvaluateConstantsPass {
+            symbol_table: symbol_table,
+            result: result,
+            states: HashMap::new(),
+            trail: Vec::new(),
+        }
+    }
+
+    fn is_scalar_type_name(type_name: &str) -> bool {
+        type_name == "bool" || literals::is_integer_type_name(type_name) || literals::is_float_type_name(type_name)
+    }
+
+    fn find_type_name(&self, type_ref: Option<TypeReference>) -> Option<String> {
+        match type_ref {
+            Some(t) => symbol_table!(self).find_type(t).map(|t| t.get_name().to_owned()),
+            None => None,
+        }
+    }
+
+    fn evaluate_constant(&mut self, constants: &HashMap<String, &ConstantDefinition>, name: &str, span: Span) -> TypeCheckResult<(ConstantValue, String)> {
+        match self.states.get(name) {
+            Some(&ConstantState::Evaluated(value, ref type_name)) => return Ok((value, type_name.to_owned())),
+            Some(&ConstantState::Failed) => return Err(TypeError::new(span, ErrorKind::NotConstant)),
+            Some(&ConstantState::Evaluating) => {
+                let start = self.trail.iter().position(|n| n == name).unwrap_or(0);
+                let mut cycle = self.trail[start..].to_vec();
+                cycle.push(name.to_owned());
+                return Err(TypeError::new(span, ErrorKind::ConstantCycle(cycle)));
+            },
+            None => (),
+        }
+
+        let constant = match constants.get(name) {
+            Some(c) => *c,
+            None => return Err(TypeError::new(span, ErrorKind::NotConstant)),
+        };
+
+        let initializer = match constant.constant_initializer {
+            Some(ref i) => i,
+            None => return Err(TypeError::new(span, ErrorKind::NotConstant)),
+        };
+
+        let type_name = constant.constant_type_name.name.to_owned();
+        if !EvaluateConstantsPass::is_scalar_type_name(&type_name) {
+            self.states.insert(name.to_owned(), ConstantState::Failed);
+            return Err(TypeError::new(constant.constant_type_name.span, ErrorKind::NotConstant));
+        }
+
+        self.states.insert(name.to_owned(), ConstantState::Evaluating);
+        self.trail.push(name.to_owned());
+        let evaluated = self.evaluate_expression(constants, initializer, Some(&type_name));
+        self.trail.pop();
+
+        let evaluated = evaluated.and_then(|(value, value_type_name)| {
+            if value_type_name != type_name {
+                Err(TypeError::new(initializer.get_span(), ErrorKind::IncompatibleTypes(constant.constant_type_name.span, initializer.get_span())))
+            } else {
+                Ok(value)
+            }
+        });
+
+        match evaluated {
+            Ok(value) => {
+                self.states.insert(name.to_owned(), ConstantState::Evaluated(value, type_name.to_owned()));
+                Ok((value, type_name))
+            },
+            Err(err) => {
+                self.states.insert(name.to_owned(), ConstantState::Failed);
+                Err(err)
+            },
+        }
+    }
+
+    /// unsuffixed literals take `expected_type_name` like in `CheckLiteralsPass`, which runs later
+    fn evaluate_expression(&mut self, constants: &HashMap<String, &ConstantDefinition>, expression: &ExpressionStatement, expected_type_name: Option<&str>) -> TypeCheckResult<(ConstantValue, String)> {
+        match *expression {
+            ExpressionStatement::Literal(ref literal) => {
+                let type_name = match self.find_type_name(literal.literal_type) {
+                    Some(name) => name,
+                    None => match (&literal.literal_type_name, expected_type_name) {
+                        (&Some(ref suffix), _) => suffix.name.to_owned(),
+                        (&None, Some(name)) if literals::is_literal_compatible(&literal.literal_expression_type, name) => name.to_owned(),
+                        (&None, _) => literals::default_type_name(&literal.literal_expression_type).to_owned(),
+                    },
+                };
+                let value = try!(ConstantValue::from_literal(literal, &type_name));
+                Ok((value, type_name))
+            },
+            ExpressionStatement::Infix(ref infix) => {
+                let (lhs, lhs_type_name) = try!(self.evaluate_expression(constants, &infix.left_hand, expected_type_name));
+                let (rhs, rhs_type_name) = try!(self.evaluate_expression(constants, &infix.right_hand, expected_type_name));
+
+                if lhs_type_name != rhs_type_name {
+                    return Err(TypeError::new(infix.span, ErrorKind::IncompatibleTypes(infix.left_hand.get_span(), infix.right_hand.get_span())));
+                }
+
+                let value = try!(ConstantValue::apply(&infix.operator, lhs, rhs, &lhs_type_name, infix.span));
+                Ok((value, lhs_type_name))
+            },
+            ExpressionStatement::Call(ref call) => {
+                let type_name = call.function_name.name.to_owned();
+                if !EvaluateConstantsPass::is_scalar_type_name(&type_name) || call.arguments.len() != 1 {
+                    return Err(TypeError::new(call.span, ErrorKind::NotConstant));
+                }
+
+                let (value, _) = try!(self.evaluate_expression(constants, &call.arguments[0], None));
+                let value = try!(value.cast(&type_name, call.span));
+                Ok((value, type_name))
+            },
+            ExpressionStatement::Variable(ref variable) => {
+                self.evaluate_constant(constants, &variable.variable_name.name, variable.span)
+            },
+            ref e => Err(TypeError::new(e.get_span(), ErrorKind::NotConstant)),
+        }
+    }
+
+    /// renames an array type like `[Light; MAX_LIGHTS]` to `[Light; 4]`, element types first
+    fn fold_array_type(&mut self, type_name: &mut TypeName) -> TypeCheckResult<()> {
+        let array = match type_name.array {
+            Some(ref mut array) => array,
+            None => return Ok(()),
+        };
+        try!(self.fold_array_type(&mut array.element_type_name));
+
+        // all constants are evaluated at this point, their values are found in `states`
+        let (value, _) = try!(self.evaluate_expression(&HashMap::new(), &array.length, None));
+        let length = match value {
+            ConstantValue::Int(length) if length > 0 && length <= u32::MAX as i64 => length as u32,
+            ConstantValue::UInt(length) if length > 0 && length <= u32::MAX as u64 => length as u32,
+            _ => return Err(TypeError::new(array.length.get_span(), ErrorKind::InvalidArrayLength(type_name.name.to_owned()))),
+        };
+
+        type_name.name = format!("[{}; {}]", array.element_type_name.name, length);
+        Ok(())
+    }
+}
+
+/// every member, argument and return type written in an item
+fn type_names_mut(item: &mut ItemKind) -> Vec<&mut TypeName> {
+    fn function_type_names(function: &mut FunctionDeclaration) -> Vec<&mut TypeName> {
+        let mut type_names: Vec<&mut TypeName> = function.arguments.iter_mut().map(|a| &mut a.argument_type_name).collect();
+        type_names.push(&mut function.return_type_name);
+        type_names
+    }
+
+    match *item {
+        ItemKind::UniformBlock(ref mut block) => block.block_member.iter_mut().map(|m| &mut m.struct_member_type_name).collect(),
+        ItemKind::Struct(ref mut structure) => structure.struct_member.iter_mut().map(|m| &mut m.struct_member_type_name).collect(),
+        ItemKind::Function(ref mut function) => function_type_names(function),
+        ItemKind::Program(ref mut program) => program.program_stages.iter_mut().flat_map(|stage| match stage.function {
+            ProgramStageFunction::Inline(ref mut function) => function_type_names(function),
+            ProgramStageFunction::Reference(_) => Vec::new(),
+        }).collect(),
+        ItemKind::Operator(ref mut operator) => operator.arguments.iter_mut().map(|a| &mut a.argument_type_name).collect(),
+        _ => Vec::new(),
+    }
+}
+
+ast_pass_impl!(EvaluateConstantsPass, {
+    fn visit(&mut self, items: &mut Ast) {
+        let mut values = HashMap::new();
+
+        {
+            let mut constants = HashMap::new();
+            for item in items.iter() {
+                if let ItemKind::Constant(ref constant) = *item {
+                    if constant.constant_variant == ConstantVariant::Constant {
+                        constants.insert(constant.constant_name.name.to_owned(), constant);
+                    }
+                }
+            }
+
+            for item in items.iter() {
+                if let ItemKind::Constant(ref constant) = *item {
+                    if constant.constant_variant != ConstantVariant::Constant || constant.constant_initializer.is_none() {
+                        continue;
+                    }
+
+                    let name = &constant.constant_name.name;
+                    let already_failed = match self.states.get(name) {
+                        Some(&ConstantState::Failed) => true,
+                        _ => false,
+                    };
+                    if already_failed {
+                        continue;
+                    }
+
+                    match self.evaluate_constant(&constants, name, constant.constant_name.span) {
+                        Ok((value, _)) => { values.insert(name.to_owned(), value); },
+                        Err(err) => self.result.borrow_mut().add_error(Box::new(err)),
+                    }
+                }
+            }
+        }
+
+        for item in items.iter_mut() {
+            for type_name in type_names_mut(item) {
+                if let Err(err) = self.fold_array_type(type_name) {
+                    self.result.borrow_mut().add_error(Box::new(err));
+                }
+            }
+        }
+
+        for item in items.iter_mut() {
+            if let ItemKind::Constant(ref mut constant) = *item {
+                if constant.constant_variant != ConstantVariant::Constant {
+                    continue;
+                }
+
+                let name = constant.constant_name.name.to_owned();
+                if constant.constant_initializer.is_none() {
+                    self.result.borrow_mut().add_error(Box::new(TypeError::new(constant.span, ErrorKind::ConstantWithoutInitializer(name.to_owned()))));
+                }
+
+                // structures are not discovered yet, constants of other than scalar types are reported above
+                if !EvaluateConstantsPass::is_scalar_type_name(&constant.constant_type_name.name) {
+                    continue;
+                }
+
+                let type_ref = match symbol_table!(self).find_type_ref(&constant.constant_type_name.name) {
+                    Some(t) => t,
+                    None => {
+                        self.result.borrow_mut().add_error(Box::new(TypeError::new(constant.constant_type_name.span, ErrorKind::TypeNotFound(constant.constant_type_name.name.to_owned()))));
+                        continue;
+                    },
+                };
+                constant.constant_type = Some(type_ref);
+
+                if let Err(err) = symbol_table_mut!(self).add_symbol_with_type(&name, type_ref, SymbolKind::Constant) {
+                    self.result.borrow_mut().add_error(Box::new(err));
+                    continue;
+                }
+
+                if let Some(&value) = values.get(&name) {
+                    constant.constant_value = Some(value);
+                    if let Err(err) = symbol_table_mut!(self).add_constant_value(&name, value) {
+                        self.result.borrow_mut().add_error(Box::new(err));
+                    }
+                }
+            }
+        }
+    }
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::testing::compile_ast;
+    use ::passes::results::PassResult;
+    use ::type_system::symbol_table::SymbolTable;
+    use ::type_system::type_environment::TypeEnvironment;
+    use ::passes::ast::type_checking::check_primitives_pass;
+    use ::passes::ast::type_checking::discover_structs_pass;
+    use ::passes::ast::type_checking::check_struct_member_pass;
+    use ::passes::ast::type_checking::check_literals_pass;
+
+    const PRIMITIVES: &str = "primitive type bool; primitive type i32; primitive type i64; primitive type u32; primitive type u64; primitive type f32; primitive type f64;";
+
+    fn evaluate_ast(code: &str) -> (Ast, SymbolTableReference, PassResultReference) {
+        let mut ast = compile_ast(&format!("{} {}", PRIMITIVES, code));
+        let symbol_table = SymbolTableReference::new(SymbolTable::new(TypeEnvironment::new()));
+        let result = PassResultReference::new(PassResult::new());
+
+        let mut passes = PassCollection::from_passes(vec![
+            Box::new(check_primitives_pass::CheckPrimitivesPass::new(symbol_table.clone(), result.clone())),
+            Box::new(EvaluateConstantsPass::new(symbol_table.clone(), result.clone())),
+            Box::new(discover_structs_pass::DiscoverStructsPass::new(symbol_table.clone(), result.clone())),
+            Box::new(check_struct_member_pass::CheckStructMemberPass::new(symbol_table.clone(), result.clone())),
+            Box::new(check_literals_pass::CheckLiteralsPass::new(symbol_table.clone(), result.clone())),
+        ]);
+
+        passes.execute(&mut ast);
+
+        (ast, symbol_table, result)
+    }
+
+    fn evaluate(code: &str) -> (SymbolTableReference, PassResultReference) {
+        let (_, symbol_table, result) = evaluate_ast(code);
+        (symbol_table, result)
+    }
+
+    fn type_errors(result: &PassResultReference) -> Vec<ErrorKind> {
+        result.borrow_mut().take_errors().into_iter().map(|e| e.downcast::<TypeError>().unwrap().get_kind().clone()).collect()
+    }
+
+    #[test]
+    fn fold_infix_expression() {
+        let (symbol_table, result) = evaluate("const LIGHT_COUNT: i32 = 4 * 2;");
+
+        assert!(!result.borrow().has_errors());
+        assert_eq!(symbol_table.borrow().find_constant_value("LIGHT_COUNT"), Some(ConstantValue::Int(8)));
+    }
+
+    #[test]
+    fn fold_references_and_casts() {
+        let (symbol_table, result) = evaluate("const MASK: u32 = BITS << 4u; const BITS: u32 = 0xF; const HALF: f32 = f32(LIGHTS) / 2.0; const LIGHTS: i32 = 5;");
+
+        assert!(!result.borrow().has_errors());
+        assert_eq!(symbol_table.borrow().find_constant_value("MASK"), Some(ConstantValue::UInt(0xF0)));
+        assert_eq!(symbol_table.borrow().find_constant_value("HALF"), Some(ConstantValue::Float(2.5)));
+    }
+
+    #[test]
+    fn cyclic_constants_produce_an_error() {
+        let (symbol_table, result) = evaluate("const A: i32 = B + 1; const B: i32 = A;");
+
+        assert!(result.borrow().has_errors());
+        assert_eq!(symbol_table.borrow().find_constant_value("A"), None);
+    }
+
+    #[test]
+    fn overflow_and_division_by_zero_produce_an_error() {
+        let (_, result) = evaluate("const A: i32 = 2147483647 + 1;");
+        assert!(result.borrow().has_errors());
+
+        let (_, result) = evaluate("const B: u32 = 1 / 0;");
+        assert!(result.borrow().has_errors());
+    }
+
+    #[test]
+    fn constant_without_initializer_produces_an_error() {
+        let (_, result) = evaluate("const mvp: f32;");
+
+        assert!(result.borrow().has_errors());
+    }
+
+    #[test]
+    fn mismatched_operand_types_produce_an_error() {
+        let (_, result) = evaluate("const A: i64 = 1 + 2i32;");
+
+        assert!(result.borrow().has_errors());
+    }
+
+    #[test]
+    fn fold_array_lengths() {
+        let (ast, symbol_table, result) = evaluate_ast("const MAX_LIGHTS: u32 = 4; struct Light { intensity: f32, } struct Lights { lights: [Light; MAX_LIGHTS], grid: [[f32; MAX_LIGHTS * 2u]; 3], }");
+
+        assert!(!result.borrow().has_errors());
+        let lights = match ast.last() {
+            Some(&ItemKind::Struct(ref lights)) => lights,
+            _ => panic!("expected struct"),
+        };
+        assert_eq!(lights.struct_member[0].struct_member_type_name.name, "[Light; 4]");
+        assert_eq!(lights.struct_member[1].struct_member_type_name.name, "[[f32; 8]; 3]");
+
+        let symbol_table = symbol_table.borrow();
+        let light_type = symbol_table.find_type_ref("Light").unwrap();
+        let member_type = lights.struct_member[0].struct_member_type.unwrap();
+        assert_eq!(symbol_table.find_type(member_type).unwrap().get_array(), Some((light_type, 4)));
+    }
+
+    #[test]
+    fn array_lengths_must_be_positive_constants() {
+        let (_, result) = evaluate("struct A { a: [f32; 0], }");
+        assert_eq!(type_errors(&result)[0], ErrorKind::InvalidArrayLength("[f32; 0]".to_string()));
+
+        let (_, result) = evaluate("const N: i32 = 2 - 3; struct A { a: [f32; N], }");
+        assert_eq!(type_errors(&result)[0], ErrorKind::InvalidArrayLength("[f32; N]".to_string()));
+
+        let (_, result) = evaluate("struct A { a: [f32; 1.5], }");
+        assert_eq!(type_errors(&result)[0], ErrorKind::InvalidArrayLength("[f32; 1.5]".to_string()));
+
+        let (_, result) = evaluate("struct A { a: [f32; count], }");
+        assert_eq!(type_errors(&result)[0], ErrorKind::NotConstant);
+    }
+}
@@ -1,27 +1,47 @@
+use ::std::collections::HashSet;
 use ::ast::Ast;
 use ::passes::{ Pass, PassCollection };
 use ::passes::results::PassResultReference;
 use ::type_system::symbol_table::SymbolTableReference;
 
 mod check_primitives_pass;
+mod check_builtins_pass;
+mod check_attributes_pass;
+mod bind_imports_pass;
 mod discover_structs_pass;
 mod check_struct_member_pass;
 mod check_exports_pass;
 mod check_function_signatures_pass;
+mod check_literals_pass;
+mod check_uniforms_pass;
+mod check_programs_pass;
+mod evaluate_constants_pass;
+mod resolve_names_pass;
+
+pub use self::check_uniforms_pass::find_program_variables;
 
 pub struct TypeChecker {
     passes: PassCollection<Ast>,
 }
 
 impl TypeChecker {
-    pub fn new(symbol_table: SymbolTableReference, result: PassResultReference) -> TypeChecker {
+    /// `failed_modules` are the paths of modules that failed to load or parse, items missing from them are not reported again
+    pub fn new(symbol_table: SymbolTableReference, result: PassResultReference, failed_modules: &HashSet<String>) -> TypeChecker {
         TypeChecker {
             passes: PassCollection::from_passes(vec![
                 Box::new(check_primitives_pass::CheckPrimitivesPass::new(symbol_table.clone(), result.clone())),
+                Box::new(check_builtins_pass::CheckBuiltinsPass::new(symbol_table.clone(), result.clone())),
+                Box::new(check_attributes_pass::CheckAttributesPass::new(result.clone())),
+                Box::new(bind_imports_pass::BindImportsPass::with_failed_modules(symbol_table.clone(), result.clone(), failed_modules.clone())),
+                Box::new(evaluate_constants_pass::EvaluateConstantsPass::new(symbol_table.clone(), result.clone())),
                 Box::new(discover_structs_pass::DiscoverStructsPass::new(symbol_table.clone(), result.clone())),
                 Box::new(check_struct_member_pass::CheckStructMemberPass::new(symbol_table.clone(), result.clone())),
                 Box::new(check_function_signatures_pass::CheckFunctionSignaturePass::new(symbol_table.clone(), result.clone())),
-                Box::new(check_exports_pass::CheckExportsPass::new(symbol_table.clone(), result.clone())),
+                Box::new(check_uniforms_pass::CheckUniformsPass::new(symbol_table.clone(), result.clone())),
+                Box::new(check_literals_pass::CheckLiteralsPass::new(symbol_table.clone(), result.clone())),
+                Box::new(check_programs_pass::CheckProgramsPass::new(symbol_table.clone(), result.clone())),
+                Box::new(resolve_names_pass::ResolveNamesPass::new(symbol_table.clone(), result.clone())),
+                Box::new(check_exports_pass::CheckExportsPass::with_failed_modules(symbol_table.clone(), result.clone(), failed_modules.clone())),
             ]),
         }
     }
@@ -0,0 +1,559 @@
+use ::std::collections::HashMap;
+use ::ast::*;
+use ::passes::*;
+use ::passes::ast::*;
+use ::passes::results::PassResultReference;
+use ::type_system::symbol_table::{ SymbolTableReference, SymbolKind };
+use ::type_system::type_environment::TypeReference;
+use ::type_system::error::{ TypeError, ErrorKind, TypeCheckResult };
+
+/// binds every variable use to the local, argument, constant, uniform or sampler it refers to
+/// samplers are declared here, the other globals are declared by earlier passes
+/// unknown names produce `VariableNotFound` with the closest visible name as suggestion
+/// calls resolve to the signature of a function of the module, an import or a builtin function of libcore
+/// stage functions additionally see the builtin inputs libcore declares for their stage kind
+pub struct ResolveNamesPass {
+    symbol_table: SymbolTableReference,
+    result: PassResultReference,
+    stage_functions: HashMap<String, StageKind>,
+}
+
+impl ResolveNamesPass {
+    pub fn new(symbol_table: SymbolTableReference, result: PassResultReference) -> ResolveNamesPass {
+        ResolveNamesPass {
+            symbol_table: symbol_table,
+            result: result,
+            stage_functions: HashMap::new(),
+        }
+    }
+}
+
+ast_pass_impl!(ResolveNamesPass, {
+    fn visit(&mut self, items: &mut Ast) {
+        for item in items.iter_mut() {
+            match *item {
+                ItemKind::Constant(ref mut constant) => {
+                    if constant.constant_variant == ConstantVariant::Sampler {
+                        self.declare_sampler(constant);
+                    }
+                },
+                ItemKind::Program(ref program) => {
+                    for stage in &program.program_stages {
+                        if let ProgramStageFunction::Reference(ref function_name) = stage.function {
+                            self.stage_functions.insert(function_name.name.to_owned(), stage.stage_kind);
+                        }
+                    }
+                },
+                _ => (),
+            }
+        }
+
+        for item in items.iter_mut() {
+            match *item {
+                ItemKind::Function(ref mut item) => self.visit_function(item),
+                ItemKind::Program(ref mut item) => self.visit_program(item),
+                _ => (),
+            }
+        }
+    }
+
+    fn visit_function(&mut self, function_declaration: &mut FunctionDeclaration) {
+        let stage_kind = self.stage_functions.get(&function_declaration.function_name.name).cloned();
+        if let Some(stage_kind) = stage_kind {
+            self.declare_builtins(stage_kind);
+        }
+
+        symbol_table_mut!(self).enter_scope();
+        self.walk_function(function_declaration);
+        symbol_table_mut!(self).leave_scope();
+
+        if stage_kind.is_some() {
+            symbol_table_mut!(self).leave_scope();
+        }
+    }
+
+    fn visit_program_stage(&mut self, program_stage_definition: &mut ProgramStageDefinition) {
+        self.declare_builtins(program_stage_definition.stage_kind);
+
+        symbol_table_mut!(self).enter_scope();
+        self.walk_program_stage(program_stage_definition);
+        symbol_table_mut!(self).leave_scope();
+
+        symbol_table_mut!(self).leave_scope();
+    }
+
+    fn visit_function_argument(&mut self, function_argument: &mut FunctionArgumentDeclaration) {
+        let argument_type = match function_argument.argument_type {
+            Some(t) => Some(t),
+            None => symbol_table!(self).find_type_ref(&function_argument.argument_type_name.name),
+        };
+
+        pass_try!(self, self.declare(&function_argument.argument_name, argument_type, SymbolKind::Argument));
+    }
+
+    fn visit_local_statement(&mut self, local_statement: &mut LocalDeclaration) {
+        self.walk_local_statement(local_statement);
+
+        pass_try!(self, self.declare(&local_statement.symbol_name, local_statement.local_type, SymbolKind::Local));
+    }
+
+    fn visit_variable_expression(&mut self, variable_expression: &mut VariableExpression) {
+        let (kind, variable_type) = pass_try!(self, self.resolve(&variable_expression.variable_name));
+
+        variable_expression.variable_kind = Some(kind);
+        variable_expression.variable_type = variable_type;
+    }
+
+    fn visit_field_accessor_expression(&mut self, field_accessor_expression: &mut FieldAccessorExpression) {
+        let (kind, variable_type) = pass_try!(self, self.resolve(&field_accessor_expression.variable_name));
+
+        field_accessor_expression.variable_kind = Some(kind);
+        field_accessor_expression.field_type = pass_try!(self, self.resolve_fields(variable_type, &field_accessor_expression.field_names));
+    }
+
+    fn visit_call_expression(&mut self, call_expression: &mut CallExpression) {
+        self.walk_call_expression(call_expression);
+
+        // items missing from failed modules are bound without a type and are not reported again
+        let function_type = symbol_table!(self).find_type_ref(&call_expression.function_name.name);
+        match function_type {
+            Some(function_type) => call_expression.function_type = Some(function_type),
+            None => pass_try!(self, Err(TypeError::new(call_expression.function_name.span, ErrorKind::FunctionNotFound(call_expression.function_name.name.to_owned())))),
+        }
+    }
+
+    fn visit_infix_expression(&mut self, infix_expression: &mut InfixExpression) {
+        self.walk_infix_expression(infix_expression);
+
+        let operand_types = (self.expression_type(&infix_expression.left_hand), self.expression_type(&infix_expression.right_hand));
+        if let (Some(left_hand), Some(right_hand)) = operand_types {
+            infix_expression.infix_type = symbol_table!(self).find_type(left_hand).and_then(|t| t.find_operator_type(infix_expression.operator, right_hand));
+        }
+    }
+
+    fn visit_struct_instantiation_expression(&mut self, struct_instantiation_expression: &mut StructInstantiationExpression) {
+        self.walk_struct_instantiation_expression(struct_instantiation_expression);
+
+        let struct_type = {
+            let struct_type_name = &struct_instantiation_expression.struct_type_name;
+            let struct_type = match symbol_table!(self).find_type_ref(&struct_type_name.name) {
+                Some(t) => t,
+                None => pass_try!(self, Err(TypeError::new(struct_type_name.span, ErrorKind::TypeNotFound(struct_type_name.name.to_owned())))),
+            };
+
+            if !symbol_table!(self).find_type(struct_type).map(|t| t.is_struct()).unwrap_or(false) {
+                pass_try!(self, Err(TypeError::new(struct_type_name.span, ErrorKind::TypeHasNoMember)));
+            }
+            struct_type
+        };
+
+        struct_instantiation_expression.struct_type = Some(struct_type);
+        self.check_struct_initializers(struct_instantiation_expression, struct_type);
+    }
+
+    fn visit_index_accessor_expression(&mut self, index_accessor_expression: &mut IndexAccesorExpression) {
+        let (kind, variable_type) = pass_try!(self, self.resolve(&index_accessor_expression.variable_name));
+
+        index_accessor_expression.variable_kind = Some(kind);
+        self.walk_index_accessor_expression(index_accessor_expression);
+
+        // indexing into other types than arrays, like vectors, stays untyped
+        let indexed_type = pass_try!(self, self.resolve_fields(variable_type, &index_accessor_expression.field_names));
+        let element_type = indexed_type.and_then(|t| symbol_table!(self).find_type(t).and_then(|t| t.get_array())).map(|(element_type, _)| element_type);
+        index_accessor_expression.accessor_type = pass_try!(self, self.resolve_fields(element_type, &index_accessor_expression.element_field_names));
+    }
+});
+
+impl ResolveNamesPass {
+    fn declare_sampler(&mut self, sampler: &mut ConstantDefinition) {
+        let sampler_type = match symbol_table!(self).find_type_ref(&sampler.constant_type_name.name) {
+            Some(t) => t,
+            None => pass_try!(self, Err(TypeError::new(sampler.constant_type_name.span, ErrorKind::TypeNotFound(sampler.constant_type_name.name.to_owned())))),
+        };
+
+        pass_try!(self, self.declare(&sampler.constant_name, Some(sampler_type), SymbolKind::Sampler));
+        sampler.constant_type = Some(sampler_type);
+    }
+
+    /// enters a scope holding the builtin inputs of the stage kind
+    /// arguments and locals live in a nested scope and may shadow them
+    fn declare_builtins(&mut self, stage_kind: StageKind) {
+        let mut symbol_table = symbol_table_mut!(self);
+        let inputs: Vec<(String, TypeReference)> = symbol_table.find_stage_inputs(stage_kind).iter().map(|b| (b.name.to_owned(), b.builtin_type)).collect();
+
+        symbol_table.enter_scope();
+        for (name, builtin_type) in inputs {
+            // the scope is fresh and builtin names are unique, this cannot collide
+            let _ = symbol_table.add_symbol_with_type(&name, builtin_type, SymbolKind::Builtin);
+        }
+    }
+
+    fn add_error(&mut self, error: TypeError) {
+        self.result.borrow_mut().add_error(Box::new(error));
+    }
+
+    fn type_name(&self, type_ref: TypeReference) -> String {
+        symbol_table!(self).find_type(type_ref).map(|t| t.get_name().to_owned()).unwrap_or_default()
+    }
+
+    /// type of an expression resolved by this or earlier passes, `None` where it is not known
+    fn expression_type(&self, expression: &ExpressionStatement) -> Option<TypeReference> {
+        match *expression {
+            ExpressionStatement::Infix(ref e) => e.infix_type,
+            ExpressionStatement::Literal(ref e) => e.literal_type,
+            ExpressionStatement::Call(ref e) => {
+                let symbol_table = symbol_table!(self);
+                e.function_type
+                    .and_then(|t| symbol_table.find_type(t))
+                    .and_then(|t| t.get_call_signature())
+                    .and_then(|s| s.get_return_type())
+            },
+            ExpressionStatement::StructInstantiation(ref e) => e.struct_type,
+            ExpressionStatement::FieldAccessor(ref e) => e.field_type,
+            ExpressionStatement::IndexAccessor(ref e) => e.accessor_type,
+            ExpressionStatement::Variable(ref e) => e.variable_type,
+        }
+    }
+
+    /// whether a value of type `from` can initialize a value of type `to`, implicit casts included
+    fn is_initializable(&self, from: TypeReference, to: TypeReference) -> bool {
+        let symbol_table = symbol_table!(self);
+        symbol_table.is_assignable(from, to) || symbol_table.find_type(from).map(|t| t.does_implicit_cast_exist(to)).unwrap_or(false)
+    }
+
+    /// every field is initialized once, by name of a member and with a matching type
+    /// fields without initializer are only allowed with a `..base` of the same structure
+    fn check_struct_initializers(&mut self, struct_instantiation_expression: &mut StructInstantiationExpression, struct_type: TypeReference) {
+        let struct_name = struct_instantiation_expression.struct_type_name.name.to_owned();
+        let members: Vec<(String, TypeReference)> = match symbol_table!(self).find_type(struct_type).and_then(|t| t.get_member()) {
+            Some(members) => members.get_members().iter().map(|m| (m.member_name.to_owned(), m.member_type)).collect(),
+            None => return,
+        };
+        let mut initialized: Vec<String> = Vec::new();
+
+        for initializer in struct_instantiation_expression.struct_field_initializer.iter_mut() {
+            let field_name = initializer.struct_field_name.name.to_owned();
+            let field_span = initializer.struct_field_name.span;
+
+            if initialized.contains(&field_name) {
+                self.add_error(TypeError::new(field_span, ErrorKind::DuplicateStructField(struct_name.to_owned(), field_name)));
+                continue;
+            }
+            initialized.push(field_name.to_owned());
+
+            let member_type = match members.iter().find(|m| m.0 == field_name) {
+                Some(&(_, t)) => t,
+                None => {
+                    let candidates = members.iter().map(|m| m.0.to_owned()).collect();
+                    self.add_error(TypeError::new(field_span, ErrorKind::UnknownStructField(struct_name.to_owned(), field_name, candidates)));
+                    continue;
+                },
+            };
+            initializer.struct_field_type = Some(member_type);
+
+            if let Some(initializer_type) = self.expression_type(&initializer.initializer) {
+                if !self.is_initializable(initializer_type, member_type) {
+                    let error = ErrorKind::StructFieldTypeMismatch(field_name, self.type_name(member_type), self.type_name(initializer_type));
+                    self.add_error(TypeError::new(initializer.span, error));
+                }
+            }
+        }
+
+        match struct_instantiation_expression.base {
+            Some(ref base) => {
+                if let Some(base_type) = self.expression_type(base) {
+                    if !self.is_initializable(base_type, struct_type) {
+                        self.add_error(TypeError::new(base.get_span(), ErrorKind::IncompatibleTypes(struct_instantiation_expression.struct_type_name.span, base.get_span())));
+                    }
+                }
+            },
+            None => {
+                for &(ref member_name, _) in members.iter().filter(|m| !initialized.contains(&m.0)) {
+                    let error = ErrorKind::MissingStructField(struct_name.to_owned(), member_name.to_owned());
+                    self.add_error(TypeError::new(struct_instantiation_expression.struct_type_name.span, error));
+                }
+            },
+        }
+    }
+
+    /// type of the innermost field of an access chain like `a.b.c`
+    /// fields of structures must exist, access into other types like vector swizzles stays untyped
+    fn resolve_fields(&self, variable_type: Option<TypeReference>, field_names: &[Identifier]) -> TypeCheckResult<Option<TypeReference>> {
+        let symbol_table = symbol_table!(self);
+        let mut current_type = variable_type;
+
+        for field_name in field_names {
+            let members = match current_type.and_then(|t| symbol_table.find_type(t)).and_then(|t| t.get_member()) {
+                Some(m) => m,
+                None => return Ok(None),
+            };
+
+            current_type = match members.find_member_type(&field_name.name) {
+                Some(t) => Some(t),
+                None => return Err(TypeError::new(field_name.span, ErrorKind::MemberNotFound)),
+            };
+        }
+
+        Ok(current_type)
+    }
+
+    fn declare(&self, name: &Identifier, symbol_type: Option<TypeReference>, kind: SymbolKind) -> TypeCheckResult<()> {
+        let mut symbol_table = symbol_table_mut!(self);
+        let declared = match symbol_type {
+            Some(t) => symbol_table.add_symbol_with_type(&name.name, t, kind),
+            None => symbol_table.add_symbol(&name.name, kind),
+        };
+
+        declared.map_err(|_| TypeError::new(name.span, ErrorKind::SymbolNameAlreadyUsed(name.name.to_owned())))
+    }
+
+    fn resolve(&self, name: &Identifier) -> TypeCheckResult<(SymbolKind, Option<TypeReference>)> {
+        let mut symbol_table = symbol_table_mut!(self);
+
+        if let Some(symbol) = symbol_table.find_symbol(&name.name) {
+            return Ok((symbol.kind, symbol.get_type()));
+        }
+
+        let suggestion = symbol_table.find_similar_symbol(&name.name).map(|s| s.to_owned());
+        Err(TypeError::new(name.span, ErrorKind::VariableNotFound(name.name.to_owned(), suggestion)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::testing::compile_ast;
+    use ::passes::results::PassResult;
+    use ::type_system::symbol_table::SymbolTable;
+    use ::type_system::type_environment::TypeEnvironment;
+    use ::passes::ast::type_checking::check_primitives_pass;
+    use ::passes::ast::type_checking::check_builtins_pass;
+    use ::passes::ast::type_checking::discover_structs_pass;
+    use ::passes::ast::type_checking::check_struct_member_pass;
+    use ::passes::ast::type_checking::check_function_signatures_pass;
+    use ::passes::ast::type_checking::check_uniforms_pass;
+
+    fn resolve(code: &str) -> (Ast, PassResultReference) {
+        let mut ast = compile_ast(&format!("primitive type u32; primitive type f32; primitive type vec4; primitive type Sampler2d; builtin vertex_index: u32 in(vertex); {}", code));
+        let symbol_table = SymbolTableReference::new(SymbolTable::new(TypeEnvironment::new()));
+        let result = PassResultReference::new(PassResult::new());
+
+        let mut passes = PassCollection::from_passes(vec![
+            Box::new(check_primitives_pass::CheckPrimitivesPass::new(symbol_table.clone(), result.clone())),
+            Box::new(check_builtins_pass::CheckBuiltinsPass::new(symbol_table.clone(), result.clone())),
+            Box::new(discover_structs_pass::DiscoverStructsPass::new(symbol_table.clone(), result.clone())),
+            Box::new(check_struct_member_pass::CheckStructMemberPass::new(symbol_table.clone(), result.clone())),
+            Box::new(check_function_signatures_pass::CheckFunctionSignaturePass::new(symbol_table.clone(), result.clone())),
+            Box::new(check_uniforms_pass::CheckUniformsPass::new(symbol_table.clone(), result.clone())),
+            Box::new(ResolveNamesPass::new(symbol_table.clone(), result.clone())),
+        ]);
+
+        passes.execute(&mut ast);
+
+        (ast, result)
+    }
+
+    fn return_expression(ast: &Ast) -> &ExpressionStatement {
+        for item in ast.iter() {
+            if let ItemKind::Function(ref function) = *item {
+                for statement in &function.block.statements {
+                    if let BlockStatement::Return(ref statement) = *statement {
+                        return &statement.expression;
+                    }
+                }
+            }
+        }
+        panic!("no return statement found");
+    }
+
+    #[test]
+    fn resolve_arguments_and_locals() {
+        let (ast, result) = resolve("fn test(input: f32) -> f32 { let doubled = input * input; return doubled; }");
+
+        assert!(!result.borrow().has_errors());
+        match *return_expression(&ast) {
+            ExpressionStatement::Variable(ref v) => assert_eq!(v.variable_kind, Some(SymbolKind::Local)),
+            _ => panic!("expected variable"),
+        }
+    }
+
+    #[test]
+    fn resolve_uniform_block_member() {
+        let (ast, result) = resolve("uniform Light { color: vec4, } fn test() -> vec4 { return Light.color; } program Test { stage fragment() -> vec4 { return test(); } }");
+
+        assert!(!result.borrow().has_errors());
+        match *return_expression(&ast) {
+            ExpressionStatement::FieldAccessor(ref f) => {
+                assert_eq!(f.variable_kind, Some(SymbolKind::Uniform));
+                assert!(f.field_type.is_some());
+            },
+            _ => panic!("expected field accessor"),
+        }
+    }
+
+    #[test]
+    fn resolve_nested_fields() {
+        let (ast, result) = resolve("struct Light { color: vec4, } struct Scene { light: Light, } fn test(scene: Scene) -> vec4 { return scene.light.color; }");
+
+        assert!(!result.borrow().has_errors());
+        match *return_expression(&ast) {
+            ExpressionStatement::FieldAccessor(ref f) => assert!(f.field_type.is_some()),
+            _ => panic!("expected field accessor"),
+        }
+
+        let (_, result) = resolve("struct Light { color: vec4, } struct Scene { light: Light, } fn test(scene: Scene) -> vec4 { return scene.light.colour; }");
+        assert!(result.borrow().has_errors());
+
+        let (_, result) = resolve("fn test(position: vec4) -> f32 { return position.x; }");
+        assert!(!result.borrow().has_errors());
+    }
+
+    #[test]
+    fn resolve_indexed_fields() {
+        let scene = "struct Light { color: vec4, } struct Scene { lights: [Light; 4], }";
+        let (ast, result) = resolve(&format!("{} fn test(scene: Scene) -> vec4 {{ return scene.lights[1].color; }}", scene));
+
+        assert!(!result.borrow().has_errors());
+        match *return_expression(&ast) {
+            ExpressionStatement::IndexAccessor(ref e) => {
+                assert_eq!(e.variable_kind, Some(SymbolKind::Argument));
+                assert!(e.accessor_type.is_some());
+            },
+            _ => panic!("expected index accessor"),
+        }
+
+        let (_, result) = resolve(&format!("{} fn test(scene: Scene) -> Light {{ let light = scene.lights[0]; return Light {{ color: light.color, }}; }}", scene));
+        assert!(!result.borrow().has_errors());
+
+        let errors = resolve(&format!("{} fn test(scene: Scene) -> vec4 {{ return scene.lights[1].colour; }}", scene)).1.borrow_mut().take_errors();
+        assert_eq!(errors[0].downcast_ref::<TypeError>().unwrap().get_kind(), &ErrorKind::MemberNotFound);
+    }
+
+    #[test]
+    fn resolve_nested_struct_instantiation() {
+        let (_, result) = resolve("struct Light { color: vec4, } struct Scene { light: Light, } fn test(color: vec4) -> Scene { return Scene { light: Light { color: color, }, }; }");
+        assert!(!result.borrow().has_errors());
+
+        let (_, result) = resolve("struct Scene { color: vec4, } fn test(color: vec4) -> Scene { return Scene { light: Lihgt { color: color, }, }; }");
+        assert!(result.borrow().has_errors());
+    }
+
+    fn instantiation_errors(instantiation: &str) -> Vec<ErrorKind> {
+        let code = format!("operator + (lhs: f32, rhs: f32) -> f32; operator * (lhs: vec4, rhs: f32) -> vec4; struct Light {{ color: vec4, intensity: f32, }} fn test(color: vec4, intensity: f32, base: Light) -> Light {{ return {}; }}", instantiation);
+        let (_, result) = resolve(&code);
+        let errors = result.borrow_mut().take_errors();
+        errors.into_iter().map(|e| e.downcast::<TypeError>().unwrap().get_kind().clone()).collect()
+    }
+
+    #[test]
+    fn struct_instantiations_are_complete() {
+        assert_eq!(instantiation_errors("Light { color: color, intensity: intensity, }"), vec![]);
+        assert_eq!(instantiation_errors("Light { color: color, }"), vec![
+            ErrorKind::MissingStructField("Light".to_string(), "intensity".to_string()),
+        ]);
+        assert_eq!(instantiation_errors("Light { color: color, intensity: intensity, colour: color, }"), vec![
+            ErrorKind::UnknownStructField("Light".to_string(), "colour".to_string(), vec!["color".to_string(), "intensity".to_string()]),
+        ]);
+        assert_eq!(instantiation_errors("Light { color: color, color: color, intensity: intensity, }"), vec![
+            ErrorKind::DuplicateStructField("Light".to_string(), "color".to_string()),
+        ]);
+        assert_eq!(instantiation_errors("Light { color: intensity, intensity: intensity, }"), vec![
+            ErrorKind::StructFieldTypeMismatch("color".to_string(), "vec4".to_string(), "f32".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn infix_initializers_have_the_operator_result_type() {
+        assert_eq!(instantiation_errors("Light { color: color * intensity, intensity: intensity + intensity, }"), vec![]);
+        assert_eq!(instantiation_errors("Light { color: intensity + intensity, intensity: intensity, }"), vec![
+            ErrorKind::StructFieldTypeMismatch("color".to_string(), "vec4".to_string(), "f32".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn struct_instantiation_errors_point_at_the_field() {
+        let (_, result) = resolve("struct Light { color: vec4, } fn test(color: vec4) -> Light { return Light { color: color, color: color, }; }");
+        let errors = result.borrow_mut().take_errors();
+        let error = errors.into_iter().next().unwrap().downcast::<TypeError>().unwrap();
+
+        assert_eq!(error.get_span(), Span::new(216, 5, 1, 217));
+    }
+
+    #[test]
+    fn struct_instantiation_with_base() {
+        assert_eq!(instantiation_errors("Light { color: color, ..base }"), vec![]);
+        assert_eq!(instantiation_errors("Light { ..base }"), vec![]);
+        assert_eq!(instantiation_errors("Light { color: color, ..intensity }").len(), 1);
+    }
+
+    #[test]
+    fn resolve_samplers_in_stages() {
+        let (_, result) = resolve("builtin fn sample(texture: Sampler2d, uv: f32) -> f32; sampler albedo: Sampler2d; program Test { stage fragment(uv: f32) -> f32 { return sample(albedo, uv); } }");
+
+        assert!(!result.borrow().has_errors());
+    }
+
+    #[test]
+    fn resolve_stage_builtins() {
+        let (ast, result) = resolve("fn vertex_main() -> u32 { return vertex_index; } program Test { vertex: vertex_main, }");
+
+        assert!(!result.borrow().has_errors());
+        match *return_expression(&ast) {
+            ExpressionStatement::Variable(ref v) => {
+                assert_eq!(v.variable_kind, Some(SymbolKind::Builtin));
+                assert!(v.variable_type.is_some());
+            },
+            _ => panic!("expected variable"),
+        }
+
+        let (_, result) = resolve("fn helper() -> u32 { return vertex_index; }");
+        assert!(result.borrow().has_errors());
+
+        let (_, result) = resolve("program Test { stage vertex(vertex_index: f32) -> f32 { return vertex_index; } }");
+        assert!(!result.borrow().has_errors());
+    }
+
+    fn unknown_variable_error(code: &str) -> TypeError {
+        let (_, result) = resolve(code);
+        let mut errors = result.borrow_mut().take_errors();
+        assert_eq!(errors.len(), 1);
+        *errors.remove(0).downcast::<TypeError>().unwrap()
+    }
+
+    #[test]
+    fn unknown_variables_suggest_a_similar_name() {
+        let error = unknown_variable_error("fn test(input: f32) -> f32 { return intput; }");
+
+        assert_eq!(error.get_kind(), &ErrorKind::VariableNotFound("intput".to_string(), Some("input".to_string())));
+        assert_eq!(error.to_string(), "Unknown variable \"intput\", did you mean \"input\"?");
+    }
+
+    #[test]
+    fn unknown_variables_without_similar_name() {
+        let error = unknown_variable_error("fn test(input: f32) -> f32 { return brightness; }");
+
+        assert_eq!(error.get_kind(), &ErrorKind::VariableNotFound("brightness".to_string(), None));
+    }
+
+    #[test]
+    fn resolve_calls() {
+        let (ast, result) = resolve("builtin fn max(a: f32, b: f32) -> f32; fn test(input: f32) -> f32 { return max(input, 0.0); }");
+
+        assert!(!result.borrow().has_errors());
+        match *return_expression(&ast) {
+            ExpressionStatement::Call(ref e) => assert!(e.function_type.is_some()),
+            _ => panic!("expected a call"),
+        }
+
+        let errors = resolve("fn test(input: f32) -> f32 { return maximum(input, 0.0); }").1.borrow_mut().take_errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].downcast_ref::<TypeError>().unwrap().get_kind(), &ErrorKind::FunctionNotFound("maximum".to_string()));
+    }
+
+    #[test]
+    fn locals_are_scoped_to_their_function() {
+        let (_, result) = resolve("fn a() -> f32 { let x = 1.0; return x; } fn b() -> f32 { return x; }");
+
+        assert!(result.borrow().has_errors());
+    }
+}
@@ -28,6 +28,10 @@ impl PassResult {
         self.errors.len() > 0
     }
 
+    pub fn take_errors(&mut self) -> Vec<Box<Error>> {
+        ::std::mem::replace(&mut self.errors, Vec::new())
+    }
+
     pub fn add_error(&mut self, error: Box<Error>) {
         self.errors.push(error);
     }
@@ -0,0 +1,262 @@
+use ::std::collections::HashMap;
+use ::std::error::Error;
+use ::std::fmt;
+use ::std::fs::File;
+use ::std::io::{ self, Read };
+use ::std::path::PathBuf;
+use ::module_path;
+
+/// a module found by a `ModuleResolver`
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ResolvedModule {
+    /// canonical path of the module, modules are loaded once per canonical path
+    pub path: String,
+    pub source: String,
+}
+
+impl ResolvedModule {
+    pub fn new(path: &str, source: &str) -> ResolvedModule {
+        ResolvedModule {
+            path: path.to_owned(),
+            source: source.to_owned(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ResolveError {
+    NotFound(String /* Module path */),
+    Io(String /* Module path */, io::Error),
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ResolveError::NotFound(ref module_path) => write!(f, "Module `{}` not found.", module_path),
+            ResolveError::Io(ref module_path, ref error) => write!(f, "Module `{}` could not be read: {}", module_path, error),
+        }
+    }
+}
+
+impl Error for ResolveError {
+    fn description(&self) -> &str {
+        match *self {
+            ResolveError::NotFound(_) => "Module not found",
+            ResolveError::Io(_, _) => "Module could not be read",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match *self {
+            ResolveError::NotFound(_) => None,
+            ResolveError::Io(_, ref error) => Some(error),
+        }
+    }
+}
+
+pub trait ModuleResolver {
+    /// canonical path and source of the module at `module_path`
+    fn resolve(&mut self, module_path: &str) -> Result<ResolvedModule, ResolveError>;
+
+    /// canonical path of the module at `module_path` without reading it
+    /// imports are found by their canonical path first, each module is only resolved once
+    fn canonicalize(&mut self, module_path: &str) -> Result<String, ResolveError>;
+}
+
+/// modules held in memory, e.g. for tests or unsaved editor buffers
+pub struct MemoryResolver {
+    modules: HashMap<String, String>,
+}
+
+impl MemoryResolver {
+    pub fn new() -> MemoryResolver {
+        MemoryResolver {
+            modules: HashMap::new(),
+        }
+    }
+
+    /// adds or replaces the module at `module_path`
+    pub fn insert(&mut self, module_path: &str, source: &str) {
+        self.modules.insert(module_path::join("", module_path), source.to_owned());
+    }
+
+    pub fn remove(&mut self, module_path: &str) -> Option<String> {
+        self.modules.remove(&module_path::join("", module_path))
+    }
+}
+
+impl ModuleResolver for MemoryResolver {
+    fn resolve(&mut self, module_path: &str) -> Result<ResolvedModule, ResolveError> {
+        let path = module_path::join("", module_path);
+        match self.modules.get(&path) {
+            Some(source) => Ok(ResolvedModule::new(&path, source)),
+            None => Err(ResolveError::NotFound(module_path.to_owned())),
+        }
+    }
+
+    fn canonicalize(&mut self, module_path: &str) -> Result<String, ResolveError> {
+        let path = module_path::join("", module_path);
+        if self.modules.contains_key(&path) {
+            Ok(path)
+        } else {
+            Err(ResolveError::NotFound(module_path.to_owned()))
+        }
+    }
+}
+
+/// modules read from files below a root directory, module paths are relative to the root
+/// paths leaving the root are not found
+pub struct DirectoryResolver {
+    root: PathBuf,
+}
+
+impl DirectoryResolver {
+    pub fn new<P: Into<PathBuf>>(root: P) -> DirectoryResolver {
+        DirectoryResolver {
+            root: root.into(),
+        }
+    }
+
+    fn find_file(&self, module_path: &str) -> Option<(String, PathBuf)> {
+        let path = module_path::join("", module_path);
+        if path.is_empty() || path.starts_with('/') || path.starts_with("..") {
+            return None;
+        }
+
+        let file = self.root.join(&path);
+        if file.is_file() {
+            Some((path, file))
+        } else {
+            None
+        }
+    }
+}
+
+impl ModuleResolver for DirectoryResolver {
+    fn resolve(&mut self, module_path: &str) -> Result<ResolvedModule, ResolveError> {
+        let (path, file) = match self.find_file(module_path) {
+            Some(found) => found,
+            None => return Err(ResolveError::NotFound(module_path.to_owned())),
+        };
+
+        let mut source = String::new();
+        let read = File::open(&file).and_then(|mut f| f.read_to_string(&mut source));
+        match read {
+            Ok(_) => Ok(ResolvedModule { path: path, source: source }),
+            Err(error) => Err(ResolveError::Io(path, error)),
+        }
+    }
+
+    fn canonicalize(&mut self, module_path: &str) -> Result<String, ResolveError> {
+        match self.find_file(module_path) {
+            Some((path, _)) => Ok(path),
+            None => Err(ResolveError::NotFound(module_path.to_owned())),
+        }
+    }
+}
+
+/// stacks resolvers, a module is taken from the most recently added resolver that has it
+/// e.g. a `MemoryResolver` with unsaved editor buffers over a `DirectoryResolver`
+pub struct OverlayResolver {
+    layers: Vec<Box<ModuleResolver>>,
+}
+
+impl OverlayResolver {
+    pub fn new(base: Box<ModuleResolver>) -> OverlayResolver {
+        OverlayResolver {
+            layers: vec![base],
+        }
+    }
+
+    /// adds `overlay` on top of the resolvers added before
+    pub fn add_overlay(&mut self, overlay: Box<ModuleResolver>) {
+        self.layers.push(overlay);
+    }
+}
+
+impl ModuleResolver for OverlayResolver {
+    fn resolve(&mut self, module_path: &str) -> Result<ResolvedModule, ResolveError> {
+        for layer in self.layers.iter_mut().rev() {
+            match layer.resolve(module_path) {
+                Err(ResolveError::NotFound(_)) => continue,
+                result => return result,
+            }
+        }
+        Err(ResolveError::NotFound(module_path.to_owned()))
+    }
+
+    fn canonicalize(&mut self, module_path: &str) -> Result<String, ResolveError> {
+        for layer in self.layers.iter_mut().rev() {
+            match layer.canonicalize(module_path) {
+                Err(ResolveError::NotFound(_)) => continue,
+                result => return result,
+            }
+        }
+        Err(ResolveError::NotFound(module_path.to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::std::env;
+    use ::std::fs;
+    use super::*;
+
+    fn not_found(result: Result<ResolvedModule, ResolveError>) -> bool {
+        match result {
+            Err(ResolveError::NotFound(_)) => true,
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn resolve_memory_modules() {
+        let mut resolver = MemoryResolver::new();
+        resolver.insert("./shaders/a.xs", "const A: f32 = 1.0;");
+
+        assert_eq!(resolver.resolve("shaders/lib/../a.xs").unwrap(), ResolvedModule::new("shaders/a.xs", "const A: f32 = 1.0;"));
+        assert_eq!(resolver.canonicalize("./shaders/a.xs").unwrap(), "shaders/a.xs");
+        assert!(not_found(resolver.resolve("a.xs")));
+
+        assert_eq!(resolver.remove("shaders/a.xs"), Some("const A: f32 = 1.0;".to_string()));
+        assert!(not_found(resolver.resolve("shaders/a.xs")));
+    }
+
+    #[test]
+    fn resolve_directory_modules() {
+        let root = env::temp_dir().join(format!("xshade_resolver_{}", ::std::process::id()));
+        fs::create_dir_all(root.join("shaders")).unwrap();
+        fs::write(root.join("shaders/a.xs"), "const A: f32 = 1.0;").unwrap();
+
+        let mut resolver = DirectoryResolver::new(root.join("shaders"));
+        let resolved = resolver.resolve("./lib/../a.xs");
+        let canonical = resolver.canonicalize("a.xs");
+        let missing = resolver.resolve("b.xs");
+        let outside = resolver.resolve("../shaders/a.xs");
+        let directory = DirectoryResolver::new(&root).resolve("shaders");
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(resolved.unwrap(), ResolvedModule::new("a.xs", "const A: f32 = 1.0;"));
+        assert_eq!(canonical.unwrap(), "a.xs");
+        assert!(not_found(missing));
+        assert!(not_found(outside));
+        assert!(not_found(directory));
+    }
+
+    #[test]
+    fn resolve_overlay_modules() {
+        let mut base = MemoryResolver::new();
+        base.insert("a.xs", "saved");
+        base.insert("b.xs", "saved");
+        let mut buffers = MemoryResolver::new();
+        buffers.insert("a.xs", "unsaved");
+
+        let mut resolver = OverlayResolver::new(Box::new(base));
+        resolver.add_overlay(Box::new(buffers));
+
+        assert_eq!(resolver.resolve("a.xs").unwrap().source, "unsaved");
+        assert_eq!(resolver.resolve("b.xs").unwrap().source, "saved");
+        assert_eq!(resolver.canonicalize("./b.xs").unwrap(), "b.xs");
+        assert!(not_found(resolver.resolve("c.xs")));
+    }
+}
@@ -0,0 +1,33 @@
+use ::ast::StageKind;
+use ::type_system::type_environment::TypeReference;
+
+/// a system value provided or consumed by the pipeline, e.g. `vertex_index`
+/// the set of builtins is declared in libcore with `builtin name: type in(...) out(...);`
+#[derive(Debug, Eq, PartialEq)]
+pub struct StageBuiltin {
+    pub name: String,
+    pub builtin_type: TypeReference,
+    pub input_stages: Vec<StageKind>,
+    pub output_stages: Vec<StageKind>,
+}
+
+impl StageBuiltin {
+    pub fn new(name: &str, builtin_type: TypeReference, input_stages: Vec<StageKind>, output_stages: Vec<StageKind>) -> StageBuiltin {
+        StageBuiltin {
+            name: name.to_owned(),
+            builtin_type: builtin_type,
+            input_stages: input_stages,
+            output_stages: output_stages,
+        }
+    }
+
+    /// the pipeline provides the builtin to stages of this kind
+    pub fn is_input_of(&self, stage_kind: StageKind) -> bool {
+        self.input_stages.contains(&stage_kind)
+    }
+
+    /// stages of this kind may write the builtin
+    pub fn is_output_of(&self, stage_kind: StageKind) -> bool {
+        self.output_stages.contains(&stage_kind)
+    }
+}
@@ -16,6 +16,10 @@ impl CallSignature {
         }
     }
 
+    pub fn get_arguments(&self) -> &Vec<TypeReference> {
+        &self.arguments
+    }
+
     pub fn match_arguments(&self, arguments: Vec<TypeReference>) -> bool {
         self.arguments == arguments
     }
@@ -0,0 +1,255 @@
+use ::std::f32;
+use ::ast::{ Span, Operator, LiteralExpression, LiteralType };
+use ::type_system::error::{ TypeError, ErrorKind, TypeCheckResult };
+use ::type_system::literals;
+
+/// a value known at compile time
+/// signed integers are stored as `Int`, unsigned integers as `UInt`
+#[derive(Debug, Copy, Clone)]
+pub enum ConstantValue {
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+}
+
+impl PartialEq for ConstantValue {
+    fn eq(&self, other: &ConstantValue) -> bool {
+        match (*self, *other) {
+            (ConstantValue::Bool(a), ConstantValue::Bool(b)) => a == b,
+            (ConstantValue::Int(a), ConstantValue::Int(b)) => a == b,
+            (ConstantValue::UInt(a), ConstantValue::UInt(b)) => a == b,
+            (ConstantValue::Float(a), ConstantValue::Float(b)) => a.to_bits() == b.to_bits(),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for ConstantValue {}
+
+fn overflow(span: Span, type_name: &str) -> TypeError {
+    TypeError::new(span, ErrorKind::ConstantOverflow(type_name.to_owned()))
+}
+
+fn bit_width(type_name: &str) -> u32 {
+    match type_name {
+        "i32" | "u32" | "f32" => 32,
+        _ => 64,
+    }
+}
+
+/// `a << b` as the product `a * 2^b`, so shifting out a set bit overflows like a multiplication
+fn checked_shift_left(a: i128, b: i128, type_name: &str) -> Option<i128> {
+    if b < 0 || b >= bit_width(type_name) as i128 {
+        None
+    } else {
+        Some(a << b)
+    }
+}
+
+fn checked_shift_right(a: i128, b: i128, type_name: &str) -> Option<i128> {
+    if b < 0 || b >= bit_width(type_name) as i128 {
+        None
+    } else {
+        Some(a >> b)
+    }
+}
+
+impl ConstantValue {
+    pub fn from_literal(literal: &LiteralExpression, type_name: &str) -> TypeCheckResult<ConstantValue> {
+        let out_of_range = || TypeError::new(literal.span, ErrorKind::LiteralOutOfRange(literal.value.to_owned(), type_name.to_owned()));
+
+        try!(literals::check_literal_range(literal, type_name));
+
+        match literal.literal_expression_type {
+            LiteralType::Bool => Ok(ConstantValue::Bool(literal.value == "true")),
+            LiteralType::Int => {
+                let (negative, magnitude) = literals::split_sign(&literal.value);
+                let value = try!(literals::parse_integer_literal(magnitude).ok_or_else(out_of_range));
+                if negative {
+                    // the range check allows at most the magnitude of `i64::min_value()`
+                    ConstantValue::Int((value as i64).wrapping_neg()).cast(type_name, literal.span)
+                } else {
+                    ConstantValue::UInt(value).cast(type_name, literal.span)
+                }
+            },
+            LiteralType::Float => {
+                let value = try!(literals::parse_float_literal(&literal.value).ok_or_else(out_of_range));
+                ConstantValue::Float(value).cast(type_name, literal.span)
+            },
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            ConstantValue::Int(v) if v >= 0 => Some(v as u64),
+            ConstantValue::UInt(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// checks that the value is representable by the named type
+    pub fn check_range(self, type_name: &str, span: Span) -> TypeCheckResult<ConstantValue> {
+        let fits = match (self, type_name) {
+            (ConstantValue::Int(v), "i32") => v >= i32::min_value() as i64 && v <= i32::max_value() as i64,
+            (ConstantValue::UInt(v), "u32") => v <= u32::max_value() as u64,
+            (ConstantValue::Float(v), "f32") => (v as f32).is_finite(),
+            (ConstantValue::Float(v), _) => v.is_finite(),
+            _ => true,
+        };
+
+        if fits {
+            Ok(self)
+        } else {
+            Err(overflow(span, type_name))
+        }
+    }
+
+    /// converts the value to the named primitive type
+    pub fn cast(self, type_name: &str, span: Span) -> TypeCheckResult<ConstantValue> {
+        let value = match (self, type_name) {
+            (ConstantValue::Bool(v), "bool") => ConstantValue::Bool(v),
+
+            (ConstantValue::Int(v), "i32") | (ConstantValue::Int(v), "i64") => ConstantValue::Int(v),
+            (ConstantValue::UInt(v), "i32") | (ConstantValue::UInt(v), "i64") => {
+                if v > i64::max_value() as u64 { return Err(overflow(span, type_name)); }
+                ConstantValue::Int(v as i64)
+            },
+            (ConstantValue::Float(v), "i32") | (ConstantValue::Float(v), "i64") => {
+                if !v.is_finite() || v.trunc() < i64::min_value() as f64 || v.trunc() > i64::max_value() as f64 { return Err(overflow(span, type_name)); }
+                ConstantValue::Int(v.trunc() as i64)
+            },
+
+            (ConstantValue::UInt(v), "u32") | (ConstantValue::UInt(v), "u64") => ConstantValue::UInt(v),
+            (ConstantValue::Int(v), "u32") | (ConstantValue::Int(v), "u64") => {
+                if v < 0 { return Err(overflow(span, type_name)); }
+                ConstantValue::UInt(v as u64)
+            },
+            (ConstantValue::Float(v), "u32") | (ConstantValue::Float(v), "u64") => {
+                if !v.is_finite() || v.trunc() < 0.0 || v.trunc() > u64::max_value() as f64 { return Err(overflow(span, type_name)); }
+                ConstantValue::UInt(v.trunc() as u64)
+            },
+
+            (ConstantValue::Float(v), "f32") => ConstantValue::Float(v as f32 as f64),
+            (ConstantValue::Float(v), "f64") => ConstantValue::Float(v),
+            (ConstantValue::Int(v), "f32") => ConstantValue::Float(v as f32 as f64),
+            (ConstantValue::Int(v), "f64") => ConstantValue::Float(v as f64),
+            (ConstantValue::UInt(v), "f32") => ConstantValue::Float(v as f32 as f64),
+            (ConstantValue::UInt(v), "f64") => ConstantValue::Float(v as f64),
+
+            _ => return Err(TypeError::new(span, ErrorKind::NotConstant)),
+        };
+
+        value.check_range(type_name, span)
+    }
+
+    /// applies an infix operator to two values of the named type
+    pub fn apply(operator: &Operator, lhs: ConstantValue, rhs: ConstantValue, type_name: &str, span: Span) -> TypeCheckResult<ConstantValue> {
+        let result = match (lhs, rhs) {
+            (ConstantValue::Int(a), ConstantValue::Int(b)) => {
+                let value = match *operator {
+                    Operator::Plus => a.checked_add(b),
+                    Operator::Minus => a.checked_sub(b),
+                    Operator::Multiply => a.checked_mul(b),
+                    Operator::Divide => {
+                        if b == 0 { return Err(TypeError::new(span, ErrorKind::DivisionByZero)); }
+                        a.checked_div(b)
+                    },
+                    Operator::BitwiseAnd => Some(a & b),
+                    Operator::BitwiseOr => Some(a | b),
+                    Operator::BitwiseXor => Some(a ^ b),
+                    Operator::ShiftLeft => checked_shift_left(a as i128, b as i128, type_name).and_then(|v| if v >= i64::min_value() as i128 && v <= i64::max_value() as i128 { Some(v as i64) } else { None }),
+                    Operator::ShiftRight => checked_shift_right(a as i128, b as i128, type_name).map(|v| v as i64),
+                };
+                ConstantValue::Int(try!(value.ok_or_else(|| overflow(span, type_name))))
+            },
+            (ConstantValue::UInt(a), ConstantValue::UInt(b)) => {
+                let value = match *operator {
+                    Operator::Plus => a.checked_add(b),
+                    Operator::Minus => a.checked_sub(b),
+                    Operator::Multiply => a.checked_mul(b),
+                    Operator::Divide => {
+                        if b == 0 { return Err(TypeError::new(span, ErrorKind::DivisionByZero)); }
+                        a.checked_div(b)
+                    },
+                    Operator::BitwiseAnd => Some(a & b),
+                    Operator::BitwiseOr => Some(a | b),
+                    Operator::BitwiseXor => Some(a ^ b),
+                    Operator::ShiftLeft => checked_shift_left(a as i128, b as i128, type_name).and_then(|v| if v <= u64::max_value() as i128 { Some(v as u64) } else { None }),
+                    Operator::ShiftRight => checked_shift_right(a as i128, b as i128, type_name).map(|v| v as u64),
+                };
+                ConstantValue::UInt(try!(value.ok_or_else(|| overflow(span, type_name))))
+            },
+            (ConstantValue::Float(a), ConstantValue::Float(b)) => {
+                let value = match *operator {
+                    Operator::Plus => a + b,
+                    Operator::Minus => a - b,
+                    Operator::Multiply => a * b,
+                    Operator::Divide => {
+                        if b == 0.0 { return Err(TypeError::new(span, ErrorKind::DivisionByZero)); }
+                        a / b
+                    },
+                    _ => return Err(TypeError::new(span, ErrorKind::NotConstant)),
+                };
+                if type_name == "f32" { ConstantValue::Float(value as f32 as f64) } else { ConstantValue::Float(value) }
+            },
+            (ConstantValue::Bool(a), ConstantValue::Bool(b)) => {
+                match *operator {
+                    Operator::BitwiseAnd => ConstantValue::Bool(a & b),
+                    Operator::BitwiseOr => ConstantValue::Bool(a | b),
+                    Operator::BitwiseXor => ConstantValue::Bool(a ^ b),
+                    _ => return Err(TypeError::new(span, ErrorKind::NotConstant)),
+                }
+            },
+            _ => return Err(TypeError::new(span, ErrorKind::NotConstant)),
+        };
+
+        result.check_range(type_name, span)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integer_arithmetic_is_range_checked() {
+        let span = Span::empty();
+
+        assert_eq!(ConstantValue::apply(&Operator::Multiply, ConstantValue::Int(4), ConstantValue::Int(2), "i32", span), Ok(ConstantValue::Int(8)));
+        assert!(ConstantValue::apply(&Operator::Multiply, ConstantValue::Int(i32::max_value() as i64), ConstantValue::Int(2), "i32", span).is_err());
+        assert!(ConstantValue::apply(&Operator::Multiply, ConstantValue::Int(i32::max_value() as i64), ConstantValue::Int(2), "i64", span).is_ok());
+        assert!(ConstantValue::apply(&Operator::Divide, ConstantValue::UInt(1), ConstantValue::UInt(0), "u32", span).is_err());
+    }
+
+    #[test]
+    fn shifts_respect_bit_width() {
+        let span = Span::empty();
+
+        assert_eq!(ConstantValue::apply(&Operator::ShiftLeft, ConstantValue::UInt(0x0FFF_FFFF), ConstantValue::UInt(4), "u32", span), Ok(ConstantValue::UInt(0xFFFF_FFF0)));
+        assert!(ConstantValue::apply(&Operator::ShiftLeft, ConstantValue::UInt(1), ConstantValue::UInt(32), "u32", span).is_err());
+        assert_eq!(ConstantValue::apply(&Operator::ShiftRight, ConstantValue::Int(-16), ConstantValue::Int(2), "i32", span), Ok(ConstantValue::Int(-4)));
+    }
+
+    #[test]
+    fn shifting_out_set_bits_overflows() {
+        let span = Span::empty();
+
+        assert!(ConstantValue::apply(&Operator::ShiftLeft, ConstantValue::UInt(0xFFFF_FFFF), ConstantValue::UInt(4), "u32", span).is_err());
+        assert!(ConstantValue::apply(&Operator::ShiftLeft, ConstantValue::UInt(u64::max_value()), ConstantValue::UInt(1), "u64", span).is_err());
+        assert_eq!(ConstantValue::apply(&Operator::ShiftLeft, ConstantValue::Int(1), ConstantValue::Int(30), "i32", span), Ok(ConstantValue::Int(1 << 30)));
+        assert!(ConstantValue::apply(&Operator::ShiftLeft, ConstantValue::Int(1), ConstantValue::Int(31), "i32", span).is_err());
+        assert!(ConstantValue::apply(&Operator::ShiftLeft, ConstantValue::Int(3), ConstantValue::Int(62), "i64", span).is_err());
+        assert_eq!(ConstantValue::apply(&Operator::ShiftLeft, ConstantValue::Int(-1), ConstantValue::Int(4), "i32", span), Ok(ConstantValue::Int(-16)));
+        assert!(ConstantValue::apply(&Operator::ShiftLeft, ConstantValue::Int(1), ConstantValue::Int(-1), "i32", span).is_err());
+    }
+
+    #[test]
+    fn casts_between_primitives() {
+        let span = Span::empty();
+
+        assert_eq!(ConstantValue::Float(2.75).cast("i32", span), Ok(ConstantValue::Int(2)));
+        assert_eq!(ConstantValue::Int(3).cast("f32", span), Ok(ConstantValue::Float(3.0)));
+        assert!(ConstantValue::Int(-1).cast("u32", span).is_err());
+    }
+}
@@ -4,13 +4,13 @@ use ::ast::Span;
 
 pub type TypeCheckResult<T> = Result<T, TypeError>;
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq, Clone)]
 pub enum ErrorKind {
     TypeNotFound(String),
     SymbolNameAlreadyUsed(String),
     SyntaxOnlyValidInCoreModule,
     CastAlreadyDeclared(String, String),
-    VariableNotFound(String),
+    VariableNotFound(String /* Variable name */, Option<String> /* Suggested name */),
     CannotInfer(String),
     IncompatibleTypes(Span, Span),
     CannotMakeCallable,
@@ -23,9 +23,47 @@ pub enum ErrorKind {
     ProgramStageTooManyArguments(String, String),
     ProgramStageSignatureMismatch(String /* Source Stage */, String /* Target Stage */, String /* Source Stage Output */, String /* Target Stage Input */),
     InvalidExport(String /* Type name */),
+    LiteralOutOfRange(String /* Literal */, String /* Type name */),
+    IncompatibleLiteral(String /* Literal */, String /* Type name */),
+    ConstantCycle(Vec<String> /* Constant names */),
+    NotConstant,
+    ConstantOverflow(String /* Type name */),
+    DivisionByZero,
+    InvalidArrayLength(String /* Array type */),
+    ConstantWithoutInitializer(String /* Constant name */),
+    UniformInConstant(String /* Uniform name */),
+    UniformOutsideStage(String /* Uniform name */, String /* Function name */),
+    UniformIsReadOnly(String /* Uniform name */),
+    ProgramStageInputNotStruct(String /* Program */, String /* Stage */, String /* Type name */),
+    InvalidRenderTarget(String /* Type name */),
+    StageFunctionNotFound(String /* Function name */),
+    FunctionNotFound(String /* Function name */),
+    MissingProgramStage(String /* Program */, String /* Stage */),
+    ProgramStageNotAllowed(String /* Program */, String /* Stage */),
+    MissingWorkgroupSize(String /* Program */),
+    InvalidWorkgroupSize(String /* Program */, String /* Stage */),
+    BuiltinNotFound(String /* Builtin name */),
+    BuiltinTypeMismatch(String /* Builtin name */, String /* Builtin type */),
+    BuiltinNotStageInput(String /* Builtin name */, String /* Stage */),
+    BuiltinNotStageOutput(String /* Builtin name */, String /* Stage */),
+    BuiltinInUniform(String /* Builtin name */),
+    UnknownAttribute(String /* Attribute name */),
+    MisplacedAttribute(String /* Attribute name */, String /* Target */),
+    DuplicateAttribute(String /* Attribute name */),
+    InvalidAttributeArguments(String /* Attribute name */),
+    NoMemoryLayout(String /* Type name */),
+    RecursiveStruct(Vec<String> /* Structure names */),
+    MissingStructField(String /* Structure */, String /* Field */),
+    UnknownStructField(String /* Structure */, String /* Field */, Vec<String> /* Candidates */),
+    DuplicateStructField(String /* Structure */, String /* Field */),
+    StructFieldTypeMismatch(String /* Field */, String /* Expected type */, String /* Found type */),
+    ModuleNotLoaded(String /* Module */),
+    ImportNotFound(String /* Name */, String /* Module */),
+    ImportNotExported(String /* Name */, String /* Module */),
+    ExportConflict(String /* Name */),
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq, Clone)]
 pub struct TypeError {
     span: Span,
     kind: ErrorKind,
@@ -63,7 +101,10 @@ impl fmt::Display for TypeError {
             ErrorKind::CastAlreadyDeclared(ref from, ref to) => {
                 write!(f, "Cast from \"{}\" to \"{}\" already declared.", from, to)
             },
-            ErrorKind::VariableNotFound(ref variable_name) => {
+            ErrorKind::VariableNotFound(ref variable_name, Some(ref suggestion)) => {
+                write!(f, "Unknown variable \"{}\", did you mean \"{}\"?", variable_name, suggestion)
+            },
+            ErrorKind::VariableNotFound(ref variable_name, None) => {
                 write!(f, "Unknown variable \"{}\".", variable_name)
             },
             ErrorKind::CannotInfer(ref variable_name) => {
@@ -109,8 +150,121 @@ impl fmt::Display for TypeError {
                  target_stage_input_type_name,
                  target_stage_name)
             },
-            ErrorKind::InvalidExport(ref type_name) => write!(f, "Invalid export type named \"{}\"", type_name)
-            
+            ErrorKind::InvalidExport(ref type_name) => write!(f, "Invalid export type named \"{}\"", type_name),
+            ErrorKind::LiteralOutOfRange(ref literal, ref type_name) => {
+                write!(f, "Literal \"{}\" is out of range for type \"{}\".", literal, type_name)
+            },
+            ErrorKind::IncompatibleLiteral(ref literal, ref type_name) => {
+                write!(f, "Literal \"{}\" cannot be of type \"{}\".", literal, type_name)
+            },
+            ErrorKind::ConstantCycle(ref names) => {
+                write!(f, "Constant \"{}\" depends on itself ({}).", names[0], names.join(" -> "))
+            },
+            ErrorKind::NotConstant => {
+                write!(f, "Expression cannot be evaluated at compile time.")
+            },
+            ErrorKind::ConstantOverflow(ref type_name) => {
+                write!(f, "Constant expression overflows type \"{}\".", type_name)
+            },
+            ErrorKind::DivisionByZero => {
+                write!(f, "Division by zero in constant expression.")
+            },
+            ErrorKind::InvalidArrayLength(ref type_name) => {
+                write!(f, "Length of array type \"{}\" is not a positive integer.", type_name)
+            },
+            ErrorKind::ConstantWithoutInitializer(ref name) => {
+                write!(f, "Constant \"{}\" has no initializer, use \"uniform\" for values set at runtime.", name)
+            },
+            ErrorKind::UniformInConstant(ref name) => {
+                write!(f, "Uniform \"{}\" cannot be used in a constant expression.", name)
+            },
+            ErrorKind::UniformOutsideStage(ref name, ref function_name) => {
+                write!(f, "Uniform \"{}\" used in function \"{}\" which is not reachable from a program stage.", name, function_name)
+            },
+            ErrorKind::UniformIsReadOnly(ref name) => {
+                write!(f, "Uniform \"{}\" is read-only.", name)
+            },
+            ErrorKind::ProgramStageInputNotStruct(ref program_type, ref stage_type, ref type_name) => {
+                write!(f, "Input \"{}\" of stage function \"{}\" in program \"{}\" is not a structure.", type_name, stage_type, program_type)
+            },
+            ErrorKind::InvalidRenderTarget(ref type_name) => {
+                write!(f, "Type \"{}\" cannot be used as render target.", type_name)
+            },
+            ErrorKind::StageFunctionNotFound(ref function_name) => {
+                write!(f, "Unknown stage function \"{}\".", function_name)
+            },
+            ErrorKind::FunctionNotFound(ref function_name) => {
+                write!(f, "Unknown function \"{}\".", function_name)
+            },
+            ErrorKind::MissingProgramStage(ref program_type, ref stage_type) => {
+                write!(f, "Program \"{}\" requires a \"{}\" stage.", program_type, stage_type)
+            },
+            ErrorKind::ProgramStageNotAllowed(ref program_type, ref stage_type) => {
+                write!(f, "Stage \"{}\" is not allowed in program \"{}\".", stage_type, program_type)
+            },
+            ErrorKind::MissingWorkgroupSize(ref program_type) => {
+                write!(f, "Compute stage of kernel \"{}\" requires a workgroup size.", program_type)
+            },
+            ErrorKind::InvalidWorkgroupSize(ref program_type, ref stage_type) => {
+                write!(f, "Invalid workgroup size on stage \"{}\" of program \"{}\".", stage_type, program_type)
+            },
+            ErrorKind::BuiltinNotFound(ref builtin_name) => {
+                write!(f, "Unknown builtin \"{}\".", builtin_name)
+            },
+            ErrorKind::BuiltinTypeMismatch(ref builtin_name, ref type_name) => {
+                write!(f, "Builtin \"{}\" must be of type \"{}\".", builtin_name, type_name)
+            },
+            ErrorKind::BuiltinNotStageInput(ref builtin_name, ref stage_type) => {
+                write!(f, "Builtin \"{}\" is not an input of stage \"{}\".", builtin_name, stage_type)
+            },
+            ErrorKind::BuiltinNotStageOutput(ref builtin_name, ref stage_type) => {
+                write!(f, "Builtin \"{}\" is not an output of stage \"{}\".", builtin_name, stage_type)
+            },
+            ErrorKind::BuiltinInUniform(ref builtin_name) => {
+                write!(f, "Builtin \"{}\" cannot be bound to a uniform.", builtin_name)
+            },
+            ErrorKind::UnknownAttribute(ref attribute_name) => {
+                write!(f, "Unknown attribute \"@{}\".", attribute_name)
+            },
+            ErrorKind::MisplacedAttribute(ref attribute_name, ref target) => {
+                write!(f, "Attribute \"@{}\" cannot be applied to a {}.", attribute_name, target)
+            },
+            ErrorKind::DuplicateAttribute(ref attribute_name) => {
+                write!(f, "Attribute \"@{}\" is applied more than once.", attribute_name)
+            },
+            ErrorKind::InvalidAttributeArguments(ref attribute_name) => {
+                write!(f, "Invalid arguments for attribute \"@{}\".", attribute_name)
+            },
+            ErrorKind::NoMemoryLayout(ref type_name) => {
+                write!(f, "Type \"{}\" has no memory layout.", type_name)
+            },
+            ErrorKind::RecursiveStruct(ref struct_names) => {
+                write!(f, "Structure \"{}\" contains itself: {}.", struct_names[0], struct_names.join(" -> "))
+            },
+            ErrorKind::MissingStructField(ref struct_name, ref field_name) => {
+                write!(f, "Missing field \"{}\" in instantiation of \"{}\".", field_name, struct_name)
+            },
+            ErrorKind::UnknownStructField(ref struct_name, ref field_name, ref candidates) => {
+                write!(f, "Structure \"{}\" has no field \"{}\", available fields are: {}.", struct_name, field_name, candidates.join(", "))
+            },
+            ErrorKind::DuplicateStructField(ref struct_name, ref field_name) => {
+                write!(f, "Field \"{}\" of \"{}\" is initialized more than once.", field_name, struct_name)
+            },
+            ErrorKind::StructFieldTypeMismatch(ref field_name, ref expected_type, ref found_type) => {
+                write!(f, "Field \"{}\" expects type \"{}\" but was initialized with \"{}\".", field_name, expected_type, found_type)
+            },
+            ErrorKind::ModuleNotLoaded(ref module) => {
+                write!(f, "Module \"{}\" has not been loaded.", module)
+            },
+            ErrorKind::ImportNotFound(ref name, ref module) => {
+                write!(f, "Module \"{}\" has no item \"{}\".", module, name)
+            },
+            ErrorKind::ImportNotExported(ref name, ref module) => {
+                write!(f, "Item \"{}\" of module \"{}\" is not exported.", name, module)
+            },
+            ErrorKind::ExportConflict(ref name) => {
+                write!(f, "Different items are exported as \"{}\".", name)
+            },
         }
     }
 }
@@ -122,7 +276,7 @@ impl Error for TypeError {
             ErrorKind::SymbolNameAlreadyUsed(_) => "Symbol name already declared.",
             ErrorKind::SyntaxOnlyValidInCoreModule => "Syntax only valid in core module.",
             ErrorKind::CastAlreadyDeclared(_, _) => "Cast already declared.",
-            ErrorKind::VariableNotFound(_) => "Unknown type.",
+            ErrorKind::VariableNotFound(_, _) => "Unknown variable.",
             ErrorKind::CannotInfer(_) => "Cannot infer type.",
             ErrorKind::IncompatibleTypes(_, _) => "Incompatible types.",
             ErrorKind::CannotMakeCallable => "Cannot make type callable.",
@@ -135,6 +289,44 @@ impl Error for TypeError {
             ErrorKind::ProgramStageTooManyArguments(_, _) => "Too many arguments in stage function.",
             ErrorKind::ProgramStageSignatureMismatch(_, _, _, _) => "Incompatible signatures between linked program stages.",
             ErrorKind::InvalidExport(_) => "Invalid export type",
+            ErrorKind::LiteralOutOfRange(_, _) => "Literal out of range.",
+            ErrorKind::IncompatibleLiteral(_, _) => "Literal incompatible with type.",
+            ErrorKind::ConstantCycle(_) => "Cyclic constant definition.",
+            ErrorKind::NotConstant => "Expression not constant.",
+            ErrorKind::ConstantOverflow(_) => "Constant expression overflows type.",
+            ErrorKind::DivisionByZero => "Division by zero.",
+            ErrorKind::InvalidArrayLength(_) => "Invalid array length.",
+            ErrorKind::ConstantWithoutInitializer(_) => "Constant without initializer.",
+            ErrorKind::UniformInConstant(_) => "Uniform used in constant expression.",
+            ErrorKind::UniformOutsideStage(_, _) => "Uniform used outside of program stages.",
+            ErrorKind::UniformIsReadOnly(_) => "Uniform is read-only.",
+            ErrorKind::ProgramStageInputNotStruct(_, _, _) => "Stage function input is not a structure.",
+            ErrorKind::InvalidRenderTarget(_) => "Invalid render target type.",
+            ErrorKind::StageFunctionNotFound(_) => "Unknown stage function.",
+            ErrorKind::FunctionNotFound(_) => "Unknown function.",
+            ErrorKind::MissingProgramStage(_, _) => "Missing program stage.",
+            ErrorKind::ProgramStageNotAllowed(_, _) => "Stage not allowed in program.",
+            ErrorKind::MissingWorkgroupSize(_) => "Missing workgroup size.",
+            ErrorKind::InvalidWorkgroupSize(_, _) => "Invalid workgroup size.",
+            ErrorKind::BuiltinNotFound(_) => "Unknown builtin.",
+            ErrorKind::BuiltinTypeMismatch(_, _) => "Builtin type mismatch.",
+            ErrorKind::BuiltinNotStageInput(_, _) => "Builtin is not a stage input.",
+            ErrorKind::BuiltinNotStageOutput(_, _) => "Builtin is not a stage output.",
+            ErrorKind::BuiltinInUniform(_) => "Builtin in uniform.",
+            ErrorKind::UnknownAttribute(_) => "Unknown attribute.",
+            ErrorKind::MisplacedAttribute(_, _) => "Misplaced attribute.",
+            ErrorKind::DuplicateAttribute(_) => "Duplicate attribute.",
+            ErrorKind::InvalidAttributeArguments(_) => "Invalid attribute arguments.",
+            ErrorKind::NoMemoryLayout(_) => "Type has no memory layout.",
+            ErrorKind::RecursiveStruct(_) => "Recursive structure.",
+            ErrorKind::MissingStructField(_, _) => "Missing structure field.",
+            ErrorKind::UnknownStructField(_, _, _) => "Unknown structure field.",
+            ErrorKind::DuplicateStructField(_, _) => "Duplicate structure field.",
+            ErrorKind::StructFieldTypeMismatch(_, _, _) => "Structure field type mismatch.",
+            ErrorKind::ModuleNotLoaded(_) => "Module not loaded.",
+            ErrorKind::ImportNotFound(_, _) => "Imported item not found.",
+            ErrorKind::ImportNotExported(_, _) => "Imported item not exported.",
+            ErrorKind::ExportConflict(_) => "Conflicting exports.",
         }
     }
 }
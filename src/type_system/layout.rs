@@ -0,0 +1,277 @@
+/// memory layout rules for uniform and storage buffers
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum LayoutRule {
+    /// uniform buffer layout, arrays and structures are aligned to 16 bytes
+    Std140,
+    /// storage buffer layout, like std140 without rounding arrays and structures to 16 bytes
+    Std430,
+    /// every type is aligned to its component size
+    Scalar,
+}
+
+/// the shape of a type as far as its memory layout is concerned
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum TypeShape {
+    /// a scalar of the given size in bytes
+    Scalar(u32),
+    /// component size in bytes and number of components
+    Vector(u32, u32),
+    /// component size in bytes, columns and rows, stored column major
+    Matrix(u32, u32, u32),
+    /// element shape and length
+    Array(Box<TypeShape>, u32),
+    /// named members in declaration order
+    Struct(Vec<(String, TypeShape)>),
+}
+
+fn round_up(value: u32, alignment: u32) -> u32 {
+    (value + alignment - 1) / alignment * alignment
+}
+
+fn struct_alignment(members: &[(String, TypeShape)], rule: LayoutRule) -> u32 {
+    let alignment = members.iter().map(|&(_, ref shape)| shape.alignment(rule)).max().unwrap_or(1);
+
+    match rule {
+        LayoutRule::Std140 => round_up(alignment, 16),
+        LayoutRule::Std430 | LayoutRule::Scalar => alignment,
+    }
+}
+
+fn parse_dimension(value: &str) -> Option<u32> {
+    match value.parse() {
+        Ok(n) if n >= 2 && n <= 4 => Some(n),
+        _ => None,
+    }
+}
+
+impl TypeShape {
+    /// shape of a primitive type of libcore, e.g. `f32`, `uvec3` or `mat4x3`
+    pub fn from_primitive_name(type_name: &str) -> Option<TypeShape> {
+        let scalar_size = |prefix: &str| match prefix {
+            "" | "i" | "u" | "b" => Some(4),
+            "d" => Some(8),
+            _ => None,
+        };
+
+        match type_name {
+            "bool" | "i32" | "u32" | "f32" => return Some(TypeShape::Scalar(4)),
+            "i64" | "u64" | "f64" => return Some(TypeShape::Scalar(8)),
+            _ => (),
+        }
+
+        if let Some(index) = type_name.find("vec") {
+            let component_size = scalar_size(&type_name[..index])?;
+            let components = parse_dimension(&type_name[index + 3..])?;
+            return Some(TypeShape::Vector(component_size, components));
+        }
+
+        if let Some(index) = type_name.find("mat") {
+            let component_size = match &type_name[..index] {
+                "" => 4,
+                "d" => 8,
+                _ => return None,
+            };
+            let dimensions: Vec<&str> = type_name[index + 3..].split('x').collect();
+            let (columns, rows) = match dimensions.as_slice() {
+                [n] => (parse_dimension(n)?, parse_dimension(n)?),
+                [c, r] => (parse_dimension(c)?, parse_dimension(r)?),
+                _ => return None,
+            };
+            return Some(TypeShape::Matrix(component_size, columns, rows));
+        }
+
+        None
+    }
+
+    pub fn alignment(&self, rule: LayoutRule) -> u32 {
+        match (self, rule) {
+            (&TypeShape::Scalar(size), _) => size,
+            (&TypeShape::Vector(component_size, _), LayoutRule::Scalar) => component_size,
+            (&TypeShape::Vector(component_size, 2), _) => component_size * 2,
+            (&TypeShape::Vector(component_size, _), _) => component_size * 4,
+            (&TypeShape::Matrix(component_size, _, rows), _) => TypeShape::Vector(component_size, rows).array_alignment(rule),
+            (&TypeShape::Array(ref element, _), _) => element.array_alignment(rule),
+            (&TypeShape::Struct(ref members), _) => struct_alignment(members, rule),
+        }
+    }
+
+    pub fn size(&self, rule: LayoutRule) -> u32 {
+        match *self {
+            TypeShape::Scalar(size) => size,
+            TypeShape::Vector(component_size, components) => component_size * components,
+            TypeShape::Matrix(component_size, columns, rows) => TypeShape::Vector(component_size, rows).array_stride(rule) * columns,
+            TypeShape::Array(ref element, length) => element.array_stride(rule) * length,
+            TypeShape::Struct(ref members) => StructLayout::new(members, rule).size,
+        }
+    }
+
+    /// alignment of this type used as array element or matrix column
+    fn array_alignment(&self, rule: LayoutRule) -> u32 {
+        match rule {
+            LayoutRule::Std140 => round_up(self.alignment(rule), 16),
+            LayoutRule::Std430 | LayoutRule::Scalar => self.alignment(rule),
+        }
+    }
+
+    /// distance between two elements of an array of this type
+    pub fn array_stride(&self, rule: LayoutRule) -> u32 {
+        round_up(self.size(rule), self.array_alignment(rule))
+    }
+}
+
+/// position of a structure member
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct MemberLayout {
+    pub member_name: String,
+    pub offset: u32,
+    pub size: u32,
+    pub alignment: u32,
+}
+
+/// size, alignment and member offsets of a structure under a layout rule
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct StructLayout {
+    pub rule: LayoutRule,
+    pub size: u32,
+    pub alignment: u32,
+    pub members: Vec<MemberLayout>,
+}
+
+impl StructLayout {
+    pub fn new(members: &[(String, TypeShape)], rule: LayoutRule) -> StructLayout {
+        let mut offset = 0;
+        let mut member_layouts = Vec::new();
+
+        for &(ref member_name, ref shape) in members {
+            let alignment = shape.alignment(rule);
+            let size = shape.size(rule);
+
+            offset = round_up(offset, alignment);
+            member_layouts.push(MemberLayout {
+                member_name: member_name.to_owned(),
+                offset: offset,
+                size: size,
+                alignment: alignment,
+            });
+            offset += size;
+        }
+
+        let alignment = struct_alignment(members, rule);
+
+        StructLayout {
+            rule: rule,
+            size: round_up(offset, alignment),
+            alignment: alignment,
+            members: member_layouts,
+        }
+    }
+
+    pub fn from_shape(shape: &TypeShape, rule: LayoutRule) -> Option<StructLayout> {
+        match *shape {
+            TypeShape::Struct(ref members) => Some(StructLayout::new(members, rule)),
+            _ => None,
+        }
+    }
+
+    pub fn find_member(&self, member_name: &str) -> Option<&MemberLayout> {
+        self.members.iter().find(|m| m.member_name == member_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shape(type_name: &str) -> TypeShape {
+        TypeShape::from_primitive_name(type_name).unwrap()
+    }
+
+    fn members(members: &[(&str, TypeShape)]) -> Vec<(String, TypeShape)> {
+        members.iter().map(|&(name, ref shape)| (name.to_owned(), shape.clone())).collect()
+    }
+
+    fn offsets(layout: &StructLayout) -> Vec<u32> {
+        layout.members.iter().map(|m| m.offset).collect()
+    }
+
+    #[test]
+    fn primitive_shapes() {
+        assert_eq!(shape("f32"), TypeShape::Scalar(4));
+        assert_eq!(shape("dvec2"), TypeShape::Vector(8, 2));
+        assert_eq!(shape("uvec3"), TypeShape::Vector(4, 3));
+        assert_eq!(shape("mat4x4"), TypeShape::Matrix(4, 4, 4));
+        assert_eq!(shape("mat2x3"), TypeShape::Matrix(4, 2, 3));
+        assert_eq!(shape("mat3"), TypeShape::Matrix(4, 3, 3));
+        assert_eq!(TypeShape::from_primitive_name("vec5"), None);
+        assert_eq!(TypeShape::from_primitive_name("Sampler2d"), None);
+    }
+
+    #[test]
+    fn vec3_followed_by_scalar() {
+        let light = members(&[("direction", shape("vec3")), ("intensity", shape("f32")), ("color", shape("vec4"))]);
+
+        for &rule in &[LayoutRule::Std140, LayoutRule::Std430] {
+            let layout = StructLayout::new(&light, rule);
+            assert_eq!(offsets(&layout), vec![0, 12, 16]);
+            assert_eq!(layout.size, 32);
+        }
+
+        let layout = StructLayout::new(&members(&[("a", shape("f32")), ("b", shape("vec3"))]), LayoutRule::Scalar);
+        assert_eq!(offsets(&layout), vec![0, 4]);
+        assert_eq!(layout.size, 16);
+    }
+
+    #[test]
+    fn arrays_of_scalars() {
+        let weights = members(&[("count", shape("u32")), ("weights", TypeShape::Array(Box::new(shape("f32")), 4)), ("scale", shape("f32"))]);
+
+        let std140 = StructLayout::new(&weights, LayoutRule::Std140);
+        assert_eq!(offsets(&std140), vec![0, 16, 80]);
+        assert_eq!(std140.size, 96);
+
+        let std430 = StructLayout::new(&weights, LayoutRule::Std430);
+        assert_eq!(offsets(&std430), vec![0, 4, 20]);
+        assert_eq!(std430.size, 24);
+
+        let scalar = StructLayout::new(&weights, LayoutRule::Scalar);
+        assert_eq!(offsets(&scalar), vec![0, 4, 20]);
+        assert_eq!(scalar.size, 24);
+    }
+
+    #[test]
+    fn matrices() {
+        let transform = members(&[("normal", shape("mat3x3")), ("mvp", shape("mat4x4"))]);
+
+        let std140 = StructLayout::new(&transform, LayoutRule::Std140);
+        assert_eq!(offsets(&std140), vec![0, 48]);
+        assert_eq!(std140.size, 112);
+
+        let scalar = StructLayout::new(&transform, LayoutRule::Scalar);
+        assert_eq!(offsets(&scalar), vec![0, 36]);
+        assert_eq!(scalar.size, 100);
+
+        let std430 = StructLayout::new(&members(&[("a", shape("mat2x2")), ("b", shape("f32"))]), LayoutRule::Std430);
+        assert_eq!(offsets(&std430), vec![0, 16]);
+
+        let std140 = StructLayout::new(&members(&[("a", shape("mat2x2")), ("b", shape("f32"))]), LayoutRule::Std140);
+        assert_eq!(offsets(&std140), vec![0, 32]);
+    }
+
+    #[test]
+    fn nested_structs() {
+        let inner = TypeShape::Struct(members(&[("value", shape("f32"))]));
+        let outer = members(&[("a", shape("f32")), ("inner", inner.clone()), ("b", shape("f32"))]);
+
+        let std140 = StructLayout::new(&outer, LayoutRule::Std140);
+        assert_eq!(offsets(&std140), vec![0, 16, 32]);
+        assert_eq!(std140.size, 48);
+
+        let std430 = StructLayout::new(&outer, LayoutRule::Std430);
+        assert_eq!(offsets(&std430), vec![0, 4, 8]);
+        assert_eq!(std430.size, 12);
+
+        let lights = members(&[("lights", TypeShape::Array(Box::new(TypeShape::Struct(members(&[("position", shape("vec3"))]))), 2))]);
+        assert_eq!(StructLayout::new(&lights, LayoutRule::Std430).size, 32);
+        assert_eq!(StructLayout::new(&lights, LayoutRule::Scalar).size, 24);
+    }
+}
@@ -0,0 +1,182 @@
+use ::ast::{ LiteralExpression, LiteralType };
+use ::type_system::error::{ TypeError, ErrorKind, TypeCheckResult };
+
+/// type of a literal without suffix and without a usable context
+pub fn default_type_name(literal_type: &LiteralType) -> &'static str {
+    match *literal_type {
+        LiteralType::Int => "i32",
+        LiteralType::Float => "f64",
+        LiteralType::Bool => "bool",
+    }
+}
+
+/// whether a literal of the given kind can be typed as the named type
+pub fn is_literal_compatible(literal_type: &LiteralType, type_name: &str) -> bool {
+    match *literal_type {
+        LiteralType::Int => is_integer_type_name(type_name),
+        LiteralType::Float => is_float_type_name(type_name),
+        LiteralType::Bool => type_name == "bool",
+    }
+}
+
+pub fn is_integer_type_name(type_name: &str) -> bool {
+    match type_name {
+        "i32" | "i64" | "u32" | "u64" => true,
+        _ => false,
+    }
+}
+
+pub fn is_float_type_name(type_name: &str) -> bool {
+    match type_name {
+        "f32" | "f64" => true,
+        _ => false,
+    }
+}
+
+/// splits the minus sign of a negative literal like `-2147483648` from its magnitude
+pub fn split_sign(value: &str) -> (bool, &str) {
+    if value.starts_with('-') {
+        (true, &value[1..])
+    } else {
+        (false, value)
+    }
+}
+
+/// parses decimal, hexadecimal (`0x`) and binary (`0b`) integer literals
+pub fn parse_integer_literal(value: &str) -> Option<u64> {
+    let (digits, radix) = if value.starts_with("0x") || value.starts_with("0X") {
+        (&value[2..], 16)
+    } else if value.starts_with("0b") || value.starts_with("0B") {
+        (&value[2..], 2)
+    } else {
+        (value, 10)
+    };
+
+    u64::from_str_radix(digits, radix).ok()
+}
+
+/// parses float literals including exponent forms like `1.5e-3`
+pub fn parse_float_literal(value: &str) -> Option<f64> {
+    value.parse::<f64>().ok()
+}
+
+fn integer_max(type_name: &str) -> u64 {
+    match type_name {
+        "i32" => i32::max_value() as u64,
+        "i64" => i64::max_value() as u64,
+        "u32" => u32::max_value() as u64,
+        _ => u64::max_value(),
+    }
+}
+
+/// magnitude of the smallest value of the type, `i32::min_value()` is one further from zero than its maximum
+fn integer_min_magnitude(type_name: &str) -> u64 {
+    match type_name {
+        "i32" => i32::max_value() as u64 + 1,
+        "i64" => i64::max_value() as u64 + 1,
+        _ => 0,
+    }
+}
+
+/// whether the digits before the exponent of a float literal are all zero, `0.0e5` is zero while `1e-400` only rounds to it
+fn is_zero_float_literal(value: &str) -> bool {
+    let mantissa = value.split(|c| c == 'e' || c == 'E').next().unwrap_or("");
+    mantissa.chars().all(|c| !c.is_digit(10) || c == '0')
+}
+
+/// a float is out of range if it overflows to infinity or if a non-zero literal rounds to zero, subnormals are fine
+fn is_float_in_range(value: f64, is_zero: bool, type_name: &str) -> bool {
+    let value = match type_name {
+        "f32" => value as f32 as f64,
+        _ => value,
+    };
+
+    value.is_finite() && (value != 0.0 || is_zero)
+}
+
+/// checks that the literal value is representable by the named type
+pub fn check_literal_range(literal: &LiteralExpression, type_name: &str) -> TypeCheckResult<()> {
+    let in_range = match literal.literal_expression_type {
+        LiteralType::Int => {
+            let (negative, magnitude) = split_sign(&literal.value);
+            match parse_integer_literal(magnitude) {
+                Some(value) if negative => value <= integer_min_magnitude(type_name),
+                Some(value) => value <= integer_max(type_name),
+                None => false,
+            }
+        },
+        LiteralType::Float => {
+            match parse_float_literal(&literal.value) {
+                Some(value) => is_float_in_range(value, is_zero_float_literal(&literal.value), type_name),
+                None => false,
+            }
+        },
+        LiteralType::Bool => true,
+    };
+
+    if in_range {
+        Ok(())
+    } else {
+        Err(TypeError::new(literal.span, ErrorKind::LiteralOutOfRange(literal.value.to_owned(), type_name.to_owned())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::ast::Span;
+
+    fn literal(value: &str, literal_type: LiteralType) -> LiteralExpression {
+        LiteralExpression {
+            span: Span::empty(),
+            value: value.to_string(),
+            literal_expression_type: literal_type,
+            literal_type_name: None,
+            literal_type: None,
+        }
+    }
+
+    #[test]
+    fn parse_integer_forms() {
+        assert_eq!(parse_integer_literal("42"), Some(42));
+        assert_eq!(parse_integer_literal("0xFF"), Some(255));
+        assert_eq!(parse_integer_literal("0b101"), Some(5));
+    }
+
+    #[test]
+    fn integer_range() {
+        assert!(check_literal_range(&literal("2147483647", LiteralType::Int), "i32").is_ok());
+        assert!(check_literal_range(&literal("2147483648", LiteralType::Int), "i32").is_err());
+        assert!(check_literal_range(&literal("2147483648", LiteralType::Int), "u32").is_ok());
+        assert!(check_literal_range(&literal("0xFFFFFFFFFFFFFFFF", LiteralType::Int), "u64").is_ok());
+        assert!(check_literal_range(&literal("18446744073709551616", LiteralType::Int), "u64").is_err());
+    }
+
+    #[test]
+    fn negative_integer_range() {
+        assert!(check_literal_range(&literal("-2147483648", LiteralType::Int), "i32").is_ok());
+        assert!(check_literal_range(&literal("-2147483649", LiteralType::Int), "i32").is_err());
+        assert!(check_literal_range(&literal("-9223372036854775808", LiteralType::Int), "i64").is_ok());
+        assert!(check_literal_range(&literal("-1", LiteralType::Int), "u32").is_err());
+        assert!(check_literal_range(&literal("-3.4e38", LiteralType::Float), "f32").is_ok());
+    }
+
+    #[test]
+    fn float_range() {
+        assert!(check_literal_range(&literal("3.4e38", LiteralType::Float), "f32").is_ok());
+        assert!(check_literal_range(&literal("3.5e38", LiteralType::Float), "f32").is_err());
+        assert!(check_literal_range(&literal("3.5e38", LiteralType::Float), "f64").is_ok());
+        assert!(check_literal_range(&literal("1.0e-50", LiteralType::Float), "f32").is_err());
+        assert!(check_literal_range(&literal("0.0", LiteralType::Float), "f32").is_ok());
+    }
+
+    #[test]
+    fn float_subnormal_range() {
+        assert!(check_literal_range(&literal("1e-40", LiteralType::Float), "f32").is_ok());
+        assert!(check_literal_range(&literal("-1e-40", LiteralType::Float), "f32").is_ok());
+        assert!(check_literal_range(&literal("1e-46", LiteralType::Float), "f32").is_err());
+        assert!(check_literal_range(&literal("1e-310", LiteralType::Float), "f64").is_ok());
+        assert!(check_literal_range(&literal("1e-400", LiteralType::Float), "f64").is_err());
+        assert!(check_literal_range(&literal("0.0e-400", LiteralType::Float), "f64").is_ok());
+    }
+}
@@ -1,5 +1,9 @@
+pub mod builtins;
 pub mod call_signature;
+pub mod constant_value;
 pub mod error;
+pub mod layout;
+pub mod literals;
 pub mod symbol_table;
 pub mod structure_members;
 pub mod type_environment;
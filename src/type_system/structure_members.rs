@@ -1,9 +1,12 @@
 use ::type_system::type_environment::TypeReference;
+use ::type_system::layout::{ LayoutRule, TypeShape, StructLayout };
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct StructureMember {
     pub member_name: String,
     pub member_type: TypeReference,
+    /// name of the builtin the member is bound to with `@builtin(...)`
+    pub builtin: Option<String>,
 }
 
 impl StructureMember {
@@ -11,8 +14,14 @@ impl StructureMember {
         StructureMember {
             member_name: member_name,
             member_type: member_type,
+            builtin: None,
         }
     }
+
+    pub fn with_builtin(mut self, builtin: Option<String>) -> StructureMember {
+        self.builtin = builtin;
+        self
+    }
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -27,6 +36,10 @@ impl StructureMembers {
         }
     }
 
+    pub fn get_members(&self) -> &Vec<StructureMember> {
+        &self.members
+    }
+
     pub fn find_member_type(&self, member_name: &str) -> Option<TypeReference> {
         for m in self.members.iter() {
             if m.member_name == member_name {
@@ -36,26 +49,53 @@ impl StructureMembers {
         None
     }
 
-    pub fn is_assignable_with(&self, members: &Vec<StructureMember>) -> bool {
-        if members.len() != self.members.len() {
-            return false;
+    /// shape of the structure, `shape_of` provides the shape of each member type
+    pub fn shape<F>(&self, mut shape_of: F) -> Option<TypeShape> where F: FnMut(TypeReference) -> Option<TypeShape> {
+        let mut members = Vec::new();
+        for m in self.members.iter() {
+            members.push((m.member_name.to_owned(), shape_of(m.member_type)?));
         }
+        Some(TypeShape::Struct(members))
+    }
 
-        for a in self.members.iter() {
-            let mut found = false;
-            for b in members.iter() {
-                if a == b { found = true; break; }
-            }
+    /// size, alignment and member offsets under the given layout rule
+    pub fn layout<F>(&self, rule: LayoutRule, shape_of: F) -> Option<StructLayout> where F: FnMut(TypeReference) -> Option<TypeShape> {
+        self.shape(shape_of).and_then(|shape| StructLayout::from_shape(&shape, rule))
+    }
 
-            if !found {
-                return false;
-            }
-        }
+    pub fn is_assignable_with(&self, members: &Vec<StructureMember>) -> bool {
+        self.is_assignable_with_by(members, |a, b| a == b)
+    }
+
+    /// like `is_assignable_with`, member types are compared with `is_type_assignable`
+    pub fn is_assignable_with_by<F>(&self, members: &[StructureMember], is_type_assignable: F) -> bool where F: FnMut(TypeReference, TypeReference) -> bool {
+        let members: Vec<&StructureMember> = members.iter().collect();
+        match_members(&self.members.iter().collect::<Vec<_>>(), &members, true, is_type_assignable)
+    }
 
-        true
+    /// whether a stage writing these members feeds a stage reading `members`
+    /// builtin members are written to and read from the pipeline, only the other members are matched
+    pub fn is_linkable_with_by<F>(&self, members: &[StructureMember], is_type_assignable: F) -> bool where F: FnMut(TypeReference, TypeReference) -> bool {
+        match_members(&varyings(&self.members), &varyings(members), false, is_type_assignable)
     }
 }
 
+/// members passed from one stage to the next, those not bound to a builtin
+fn varyings(members: &[StructureMember]) -> Vec<&StructureMember> {
+    members.iter().filter(|m| m.builtin.is_none()).collect()
+}
+
+/// whether both lists hold members of the same names in any order with assignable types
+fn match_members<F>(a: &[&StructureMember], b: &[&StructureMember], compare_builtins: bool, mut is_type_assignable: F) -> bool where F: FnMut(TypeReference, TypeReference) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter().all(|a| {
+        b.iter().any(|b| a.member_name == b.member_name && (!compare_builtins || a.builtin == b.builtin) && is_type_assignable(a.member_type, b.member_type))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -89,4 +129,26 @@ mod tests {
 
         assert!(!struct_members.is_assignable_with(&b));
     }
+
+    #[test]
+    fn builtin_members_do_not_link() {
+        let position = StructureMember::new("position".to_string(), TypeReference::new(0)).with_builtin(Some("position".to_string()));
+        let output = StructureMembers::new(vec![position, StructureMember::new("uv".to_string(), TypeReference::new(1))]);
+
+        assert!(output.is_linkable_with_by(&[StructureMember::new("uv".to_string(), TypeReference::new(1))], |a, b| a == b));
+        assert!(!output.is_linkable_with_by(&[StructureMember::new("uv".to_string(), TypeReference::new(0))], |a, b| a == b));
+        assert!(!output.is_assignable_with_by(&[StructureMember::new("uv".to_string(), TypeReference::new(1))], |a, b| a == b));
+    }
+
+    #[test]
+    fn layout_of_members() {
+        let members = StructureMembers::new(vec![StructureMember::new("direction".to_string(), TypeReference::new(0)), StructureMember::new("intensity".to_string(), TypeReference::new(1))]);
+        let shape_of = |t: TypeReference| if t == TypeReference::new(0) { TypeShape::from_primitive_name("vec3") } else { TypeShape::from_primitive_name("f32") };
+
+        let layout = members.layout(LayoutRule::Std140, shape_of).unwrap();
+
+        assert_eq!(layout.find_member("intensity").unwrap().offset, 12);
+        assert_eq!(layout.size, 16);
+        assert_eq!(members.layout(LayoutRule::Std140, |_| None), None);
+    }
 }
@@ -1,9 +1,12 @@
-use ::std::collections::HashMap;
+use ::std::collections::{ HashMap, HashSet };
 use ::std::rc::Rc;
 use ::std::cell::{ RefCell, Ref, RefMut };
-use ::ast::Span;
+use ::ast::{ Span, StageKind };
 use ::type_system::error::{ TypeError, ErrorKind, TypeCheckResult };
 use ::type_system::type_definition::TypeDefinition;
+use ::type_system::constant_value::ConstantValue;
+use ::type_system::builtins::StageBuiltin;
+use ::type_system::layout::TypeShape;
 use ::type_system::type_environment::{ TypeEnvironment, TypeReference };
 use ::data_structures::shared::Shared;
 
@@ -16,17 +19,32 @@ pub enum SymbolState {
     Typed(TypeReference),
 }
 
-#[derive(Debug, PartialEq, Eq)]
+/// what declared a symbol
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum SymbolKind {
+    Local,
+    Argument,
+    Constant,
+    Uniform,
+    Sampler,
+    Builtin,
+    Function,
+    Type,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Symbol {
     pub name: String,
     pub state: SymbolState,
+    pub kind: SymbolKind,
 }
 
 impl Symbol {
-    pub fn new(name: &str, state: SymbolState) -> Symbol {
+    pub fn new(name: &str, state: SymbolState, kind: SymbolKind) -> Symbol {
         Symbol {
             name: name.to_string(),
             state: state,
+            kind: kind,
         }
     }
 
@@ -50,6 +68,11 @@ impl Symbol {
 struct Scope {
     symbols: HashMap<String, Symbol>,
     types: HashMap<String, TypeReference>,
+    constants: HashMap<String, ConstantValue>,
+    /// names bound from other modules
+    imports: HashSet<String>,
+    /// namespaces of `import * as name` and the path of their module
+    namespaces: HashMap<String, String>,
 }
 
 impl Scope {
@@ -57,14 +80,88 @@ impl Scope {
         Scope {
             symbols: HashMap::new(),
             types: HashMap::new(),
+            constants: HashMap::new(),
+            imports: HashSet::new(),
+            namespaces: HashMap::new(),
         }
     }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.types.contains_key(name) || self.symbols.contains_key(name) || self.constants.contains_key(name) || self.namespaces.contains_key(name)
+    }
+
+    /// names of the items in this scope sorted by name, imported names are excluded unless `with_imports`
+    pub fn names(&self, with_imports: bool) -> Vec<String> {
+        let names: HashSet<&String> = self.types.keys().chain(self.symbols.keys()).chain(self.constants.keys())
+            .filter(|name| with_imports || !self.imports.contains(*name))
+            .collect();
+        let mut names: Vec<String> = names.into_iter().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// type and symbol state bound to `name`, equal for two names of the same item
+    fn find_item(&self, name: &str) -> (Option<TypeReference>, Option<SymbolState>) {
+        (self.types.get(name).cloned(), self.symbols.get(name).map(|s| s.state))
+    }
+
+    /// binds the item `name` of `other` as `alias`, sharing its type, symbol and constant value
+    fn bind_from(&mut self, other: &Scope, name: &str, alias: &str) {
+        if let Some(type_ref) = other.types.get(name) {
+            self.types.insert(alias.to_owned(), *type_ref);
+        }
+        if let Some(symbol) = other.symbols.get(name) {
+            let mut symbol = symbol.clone();
+            symbol.name = alias.to_owned();
+            self.symbols.insert(alias.to_owned(), symbol);
+        }
+        if let Some(value) = other.constants.get(name) {
+            self.constants.insert(alias.to_owned(), value.clone());
+        }
+    }
+
+    /// binds `alias` to `type_ref` and to an untyped symbol, standing in for an item whose module failed to declare it
+    fn bind_unknown(&mut self, alias: &str, type_ref: TypeReference) {
+        self.types.insert(alias.to_owned(), type_ref);
+        self.symbols.insert(alias.to_owned(), Symbol::new(alias, SymbolState::Free, SymbolKind::Constant));
+    }
+}
+
+/// the module scope of a checked module and the items it exports
+#[derive(Debug)]
+struct ModuleInterface {
+    scope: Scope,
+    exports: Scope,
+}
+
+/// the exports of the module at `module_path` if they contain `name`
+fn find_export<'a>(modules: &'a HashMap<String, ModuleInterface>, module_path: &str, name: &str) -> TypeCheckResult<&'a Scope> {
+    let module = match modules.get(module_path) {
+        Some(m) => m,
+        None => return Err(TypeError::new(Span::new(0, 0, 1, 1), ErrorKind::ModuleNotLoaded(module_path.to_owned()))),
+    };
+
+    if module.exports.contains(name) {
+        return Ok(&module.exports);
+    }
+
+    let kind = if module.scope.contains(name) {
+        ErrorKind::ImportNotExported(name.to_owned(), module_path.to_owned())
+    } else {
+        ErrorKind::ImportNotFound(name.to_owned(), module_path.to_owned())
+    };
+    Err(TypeError::new(Span::new(0, 0, 1, 1), kind))
 }
 
 #[derive(Debug)]
 pub struct SymbolTable {
     scopes: Vec<Scope>,
     types: TypeEnvironment,
+    builtins: Vec<StageBuiltin>,
+    modules: HashMap<String, ModuleInterface>,
+    exports: Scope,
+    /// whether a module other than the core module is being checked
+    in_module: bool,
 }
 
 impl SymbolTable {
@@ -72,6 +169,10 @@ impl SymbolTable {
         SymbolTable {
             scopes: vec![Scope::new()],
             types: types,
+            builtins: Vec::new(),
+            modules: HashMap::new(),
+            exports: Scope::new(),
+            in_module: false,
         }
     }
 
@@ -137,7 +238,52 @@ impl SymbolTable {
         self.types.find_type_mut_or_err(type_ref)
     }
 
+    /// memory layout shape of a primitive or structure type
+    pub fn find_type_shape(&self, type_ref: TypeReference) -> Option<TypeShape> {
+        self.find_type_shape_in(type_ref, &mut Vec::new())
+    }
+
+    fn find_type_shape_in(&self, type_ref: TypeReference, trail: &mut Vec<TypeReference>) -> Option<TypeShape> {
+        // a structure containing itself has no layout
+        if trail.contains(&type_ref) {
+            return None;
+        }
+
+        let type_definition = self.find_type(type_ref)?;
+        if let Some((element_type, length)) = type_definition.get_array() {
+            return Some(TypeShape::Array(Box::new(self.find_type_shape_in(element_type, trail)?), length));
+        }
+
+        match type_definition.get_member() {
+            Some(members) => {
+                trail.push(type_ref);
+                let shape = members.shape(|member_type| self.find_type_shape_in(member_type, trail));
+                trail.pop();
+                shape
+            },
+            None => TypeShape::from_primitive_name(type_definition.get_name()),
+        }
+    }
+
+    /// scope of the exporting module and the item name of a qualified name like `lighting::Light`
+    /// `None` if the namespace is unknown or the item is not exported
+    fn find_namespace_item<'a, 'b>(&'a self, name: &'b str) -> Option<(&'a Scope, &'b str)> {
+        let separator = name.find("::")?;
+        let (namespace, item) = (&name[..separator], &name[separator + 2..]);
+        let module_path = self.scopes.iter().filter_map(|scope| scope.namespaces.get(namespace)).next()?;
+        let module = self.modules.get(module_path)?;
+        if !module.exports.contains(item) {
+            return None;
+        }
+
+        Some((&module.exports, item))
+    }
+
     pub fn find_type_ref(&self, name: &str) -> Option<TypeReference> {
+        if let Some((scope, item)) = self.find_namespace_item(name) {
+            return scope.types.get(item).cloned();
+        }
+
         for scope in &self.scopes {
             if scope.types.contains_key(name) {
                 match scope.types.get(name) {
@@ -150,38 +296,111 @@ impl SymbolTable {
         None
     }
 
-    pub fn find_type_ref_or_err(&self, name: &str) -> TypeCheckResult<TypeReference> {
-        for scope in &self.scopes {
-            if scope.types.contains_key(name) {
-                match scope.types.get(name) {
-                    Some(t) => return Ok(t.clone()),
-                    None => return Err(TypeError::new(Span::new(0, 0, 1, 1), ErrorKind::TypeNotFound(name.to_owned()))),
-                }
-            }
+    /// like `find_type_ref`, array types like `[Light; 4]` are created on first use
+    pub fn resolve_type_name(&mut self, name: &str) -> Option<TypeReference> {
+        if let Some(type_ref) = self.find_type_ref(name) {
+            return Some(type_ref);
+        }
+
+        // array types are keyed by their element type, `[Light; 4]` may mean different types in different modules
+        let (element_name, length) = parse_array_type_name(name)?;
+        let element_type = self.resolve_type_name(element_name)?;
+        let key = format!("[#{}; {}]", element_type.get_id(), length);
+        let root = self.scopes.len() - 1;
+        if let Some(type_ref) = self.scopes[root].types.get(&key) {
+            return Some(*type_ref);
+        }
+
+        let type_ref = self.types.create_type(name).ok()?;
+        self.scopes[root].types.insert(key, type_ref);
+        self.find_type_mut(type_ref)?.make_array(element_type, length);
+        Some(type_ref)
+    }
+
+    pub fn resolve_type_name_or_err(&mut self, name: &str) -> TypeCheckResult<TypeReference> {
+        match self.resolve_type_name(name) {
+            Some(type_ref) => Ok(type_ref),
+            None => Err(TypeError::new(Span::new(0, 0, 1, 1), ErrorKind::TypeNotFound(name.to_owned()))),
+        }
+    }
+
+    /// whether a value of type `from` can be used as `to`
+    /// structures match member by member in any order, arrays element by element
+    pub fn is_assignable(&self, from: TypeReference, to: TypeReference) -> bool {
+        self.is_assignable_in(from, to, &mut Vec::new())
+    }
+
+    fn is_assignable_in(&self, from: TypeReference, to: TypeReference, trail: &mut Vec<(TypeReference, TypeReference)>) -> bool {
+        if from == to {
+            return true;
+        }
+
+        // recursive structures are reported elsewhere
+        if trail.contains(&(from, to)) {
+            return false;
+        }
+
+        let (from_definition, to_definition) = match (self.find_type(from), self.find_type(to)) {
+            (Some(f), Some(t)) => (f, t),
+            _ => return false,
+        };
+
+        if let (Some((from_element, from_length)), Some((to_element, to_length))) = (from_definition.get_array(), to_definition.get_array()) {
+            return from_length == to_length && self.is_assignable_in(from_element, to_element, trail);
         }
 
-        Err(TypeError::new(Span::new(0, 0, 1, 1), ErrorKind::TypeNotFound(name.to_owned())))
+        match (from_definition.get_member(), to_definition.get_member()) {
+            (Some(from_members), Some(to_members)) => {
+                trail.push((from, to));
+                let is_assignable = from_members.is_assignable_with_by(to_members.get_members(), |a, b| self.is_assignable_in(a, b, trail));
+                trail.pop();
+                is_assignable
+            },
+            _ => false,
+        }
     }
 
-    pub fn add_symbol(&mut self, name: &str) -> TypeCheckResult<()> {
+    /// whether the output of a stage of type `from` feeds a stage taking `to` as input
+    /// builtin members are skipped, the other members are matched by name and assignable type
+    pub fn is_linkable(&self, from: TypeReference, to: TypeReference) -> bool {
+        let members_of = |type_ref: TypeReference| self.find_type(type_ref).and_then(|t| t.get_member());
+
+        match (members_of(from), members_of(to)) {
+            (Some(from_members), Some(to_members)) => from_members.is_linkable_with_by(to_members.get_members(), |a, b| self.is_assignable(a, b)),
+            _ => self.is_assignable(from, to),
+        }
+    }
+
+    pub fn find_type_ref_or_err(&self, name: &str) -> TypeCheckResult<TypeReference> {
+        match self.find_type_ref(name) {
+            Some(t) => Ok(t),
+            None => Err(TypeError::new(Span::new(0, 0, 1, 1), ErrorKind::TypeNotFound(name.to_owned()))),
+        }
+    }
+
+    pub fn add_symbol(&mut self, name: &str, kind: SymbolKind) -> TypeCheckResult<()> {
         if self.scopes[0].symbols.contains_key(name) {
             return Err(TypeError::new(Span::new(0, 0, 1, 1), ErrorKind::SymbolNameAlreadyUsed(name.to_owned())));
         }
 
-        self.scopes[0].symbols.insert(name.to_string(), Symbol::new(name, SymbolState::Free));
+        self.scopes[0].symbols.insert(name.to_string(), Symbol::new(name, SymbolState::Free, kind));
         Ok(())
     }
 
-    pub fn add_symbol_with_type(&mut self, name: &str, symbol_type: TypeReference) -> TypeCheckResult<()> {
+    pub fn add_symbol_with_type(&mut self, name: &str, symbol_type: TypeReference, kind: SymbolKind) -> TypeCheckResult<()> {
         if self.scopes[0].symbols.contains_key(name) {
             return Err(TypeError::new(Span::new(0, 0, 1, 1), ErrorKind::SymbolNameAlreadyUsed(name.to_owned())));
         }
 
-        self.scopes[0].symbols.insert(name.to_string(), Symbol::new(name, SymbolState::Typed(symbol_type)));
+        self.scopes[0].symbols.insert(name.to_string(), Symbol::new(name, SymbolState::Typed(symbol_type), kind));
         Ok(())
     }
 
     pub fn find_symbol(&mut self, name: &str) -> Option<&Symbol> {
+        if let Some((scope, item)) = self.find_namespace_item(name) {
+            return scope.symbols.get(item);
+        }
+
         for scope in &self.scopes {
             if scope.symbols.contains_key(name) {
                 return scope.symbols.get(name);
@@ -201,18 +420,237 @@ impl SymbolTable {
         None
     }
 
+    /// finds the visible variable-like symbol with the name closest to `name`
+    /// used to suggest corrections for misspelled names
+    pub fn find_similar_symbol(&self, name: &str) -> Option<&str> {
+        let max_distance = ::std::cmp::max(1, name.chars().count() / 3);
+        let mut best: Option<(usize, &str)> = None;
+
+        for scope in &self.scopes {
+            for symbol in scope.symbols.values() {
+                match symbol.kind {
+                    SymbolKind::Function | SymbolKind::Type => continue,
+                    _ => (),
+                }
+
+                let distance = edit_distance(name, &symbol.name);
+                if distance == 0 || distance > max_distance {
+                    continue;
+                }
+
+                let is_better = match best {
+                    Some((best_distance, best_name)) => distance < best_distance || (distance == best_distance && *symbol.name < *best_name),
+                    None => true,
+                };
+                if is_better {
+                    best = Some((distance, &symbol.name));
+                }
+            }
+        }
+
+        best.map(|(_, name)| name)
+    }
+
     pub fn resolve_symbol_type(&mut self, name: &str, symbol_type: TypeReference) -> TypeCheckResult<()> {
         for scope in &mut self.scopes {
             if scope.symbols.contains_key(name) {
                 match scope.symbols.get_mut(name) {
                     Some(ref mut s) => s.resolve_type(symbol_type),
-                    None => return Err(TypeError::new(Span::new(0, 0, 1, 1), ErrorKind::VariableNotFound(name.to_owned()))),
+                    None => return Err(TypeError::new(Span::new(0, 0, 1, 1), ErrorKind::VariableNotFound(name.to_owned(), None))),
                 }
                 return Ok(());
             }
         }
 
-        Err(TypeError::new(Span::new(0, 0, 1, 1), ErrorKind::VariableNotFound(name.to_owned())))
+        Err(TypeError::new(Span::new(0, 0, 1, 1), ErrorKind::VariableNotFound(name.to_owned(), None)))
+    }
+
+    pub fn add_constant_value(&mut self, name: &str, value: ConstantValue) -> TypeCheckResult<()> {
+        if self.scopes[0].constants.contains_key(name) {
+            return Err(TypeError::new(Span::new(0, 0, 1, 1), ErrorKind::SymbolNameAlreadyUsed(name.to_owned())));
+        }
+
+        self.scopes[0].constants.insert(name.to_string(), value);
+        Ok(())
+    }
+
+    pub fn find_constant_value(&self, name: &str) -> Option<ConstantValue> {
+        if let Some((scope, item)) = self.find_namespace_item(name) {
+            return scope.constants.get(item).cloned();
+        }
+
+        for scope in &self.scopes {
+            if scope.constants.contains_key(name) {
+                return scope.constants.get(name).cloned();
+            }
+        }
+
+        None
+    }
+
+    pub fn add_builtin(&mut self, builtin: StageBuiltin) -> TypeCheckResult<()> {
+        if self.find_builtin(&builtin.name).is_some() {
+            return Err(TypeError::new(Span::new(0, 0, 1, 1), ErrorKind::SymbolNameAlreadyUsed(builtin.name)));
+        }
+
+        self.builtins.push(builtin);
+        Ok(())
+    }
+
+    pub fn find_builtin(&self, name: &str) -> Option<&StageBuiltin> {
+        self.builtins.iter().find(|b| b.name == name)
+    }
+
+    /// builtins the pipeline provides to stages of the given kind
+    pub fn find_stage_inputs(&self, stage_kind: StageKind) -> Vec<&StageBuiltin> {
+        self.builtins.iter().filter(|b| b.is_input_of(stage_kind)).collect()
+    }
+
+    /// exports the item `name` of the current scope from the module being checked
+    pub fn export(&mut self, name: &str) -> TypeCheckResult<()> {
+        if !self.scopes[0].contains(name) {
+            return Err(TypeError::new(Span::new(0, 0, 1, 1), ErrorKind::TypeNotFound(name.to_owned())));
+        }
+
+        if self.exports.contains(name) && self.exports.find_item(name) != self.scopes[0].find_item(name) {
+            return Err(TypeError::new(Span::new(0, 0, 1, 1), ErrorKind::ExportConflict(name.to_owned())));
+        }
+
+        self.exports.bind_from(&self.scopes[0], name, name);
+        Ok(())
+    }
+
+    /// exports every item declared in the current scope, imported items are not exported again
+    pub fn export_all(&mut self) -> TypeCheckResult<()> {
+        for name in self.scopes[0].names(false) {
+            self.export(&name)?;
+        }
+
+        Ok(())
+    }
+
+    /// exports the exported item `name` of the module at `module_path` as `alias` without binding it in the current scope
+    /// exporting a different item under a name that is already exported is a conflict
+    pub fn reexport(&mut self, module_path: &str, name: &str, alias: &str) -> TypeCheckResult<()> {
+        let exports = find_export(&self.modules, module_path, name)?;
+
+        if self.exports.contains(alias) && self.exports.find_item(alias) != exports.find_item(name) {
+            return Err(TypeError::new(Span::new(0, 0, 1, 1), ErrorKind::ExportConflict(alias.to_owned())));
+        }
+
+        self.exports.bind_from(exports, name, alias);
+        Ok(())
+    }
+
+    /// re-exports every export of the module at `module_path`
+    pub fn reexport_all(&mut self, module_path: &str) -> TypeCheckResult<()> {
+        let names = match self.find_module_exports(module_path) {
+            Some(names) => names,
+            None => return Err(TypeError::new(Span::new(0, 0, 1, 1), ErrorKind::ModuleNotLoaded(module_path.to_owned()))),
+        };
+
+        for name in names {
+            self.reexport(module_path, &name, &name)?;
+        }
+
+        Ok(())
+    }
+
+    /// whether the module at `module_path` has been checked and its interface is known
+    pub fn has_module(&self, module_path: &str) -> bool {
+        self.modules.contains_key(module_path)
+    }
+
+    /// binds the exported `name` of the module at `module_path` into the current scope as `alias`
+    /// the bound type, symbol and constant value are shared with the exporting module
+    pub fn import_as(&mut self, module_path: &str, name: &str, alias: &str) -> TypeCheckResult<()> {
+        let exports = find_export(&self.modules, module_path, name)?;
+
+        let scope = &mut self.scopes[0];
+        if scope.contains(alias) {
+            // the same item imported twice is fine, a different one with the same name is not
+            if scope.imports.contains(alias) && scope.find_item(alias) == exports.find_item(name) {
+                return Ok(());
+            }
+            return Err(TypeError::new(Span::new(0, 0, 1, 1), ErrorKind::SymbolNameAlreadyUsed(alias.to_owned())));
+        }
+
+        scope.bind_from(exports, name, alias);
+        scope.imports.insert(alias.to_owned());
+        Ok(())
+    }
+
+    /// binds `alias` into the current scope as an opaque type and an untyped symbol
+    /// used for items of modules that failed to load or parse, so their importers do not report them again
+    pub fn import_unknown(&mut self, alias: &str) -> TypeCheckResult<()> {
+        if self.scopes[0].contains(alias) {
+            return Err(TypeError::new(Span::new(0, 0, 1, 1), ErrorKind::SymbolNameAlreadyUsed(alias.to_owned())));
+        }
+
+        let type_ref = self.types.create_type(alias)?;
+        let scope = &mut self.scopes[0];
+        scope.bind_unknown(alias, type_ref);
+        scope.imports.insert(alias.to_owned());
+        Ok(())
+    }
+
+    /// like `import_unknown`, but exports `alias` without binding it in the current scope
+    pub fn reexport_unknown(&mut self, alias: &str) -> TypeCheckResult<()> {
+        if self.exports.contains(alias) {
+            return Err(TypeError::new(Span::new(0, 0, 1, 1), ErrorKind::ExportConflict(alias.to_owned())));
+        }
+
+        let type_ref = self.types.create_type(alias)?;
+        self.exports.bind_unknown(alias, type_ref);
+        Ok(())
+    }
+
+    /// makes the exports of the module at `module_path` accessible as `namespace::name`
+    pub fn import_namespace(&mut self, module_path: &str, namespace: &str) -> TypeCheckResult<()> {
+        if !self.has_module(module_path) {
+            return Err(TypeError::new(Span::new(0, 0, 1, 1), ErrorKind::ModuleNotLoaded(module_path.to_owned())));
+        }
+
+        let scope = &mut self.scopes[0];
+        if scope.contains(namespace) {
+            if scope.namespaces.get(namespace).map(|p| p == module_path).unwrap_or(false) {
+                return Ok(());
+            }
+            return Err(TypeError::new(Span::new(0, 0, 1, 1), ErrorKind::SymbolNameAlreadyUsed(namespace.to_owned())));
+        }
+
+        scope.namespaces.insert(namespace.to_owned(), module_path.to_owned());
+        scope.imports.insert(namespace.to_owned());
+        Ok(())
+    }
+
+    /// exported names of the module at `module_path`, sorted by name
+    pub fn find_module_exports(&self, module_path: &str) -> Option<Vec<String>> {
+        let module = self.modules.get(module_path)?;
+        Some(module.exports.names(true))
+    }
+
+    /// enters the scope holding the items and imports of a module
+    pub fn enter_module_scope(&mut self) {
+        self.enter_scope();
+        self.exports = Scope::new();
+        self.in_module = true;
+    }
+
+    /// whether the items checked are in a module scope, items outside of it belong to the core module
+    pub fn is_in_module_scope(&self) -> bool {
+        self.in_module
+    }
+
+    /// leaves the current module scope and keeps it as the interface of the module at `module_path`
+    pub fn leave_module_scope(&mut self, module_path: &str) {
+        let scope = self.scopes.remove(0);
+        let exports = ::std::mem::replace(&mut self.exports, Scope::new());
+        self.in_module = false;
+        self.modules.insert(module_path.to_owned(), ModuleInterface {
+            scope: scope,
+            exports: exports,
+        });
     }
 
     pub fn enter_scope(&mut self) {
@@ -224,6 +662,38 @@ impl SymbolTable {
     }
 }
 
+/// levenshtein distance between two names
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..b.len() + 1).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = previous[j] + if ca == cb { 0 } else { 1 };
+            let value = ::std::cmp::min(substitution, ::std::cmp::min(previous[j + 1] + 1, current[j] + 1));
+            current.push(value);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// element type name and length of an array type name like `[Light; 4]`
+fn parse_array_type_name(name: &str) -> Option<(&str, u32)> {
+    if !name.starts_with('[') || !name.ends_with(']') {
+        return None;
+    }
+
+    let inner = &name[1..name.len() - 1];
+    let separator = inner.rfind("; ")?;
+    match inner[separator + 2..].parse() {
+        Ok(length) if length > 0 => Some((&inner[..separator], length)),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -232,9 +702,9 @@ mod tests {
     fn add_and_find_symbol() {
         let mut symbols = SymbolTable::new(TypeEnvironment::new());
 
-        symbols.add_symbol("test_symbol").unwrap();
+        symbols.add_symbol("test_symbol", SymbolKind::Local).unwrap();
 
-        assert_eq!(symbols.find_symbol("test_symbol"), Some(&Symbol::new("test_symbol", SymbolState::Free)));
+        assert_eq!(symbols.find_symbol("test_symbol"), Some(&Symbol::new("test_symbol", SymbolState::Free, SymbolKind::Local)));
     }
 
     #[test]
@@ -248,17 +718,17 @@ mod tests {
     #[test]
     fn add_enter_then_find_symbol() {
         let mut symbols = SymbolTable::new(TypeEnvironment::new());
-        symbols.add_symbol("test_symbol").unwrap();
+        symbols.add_symbol("test_symbol", SymbolKind::Local).unwrap();
         symbols.enter_scope();
 
-        assert_eq!(symbols.find_symbol("test_symbol"), Some(&Symbol::new("test_symbol", SymbolState::Free)));
+        assert_eq!(symbols.find_symbol("test_symbol"), Some(&Symbol::new("test_symbol", SymbolState::Free, SymbolKind::Local)));
     }
 
     #[test]
     fn enter_add_leave_then_dont_find_symbol() {
         let mut symbols = SymbolTable::new(TypeEnvironment::new());
         symbols.enter_scope();
-        symbols.add_symbol("test_symbol").unwrap();
+        symbols.add_symbol("test_symbol", SymbolKind::Local).unwrap();
         symbols.leave_scope();
 
         assert_eq!(symbols.find_symbol("test_symbol"), None);
@@ -270,6 +740,174 @@ mod tests {
         symbols.leave_scope();
     }
 
+    #[test]
+    fn add_and_find_constant_value() {
+        let mut symbols = SymbolTable::new(TypeEnvironment::new());
+        symbols.add_constant_value("LIGHT_COUNT", ConstantValue::Int(8)).unwrap();
+        symbols.enter_scope();
+
+        assert_eq!(symbols.find_constant_value("LIGHT_COUNT"), Some(ConstantValue::Int(8)));
+        assert!(symbols.add_constant_value("LIGHT_COUNT", ConstantValue::Int(4)).is_ok());
+    }
+
+    #[test]
+    fn suggest_similar_symbol() {
+        let mut symbols = SymbolTable::new(TypeEnvironment::new());
+        symbols.add_symbol("input", SymbolKind::Argument).unwrap();
+        symbols.add_symbol("output", SymbolKind::Local).unwrap();
+
+        assert_eq!(symbols.find_similar_symbol("intput"), Some("input"));
+        assert_eq!(symbols.find_similar_symbol("color"), None);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn add_and_find_builtins() {
+        let mut symbols = SymbolTable::new(TypeEnvironment::new());
+        symbols.add_builtin(StageBuiltin::new("vertex_index", TypeReference::new(0), vec![StageKind::Vertex], vec![])).unwrap();
+        symbols.add_builtin(StageBuiltin::new("frag_depth", TypeReference::new(1), vec![], vec![StageKind::Fragment])).unwrap();
+
+        assert!(symbols.add_builtin(StageBuiltin::new("vertex_index", TypeReference::new(0), vec![], vec![])).is_err());
+        assert_eq!(symbols.find_stage_inputs(StageKind::Vertex).len(), 1);
+        assert!(symbols.find_stage_inputs(StageKind::Fragment).is_empty());
+        assert!(symbols.find_builtin("frag_depth").unwrap().is_output_of(StageKind::Fragment));
+    }
+
+    #[test]
+    fn find_type_shape_of_struct() {
+        use ::type_system::structure_members::{ StructureMembers, StructureMember };
+
+        let mut symbols = SymbolTable::new(TypeEnvironment::new());
+        let f32_type = symbols.create_global_type("f32").unwrap();
+        let light_type = symbols.create_type("Light").unwrap();
+        let recursive_type = symbols.create_type("Recursive").unwrap();
+        symbols.find_type_mut(light_type).unwrap().set_members(StructureMembers::new(vec![StructureMember::new("intensity".to_string(), f32_type)])).unwrap();
+        symbols.find_type_mut(recursive_type).unwrap().set_members(StructureMembers::new(vec![StructureMember::new("next".to_string(), recursive_type)])).unwrap();
+
+        assert_eq!(symbols.find_type_shape(light_type), Some(TypeShape::Struct(vec![("intensity".to_string(), TypeShape::Scalar(4))])));
+        assert_eq!(symbols.find_type_shape(recursive_type), None);
+    }
+
+    #[test]
+    fn resolve_array_types() {
+        let mut symbols = SymbolTable::new(TypeEnvironment::new());
+        let f32_type = symbols.create_global_type("f32").unwrap();
+
+        let array_type = symbols.resolve_type_name("[[f32; 2]; 3]").unwrap();
+        let element_type = symbols.find_type(array_type).unwrap().get_array().unwrap().0;
+
+        assert_eq!(symbols.resolve_type_name("[[f32; 2]; 3]"), Some(array_type));
+        assert_eq!(symbols.find_type(element_type).unwrap().get_array(), Some((f32_type, 2)));
+        assert_eq!(symbols.find_type_shape(array_type), Some(TypeShape::Array(Box::new(TypeShape::Array(Box::new(TypeShape::Scalar(4)), 2)), 3)));
+        assert_eq!(symbols.resolve_type_name("[f32; 0]"), None);
+        assert_eq!(symbols.resolve_type_name("[Unknown; 2]"), None);
+    }
+
+    #[test]
+    fn nested_structures_are_assignable() {
+        use ::type_system::structure_members::{ StructureMembers, StructureMember };
+
+        let mut symbols = SymbolTable::new(TypeEnvironment::new());
+        let f32_type = symbols.create_global_type("f32").unwrap();
+        let types: Vec<TypeReference> = ["InnerA", "InnerB", "OuterA", "OuterB"].iter().map(|name| symbols.create_type(name).unwrap()).collect();
+        let arrays: Vec<TypeReference> = ["[InnerA; 2]", "[InnerB; 2]"].iter().map(|name| symbols.resolve_type_name(name).unwrap()).collect();
+
+        for &inner in &types[..2] {
+            symbols.find_type_mut(inner).unwrap().set_members(StructureMembers::new(vec![StructureMember::new("intensity".to_string(), f32_type)])).unwrap();
+        }
+        for (&outer, &array) in types[2..].iter().zip(arrays.iter()) {
+            symbols.find_type_mut(outer).unwrap().set_members(StructureMembers::new(vec![StructureMember::new("lights".to_string(), array)])).unwrap();
+        }
+
+        assert!(symbols.is_assignable(types[2], types[3]));
+        assert!(!symbols.is_assignable(types[2], types[0]));
+        assert!(!symbols.is_assignable(f32_type, types[0]));
+    }
+
+    #[test]
+    fn import_exported_names() {
+        let mut symbols = SymbolTable::new(TypeEnvironment::new());
+        symbols.enter_module_scope();
+        let light_type = symbols.create_type("Light").unwrap();
+        symbols.create_type("Hidden").unwrap();
+        symbols.add_symbol_with_type("PI", light_type, SymbolKind::Constant).unwrap();
+        symbols.add_constant_value("PI", ConstantValue::Int(3)).unwrap();
+        symbols.export("Light").unwrap();
+        symbols.export("PI").unwrap();
+        assert!(symbols.export("Missing").is_err());
+        symbols.leave_module_scope("lighting.xs");
+
+        symbols.enter_module_scope();
+        symbols.import_as("lighting.xs", "Light", "Light").unwrap();
+        symbols.import_as("lighting.xs", "Light", "Light").unwrap();
+        symbols.import_as("lighting.xs", "PI", "PI").unwrap();
+
+        assert_eq!(symbols.find_type_ref("Light"), Some(light_type));
+        assert_eq!(symbols.find_constant_value("PI"), Some(ConstantValue::Int(3)));
+        assert_eq!(symbols.import_as("lighting.xs", "Hidden", "Hidden").unwrap_err().get_kind(), &ErrorKind::ImportNotExported("Hidden".to_owned(), "lighting.xs".to_owned()));
+        assert_eq!(symbols.import_as("lighting.xs", "Shadow", "Shadow").unwrap_err().get_kind(), &ErrorKind::ImportNotFound("Shadow".to_owned(), "lighting.xs".to_owned()));
+        assert_eq!(symbols.find_module_exports("lighting.xs"), Some(vec!["Light".to_owned(), "PI".to_owned()]));
+
+        symbols.export_all().unwrap();
+        symbols.leave_module_scope("main.xs");
+        assert_eq!(symbols.find_module_exports("main.xs"), Some(vec![]));
+    }
+
+    #[test]
+    fn import_aliases_and_namespaces() {
+        let mut symbols = SymbolTable::new(TypeEnvironment::new());
+        let f32_type = symbols.create_global_type("f32").unwrap();
+        symbols.enter_module_scope();
+        let point_light = symbols.create_type("Light").unwrap();
+        symbols.add_symbol_with_type("PI", f32_type, SymbolKind::Constant).unwrap();
+        symbols.add_constant_value("PI", ConstantValue::Float(3.0)).unwrap();
+        symbols.create_type("Hidden").unwrap();
+        symbols.export("Light").unwrap();
+        symbols.export("PI").unwrap();
+        symbols.leave_module_scope("point.xs");
+        symbols.enter_module_scope();
+        let spot_light = symbols.create_type("Light").unwrap();
+        symbols.export("Light").unwrap();
+        symbols.leave_module_scope("spot.xs");
+
+        symbols.enter_module_scope();
+        symbols.import_as("point.xs", "Light", "PointLight").unwrap();
+        symbols.import_as("spot.xs", "Light", "Light").unwrap();
+        symbols.import_namespace("point.xs", "point").unwrap();
+
+        assert_eq!(symbols.find_type_ref("PointLight"), Some(point_light));
+        assert_eq!(symbols.find_type_ref("Light"), Some(spot_light));
+        assert_eq!(symbols.find_type_ref("point::Light"), Some(point_light));
+        assert_eq!(symbols.find_type_ref("point::Hidden"), None);
+        assert_eq!(symbols.find_constant_value("point::PI"), Some(ConstantValue::Float(3.0)));
+        assert_eq!(symbols.find_symbol("point::PI").map(|s| s.kind), Some(SymbolKind::Constant));
+        assert_ne!(symbols.resolve_type_name("[point::Light; 2]"), symbols.resolve_type_name("[Light; 2]"));
+        assert!(symbols.import_namespace("spot.xs", "point").is_err());
+        assert!(symbols.import_as("point.xs", "Light", "Light").is_err());
+    }
+
+    #[test]
+    fn reexport_items_of_other_modules() {
+        let mut symbols = SymbolTable::new(TypeEnvironment::new());
+        for &(module_path, name) in &[("a.xs", "Light"), ("b.xs", "Light"), ("c.xs", "Shadow")] {
+            symbols.enter_module_scope();
+            symbols.create_type(name).unwrap();
+            symbols.export(name).unwrap();
+            symbols.leave_module_scope(module_path);
+        }
+
+        symbols.enter_module_scope();
+        symbols.reexport("a.xs", "Light", "Light").unwrap();
+        symbols.reexport_all("a.xs").unwrap();
+        symbols.reexport("b.xs", "Light", "SpotLight").unwrap();
+        symbols.reexport_all("c.xs").unwrap();
+        assert_eq!(symbols.reexport_all("b.xs").unwrap_err().get_kind(), &ErrorKind::ExportConflict("Light".to_owned()));
+        assert_eq!(symbols.find_type_ref("Light"), None);
+        symbols.leave_module_scope("facade.xs");
+
+        assert_eq!(symbols.find_module_exports("facade.xs"), Some(vec!["Light".to_owned(), "Shadow".to_owned(), "SpotLight".to_owned()]));
+    }
+
     #[test]
     fn add_type() {
         let reference = TypeReference::new(0);
@@ -1,4 +1,4 @@
-use ::ast::Span;
+use ::ast::{ Span, Operator };
 use ::type_system::call_signature::CallSignature;
 use ::type_system::structure_members::StructureMembers;
 use ::type_system::error::{ TypeError, ErrorKind, TypeCheckResult };
@@ -14,6 +14,10 @@ pub struct TypeDefinition {
     call_signature: Option<CallSignature>,
     // member: Option<Vec<TypeReference>>,
     member: Option<StructureMembers>,
+    /// element type and length of an array type
+    array: Option<(TypeReference, u32)>,
+    /// right hand and result type of the operators declared with this type on the left hand side
+    operators: Vec<(Operator, TypeReference, TypeReference)>,
 }
 
 impl TypeDefinition {
@@ -25,6 +29,8 @@ impl TypeDefinition {
             explicit_casts: Vec::new(),
             call_signature: None,
             member: None,
+            array: None,
+            operators: Vec::new(),
         }
     }
 
@@ -71,6 +77,18 @@ impl TypeDefinition {
         }
     }
 
+    pub fn make_array(&mut self, element_type: TypeReference, length: u32) {
+        self.array = Some((element_type, length));
+    }
+
+    pub fn get_array(&self) -> Option<(TypeReference, u32)> {
+        self.array
+    }
+
+    pub fn is_array(&self) -> bool {
+        self.array.is_some()
+    }
+
     pub fn make_callable(&mut self, signature: CallSignature) -> TypeCheckResult<()> {
         if self.is_callable() {
             return Err(TypeError::new(Span::empty(), ErrorKind::CannotMakeCallable));
@@ -121,6 +139,15 @@ impl TypeDefinition {
     pub fn add_explicit_cast(&mut self, other: TypeReference) {
         self.explicit_casts.push(other);
     }
+
+    pub fn add_operator(&mut self, operator: Operator, right_hand: TypeReference, result: TypeReference) {
+        self.operators.push((operator, right_hand, result));
+    }
+
+    /// result type of `self <operator> right_hand`, `None` if no such operator is declared
+    pub fn find_operator_type(&self, operator: Operator, right_hand: TypeReference) -> Option<TypeReference> {
+        self.operators.iter().find(|o| o.0 == operator && o.1 == right_hand).map(|o| o.2)
+    }
 }
 
 impl PartialEq for TypeDefinition {
@@ -141,4 +168,16 @@ mod tests {
 
         assert_eq!(a, b);
     }
+
+    #[test]
+    fn operator_type_depends_on_operator_and_right_hand() {
+        let f32_type = TypeReference::new(0);
+        let vec4_type = TypeReference::new(1);
+        let mut vec4 = TypeDefinition::new(1, "vec4");
+        vec4.add_operator(Operator::Multiply, f32_type, vec4_type);
+
+        assert_eq!(vec4.find_operator_type(Operator::Multiply, f32_type), Some(vec4_type));
+        assert_eq!(vec4.find_operator_type(Operator::Multiply, vec4_type), None);
+        assert_eq!(vec4.find_operator_type(Operator::Plus, f32_type), None);
+    }
 }
@@ -1,9 +1,8 @@
-use ::std::env;
-use ::std::fs::File;
+use ::std::fs::{ self, File };
 use ::std::io::prelude::*;
-use ::std::error::Error;
-use ::xshade::ModuleResolver;
+use ::xshade::{ ModuleResolver, ResolvedModule, ResolveError };
 
+/// modules read from the file system, canonical paths are absolute
 pub struct FileResolver;
 
 impl FileResolver {
@@ -13,12 +12,19 @@ impl FileResolver {
 }
 
 impl ModuleResolver for FileResolver {
-    fn resolve(&mut self, module_path: &str) -> Result<String, Box<Error>> {
+    fn resolve(&mut self, module_path: &str) -> Result<ResolvedModule, ResolveError> {
+        let path = self.canonicalize(module_path)?;
+        let mut source = String::new();
+        match File::open(&path).and_then(|mut f| f.read_to_string(&mut source)) {
+            Ok(_) => Ok(ResolvedModule { path: path, source: source }),
+            Err(error) => Err(ResolveError::Io(path, error)),
+        }
+    }
 
-        let mut f = File::open(module_path).expect("file not found");
-        let mut contents = String::new();
-        f.read_to_string(&mut contents)
-            .expect("something went wrong reading the file");
-        Ok(contents)
+    fn canonicalize(&mut self, module_path: &str) -> Result<String, ResolveError> {
+        match fs::canonicalize(module_path) {
+            Ok(ref path) if path.is_file() => Ok(path.to_string_lossy().replace('\\', "/")),
+            _ => Err(ResolveError::NotFound(module_path.to_owned())),
+        }
     }
 }
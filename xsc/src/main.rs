@@ -3,12 +3,17 @@ extern crate getopts;
 
 use getopts::Options;
 use std::env;
+use std::error::Error;
+use std::fs::File;
+use std::io::Write;
+use std::process;
 use xshade::*;
 
 mod file_resolver;
 
 fn get_span_line(lines: &Vec<&str>, span: Span) -> String {
-    lines[span.line - 1].to_owned()
+    // a span at the end of a source ending with a newline is on a line of its own
+    lines.get(span.line - 1).map(|line| line.to_string()).unwrap_or_default()
 }
 
 fn create_span_marker(span: Span) -> String {
@@ -49,79 +54,255 @@ fn single_span_error(line: &str, span: Span, path: &str, top: String, bottom: St
 
     let indent = span.line.to_string().len();
 
-    println!("{}", top);
-    println!("error:");
-    println!("{}", path);
-    println!("{}", prefix(&line, indent, &span.line.to_string()));
-    println!("{}", prefix(&marker, indent, ""));
-    println!("{}", bottom);
+    eprintln!("{}", top);
+    eprintln!("error:");
+    eprintln!("{}", path);
+    eprintln!("{}", prefix(&line, indent, &span.line.to_string()));
+    eprintln!("{}", prefix(&marker, indent, ""));
+    eprintln!("{}", bottom);
+}
+
+fn print_usage(program: &str, options: &Options) {
+    let brief = format!("Usage: {} [options] MODULE\n       {} bundle [options] DIRECTORY", program, program);
+    print!("{}", options.usage(&brief));
+}
+
+fn parse_layout_rule(rule: &str) -> Option<LayoutRule> {
+    match rule {
+        "std140" => Some(LayoutRule::Std140),
+        "std430" => Some(LayoutRule::Std430),
+        "scalar" => Some(LayoutRule::Scalar),
+        _ => None,
+    }
+}
+
+fn write_output(output_path: Option<String>, content: &str) {
+    match output_path {
+        Some(path) => {
+            let mut file = File::create(&path).expect("cannot create output file");
+            file.write_all(content.as_bytes()).expect("cannot write output file");
+        },
+        None => print!("{}", content),
+    }
+}
+
+fn print_error(module: &Module, error: &CompileError) {
+    let source = module.get_source();
+    let lines: Vec<&str> = source.lines().collect();
+
+    match error.get_kind() {
+        &CompileErrorKind::TypeError(ref type_error) => {
+            let path = create_path_with_span(module.get_path(), type_error.get_span());
+            match type_error.get_kind() {
+                &TypeErrorKind::TypeNotFound(ref type_name) => {
+                    let span = type_error.get_span();
+                    let line = get_span_line(&lines, span);
+                    
+                    single_span_error(&line, span, &path,
+                    format!("error: Type not found:"),
+                    format!("Type `{}` not found in current or any parent scope.", type_name));
+                }
+                &TypeErrorKind::TypeHasNoMember => {
+                    let span = type_error.get_span();
+                    let line = get_span_line(&lines, span);
+
+
+                    single_span_error(&line, span, &path,
+                    format!("error: Type has no members:"),
+                    format!("Type `{{}}` has no members."));
+                }
+                &TypeErrorKind::IncompatibleTypes(left, right) => {
+                    let left_line = get_span_line(&lines, left);
+                    let right_line = get_span_line(&lines, right);
+
+                    let left_marker = create_span_marker(left);
+                    let right_marker = create_span_marker(right);
+
+                    let indent = right.line.to_string().len();
+
+                    eprintln!("");
+                    eprintln!("error: Incompatible Types:");
+                    eprintln!("{}", path);
+                    eprintln!("{}", prefix(&left_line, indent, &left.line.to_string()));
+                    eprintln!("{}", prefix(&left_marker, indent, ""));
+                    eprintln!("{}", prefix(&right_line, indent, &right.line.to_string()));
+                    eprintln!("{}", prefix(&right_marker, indent, ""));
+                    eprintln!("Cannot use operator `{{}}` on type `{{}}` and `{{}}`.");
+                }
+                _ => {
+                    let span = type_error.get_span();
+                    let line = get_span_line(&lines, span);
+
+                    single_span_error(&line, span, &path,
+                    format!("error: {}", type_error.description()),
+                    format!("{}", type_error));
+                }
+            }
+        },
+        &CompileErrorKind::SyntaxError(ref message) => {
+            let span = error.get_span();
+            let line = get_span_line(&lines, span);
+            let path = create_path_with_span(module.get_path(), span);
+
+            single_span_error(&line, span, &path,
+            format!("error: Syntax error:"),
+            message.to_owned());
+        },
+        _ => print_compile_error(error),
+    }
+}
+
+fn print_import_cycle(cycle: &Vec<(String, Span)>) {
+    eprintln!("error: Import cycle:");
+    for (index, &(ref path, span)) in cycle.iter().enumerate() {
+        let imported = &cycle[(index + 1) % cycle.len()].0;
+        eprintln!("{} imports `{}`", create_path_with_span(path, span), imported);
+    }
+}
+
+/// errors without a span in a module source
+fn print_compile_error(error: &CompileError) {
+    match error.get_kind() {
+        &CompileErrorKind::ImportCycle(ref cycle) => print_import_cycle(cycle),
+        _ => eprintln!("error: {}", error),
+    }
+}
+
+/// `xsc bundle`, compiles the programs of every module below a directory into an archive
+fn bundle_main(program: &str, args: &[String]) {
+    let mut options = Options::new();
+    options.optopt("o", "output", "write the archive to FILE", "FILE");
+    options.optmulti("", "rust-structs", "add #[repr(C)] rust structs of each program's module to the archive", "std140|std430|scalar");
+    options.optflag("h", "help", "print this help");
+
+    let matches = match options.parse(args) {
+        Ok(m) => m,
+        Err(error) => {
+            eprintln!("{}", error);
+            print_usage(program, &options);
+            process::exit(1);
+        }
+    };
+
+    if matches.opt_present("h") {
+        print_usage(program, &options);
+        return;
+    }
+
+    let output_path = match matches.opt_str("o") {
+        Some(output_path) if matches.free.len() == 1 => output_path,
+        _ => {
+            print_usage(program, &options);
+            process::exit(1);
+        }
+    };
+
+    let mut bundler = Bundler::new();
+    for rule in matches.opt_strs("rust-structs") {
+        match parse_layout_rule(&rule) {
+            Some(rule) => bundler.add_backend(Box::new(RustStructsBackend::new(rule))),
+            None => {
+                eprintln!("unknown layout rule `{}`", rule);
+                process::exit(1);
+            }
+        }
+    }
+
+    let archive = match bundler.bundle_directory(&matches.free[0]) {
+        Ok(archive) => archive,
+        Err(BundleError::Compile(errors)) => {
+            for &(ref module_path, ref error) in &errors {
+                eprintln!("error in `{}`:", module_path);
+                print_compile_error(error);
+            }
+            process::exit(1);
+        },
+        Err(error) => {
+            eprintln!("error: {}", error);
+            process::exit(1);
+        }
+    };
+
+    let mut file = File::create(&output_path).expect("cannot create output file");
+    file.write_all(&archive.to_bytes()).expect("cannot write output file");
+    println!("bundled {} programs into `{}`", archive.get_programs().len(), output_path);
 }
 
 pub fn main() {
     let args: Vec<String> = env::args().collect();
     let program = args[0].clone();
-    let module_path = args[1].to_string();
+
+    if args.len() > 1 && args[1] == "bundle" {
+        bundle_main(&program, &args[2..]);
+        return;
+    }
+
+    let mut options = Options::new();
+    options.optopt("o", "output", "write the output to FILE instead of stdout", "FILE");
+    options.optopt("", "rust-structs", "generate #[repr(C)] rust structs for the exported structs and uniform blocks", "std140|std430|scalar");
+    options.optmulti("I", "include", "search DIR for imported modules that are not found next to the importing module", "DIR");
+    options.optflag("h", "help", "print this help");
+
+    let matches = match options.parse(&args[1..]) {
+        Ok(m) => m,
+        Err(error) => {
+            eprintln!("{}", error);
+            print_usage(&program, &options);
+            process::exit(1);
+        }
+    };
+
+    if matches.opt_present("h") || matches.free.len() != 1 {
+        print_usage(&program, &options);
+        return;
+    }
+
+    let module_path = matches.free[0].to_string();
+    let layout_rule = match matches.opt_str("rust-structs") {
+        Some(rule) => match parse_layout_rule(&rule) {
+            Some(rule) => Some(rule),
+            None => {
+                eprintln!("unknown layout rule `{}`", rule);
+                process::exit(1);
+            }
+        },
+        None => None,
+    };
 
     let file_resolver = file_resolver::FileResolver::new();
     let mut compiler = Compiler::new(Box::new(file_resolver));
+    for root in matches.opt_strs("I") {
+        compiler.add_search_root(&root);
+    }
 
     match compiler.compile_module(&module_path) {
-        Ok(module) => {
-
-            if let Some(error) = module.get_error() {
-                let source = module.get_source();
-                let lines: Vec<&str> = source.lines().collect();
-
-                match error.get_kind() {
-                    &CompileErrorKind::TypeError(ref type_error) => {
-                        let path = create_path_with_span(module.get_path(), type_error.get_span());
-                        match type_error.get_kind() {
-                            &TypeErrorKind::TypeNotFound(ref type_name) => {
-                                let span = type_error.get_span();
-                                let line = get_span_line(&lines, span);
-                                
-                                single_span_error(&line, span, &path,
-                                format!("error: Type not found:"),
-                                format!("Type `{}` not found in current or any parent scope.", type_name));
-                            }
-                            &TypeErrorKind::TypeHasNoMember => {
-                                let span = type_error.get_span();
-                                let line = get_span_line(&lines, span);
-
-
-                                single_span_error(&line, span, &path,
-                                format!("error: Type has no members:"),
-                                format!("Type `{{}}` has no members."));
-                            }
-                            &TypeErrorKind::IncompatibleTypes(left, right) => {
-                                let left_line = get_span_line(&lines, left);
-                                let right_line = get_span_line(&lines, right);
-
-                                let left_marker = create_span_marker(left);
-                                let right_marker = create_span_marker(right);
-
-                                let indent = right.line.to_string().len();
-
-                                println!("");
-                                println!("error: Incompatible Types:");
-                                println!("{}", path);
-                                println!("{}", prefix(&left_line, indent, &left.line.to_string()));
-                                println!("{}", prefix(&left_marker, indent, ""));
-                                println!("{}", prefix(&right_line, indent, &right.line.to_string()));
-                                println!("{}", prefix(&right_marker, indent, ""));
-                                println!("Cannot use operator `{{}}` on type `{{}}` and `{{}}`.");
-                            }
-                            _ => println!("{:#?}", error),
-                        }
-                    },
-                    _ => println!("{:#?}", error),
+        Ok(compilation) => {
+            let module = compilation.get_module();
+
+            let errors = compilation.get_errors();
+            if !errors.is_empty() {
+                for (module, error) in errors.iter().cloned() {
+                    print_error(module, error);
                 }
-            } else {
-                println!("{:#?}", module);
+                eprintln!("");
+                eprintln!("{} errors found", errors.len());
+                process::exit(1);
+            }
+
+            match layout_rule {
+                Some(rule) => match compilation.generate_rust_structs(rule) {
+                    Ok(source) => write_output(matches.opt_str("o"), &source),
+                    Err(error) => {
+                        print_error(module, &error);
+                        process::exit(1);
+                    }
+                },
+                None => println!("{:#?}", module),
             }
         },
         Err(error) => {
-            println!("{:#?}", error);
+            print_compile_error(&error);
+            process::exit(1);
         }
     }
-}
\ No newline at end of file
+}